@@ -1,8 +1,15 @@
+use std::fmt::Display;
 use std::fs::File;
-use std::net::Ipv4Addr;
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddrV4};
 use std::path::Path;
+use std::time::Duration;
 
-use adb_client::{AdbTcpConnexion, Device, RebootType, RustADBError};
+use adb_client::{
+    AdbSshTunnel, AdbTcpConnexion, DebugServer, DebugTarget, Device, DeviceSelector, ProfileTarget,
+    RebootType, ReconnectTarget, RustADBError, ShellEnvironment, SimpleperfOptions, TransferFilter,
+    UserId, WifiPairing,
+};
 use clap::Parser;
 
 #[derive(Parser, Debug)]
@@ -17,6 +24,40 @@ pub struct Args {
     /// Serial id of a specific device. Every request will be sent to this device.
     #[clap(short = 's', long = "serial")]
     pub serial: Option<String>,
+    /// Sends every request to the only device connected over USB.
+    #[clap(short = 'd', conflicts_with_all = ["serial", "emulator", "transport_id"])]
+    pub usb: bool,
+    /// Sends every request to the only device connected over TCP/IP.
+    #[clap(short = 'e', conflicts_with_all = ["serial", "transport_id"])]
+    pub emulator: bool,
+    /// Transport id of a specific device. Every request will be sent to this device.
+    #[clap(short = 't', long = "transport-id", conflicts_with = "serial")]
+    pub transport_id: Option<u32>,
+    /// Records bytes exchanged with ADB server into this session file.
+    #[clap(long = "record")]
+    pub record: Option<String>,
+    /// Replays the session recorded in this file instead of connecting to ADB server.
+    #[clap(long = "replay", conflicts_with = "record")]
+    pub replay: Option<String>,
+    /// Logs bytes exchanged with ADB server into this file, in a format similar to 'ADB_TRACE=all' logs of adb.
+    #[clap(long = "trace", conflicts_with_all = ["record", "replay"])]
+    pub trace: Option<String>,
+    /// Reaches ADB server through an SSH tunnel to this host ('[user@]host'), address and port being those on that host.
+    #[clap(long = "ssh", conflicts_with = "replay")]
+    pub ssh: Option<String>,
+    /// Limits the throughput of file transfers to this number of bytes per second.
+    #[clap(long = "rate-limit")]
+    pub rate_limit: Option<u64>,
+    /// Targets this user with package installs, run-as and app storage, instead of all users and the foreground one.
+    #[clap(long = "target-user")]
+    pub target_user: Option<UserId>,
+    /// Transfers files with 'cat' when the sync service is denied access to them, e.g. by SELinux.
+    #[clap(long = "shell-fallback")]
+    pub shell_fallback: bool,
+    /// Prints results as JSON, one value per line, for scripts.
+    #[cfg(feature = "serde")]
+    #[clap(long = "json")]
+    pub json: bool,
     #[clap(subcommand)]
     pub command: Command,
 }
@@ -27,25 +68,260 @@ pub enum Command {
     Version,
     /// Asks ADB server to quit immediately.
     Kill,
+    /// Resets the connection to the device, from ADB server side unless 'target' is given.
+    Reconnect {
+        #[clap(subcommand)]
+        target: Option<ReconnectTargetCommand>,
+    },
+    /// Disconnects from all devices connected over TCP/IP.
+    DisconnectAll,
+    /// Connects to the device accepting connections on 'address' (ip:port).
+    Connect { address: SocketAddrV4 },
+    /// Pairs with the device waiting to be paired on 'address' (ip:port) using 'code'.
+    Pair { address: SocketAddrV4, code: String },
+    /// Prints a QR code payload to pair devices with, and pairs with the first device scanning it within 'timeout' seconds.
+    PairQr {
+        #[clap(default_value_t = 120)]
+        timeout: u64,
+    },
+    /// Lists ADB services advertised over mDNS.
+    MdnsServices,
     /// List connected devices.
     Devices {
         #[clap(short = 'l', long = "long")]
         long: bool,
     },
     /// Tracks new devices showing up.
-    TrackDevices,
+    TrackDevices {
+        /// Prints device changes once stable for this many milliseconds, instead of raw updates
+        #[clap(long = "debounce")]
+        debounce: Option<u64>,
+        /// Re-establishes tracking when lost or silent for this many seconds
+        #[clap(long = "liveness")]
+        liveness: Option<u64>,
+    },
     /// Lists available server features.
     HostFeatures,
     /// Pushes 'filename' to the 'path' on device
-    Push { filename: String, path: String },
+    Push {
+        filename: String,
+        path: String,
+        /// Pushes into a temporary file renamed to 'path' once complete
+        #[clap(long = "atomic")]
+        atomic: bool,
+        /// Skips zero regions of 'filename', leaving holes in 'path', e.g. for disk images
+        #[clap(long = "sparse", conflicts_with = "atomic")]
+        sparse: bool,
+    },
     /// Pushes 'path' on the device to 'filename'
-    Pull { path: String, filename: String },
+    Pull {
+        path: String,
+        filename: String,
+        /// Keeps data already downloaded to 'filename' by a previous interrupted pull
+        #[clap(long = "resume")]
+        resume: bool,
+        /// Reads distinct ranges of 'path' over this many concurrent connections
+        #[clap(long = "parallel", conflicts_with = "resume")]
+        parallel: Option<usize>,
+    },
+    /// Pulls the content of directory 'path' on device into 'directory' as a tar stream
+    PullTar {
+        path: String,
+        directory: String,
+        #[clap(flatten)]
+        filter: FilterArgs,
+    },
+    /// Pushes the content of 'directory' into directory 'path' on device as a tar stream
+    PushTar {
+        directory: String,
+        path: String,
+        #[clap(flatten)]
+        filter: FilterArgs,
+    },
+    /// Prints space usage of filesystems, or of 'path' and its filesystem, on device
+    Df { path: Option<String> },
     /// List files for 'path' on device
     List { path: String },
     /// Stat file specified as 'path' on device
     Stat { path: String },
     /// Run 'command' in a shell on the device, and return its output and error streams.
-    Shell { command: Vec<String> },
+    Shell {
+        command: Vec<String>,
+        /// Feeds the content of 'input' file ('-' for stdin) to the command
+        #[clap(short = 'i', long = "input")]
+        input: Option<String>,
+        /// Runs the command in 'cwd' directory, failing if it does not exist
+        #[clap(long = "cwd")]
+        cwd: Option<String>,
+        /// Exports variable to the command, as 'NAME=value' (can be repeated)
+        #[clap(short = 'e', long = "env", value_parser = parse_variable)]
+        env: Vec<(String, String)>,
+    },
+    /// Runs 'command' on the device, bridging its standard input and output to local ones
+    Bridge { command: Vec<String> },
+    /// Pushes the OBB file 'filename' for 'package' on device
+    PushObb { package: String, filename: String },
+    /// Prints network state of the device
+    NetworkStatus,
+    /// Prints API level of the device, and the command syntax selected for it
+    ApiLevel,
+    /// Forwards 'local' endpoint on host to 'remote' endpoint on device (e.g. tcp:8080 localabstract:name)
+    Forward {
+        local: String,
+        remote: String,
+        /// Fails if 'local' is already forwarded
+        #[clap(long = "no-rebind")]
+        no_rebind: bool,
+    },
+    /// Starts debug server 'binary' on 'port' of the device, debugging 'pid' or 'command', and prints the forwarded local endpoint
+    Debug {
+        binary: String,
+        port: u16,
+        command: Vec<String>,
+        /// Attaches to the running process 'pid' instead of starting 'command'
+        #[clap(long = "pid")]
+        pid: Option<u32>,
+        /// Runs 'binary' as lldb-server instead of gdbserver
+        #[clap(long = "lldb")]
+        lldb: bool,
+    },
+    /// Profiles 'pid' or 'package' with simpleperf, and downloads the recording as 'filename'
+    Simpleperf {
+        filename: String,
+        #[clap(long = "pid", required_unless_present = "package")]
+        pid: Option<u32>,
+        #[clap(long = "package", conflicts_with = "pid")]
+        package: Option<String>,
+        /// Event to record, can be repeated
+        #[clap(short = 'e', long = "event")]
+        events: Vec<String>,
+        /// Duration of the recording in seconds
+        #[clap(long = "duration", default_value_t = 10)]
+        duration: u64,
+        /// Records call graphs
+        #[clap(short = 'g')]
+        call_graph: bool,
+        /// Prints the report of the recording
+        #[clap(long = "report")]
+        report: bool,
+    },
+    /// Prints memory usage of 'process' (pid or name)
+    MemInfo { process: String },
+    /// Dumps the Java heap of 'process' (pid or name) into 'filename'
+    DumpHeap { process: String, filename: String },
+    /// Gathers ANR traces, tombstones and crash log of the last 'since' seconds into 'directory'
+    CrashArtifacts {
+        directory: String,
+        #[clap(long = "since", default_value_t = 3600)]
+        since: u64,
+    },
+    /// Waits for the device to be fully booted, for at most 'timeout' seconds
+    WaitForBoot {
+        #[clap(default_value_t = 300)]
+        timeout: u64,
+    },
+    /// Waits for debugging to be allowed on the device, for at most 'timeout' seconds
+    WaitForAuthorization {
+        #[clap(default_value_t = 120)]
+        timeout: u64,
+    },
+    /// Sets the location reported by the device
+    #[clap(allow_negative_numbers = true)]
+    SetLocation {
+        latitude: f64,
+        longitude: f64,
+        altitude: Option<f64>,
+    },
+    /// Runs 'command' on the console of the emulator
+    Emu { command: Vec<String> },
+    /// Lists users and profiles of the device
+    Users,
+    /// Creates a user named 'name', or a work profile of user 'profile-of'
+    CreateUser {
+        name: String,
+        #[clap(long = "profile-of")]
+        profile_of: Option<UserId>,
+    },
+    /// Removes user 'user' and all its data
+    RemoveUser { user: UserId },
+    /// Switches the foreground user to 'user'
+    SwitchUser { user: UserId },
+    /// Prints the id of the foreground user
+    CurrentUser,
+    /// Installs an already installed 'package' for user 'user'
+    InstallExisting {
+        package: String,
+        #[clap(long = "user")]
+        user: UserId,
+    },
+    /// Grants runtime 'permissions' to 'package', printing the ones which could not be granted
+    Grant {
+        package: String,
+        permissions: Vec<String>,
+    },
+    /// Prints the UI hierarchy currently displayed
+    UiDump,
+    /// Tunnels a local port to 'host:port' as reached from the device, until interrupted
+    Tunnel { host: String, port: u16 },
+    /// Proxies a local port to 'remote' endpoint on the device without ADB server forwarding, until interrupted
+    ForwardListener { remote: String },
+    /// Prints the external storage path of the foreground user
+    ExternalStorage,
+    /// Prints verified boot state, bootloader lock, SELinux mode, security patch level and encryption state of the device
+    SecurityState,
+    /// Prints the locale of the device, or sets it to 'locale' language tag (e.g. fr-FR)
+    Locale { locale: Option<String> },
+    /// Lists forwardings to all devices
+    ForwardList,
+    /// Removes the forwarding of 'local' endpoint
+    ForwardRemove { local: String },
+    /// Removes forwardings to all devices
+    ForwardRemoveAll,
+    /// Forwards connections to 'remote' endpoint on device to 'local' endpoint on host (e.g. tcp:8080 tcp:8080)
+    Reverse {
+        remote: String,
+        local: String,
+        /// Fails if 'remote' is already forwarded
+        #[clap(long = "no-rebind")]
+        no_rebind: bool,
+    },
+    /// Removes the reverse forwarding of 'remote' endpoint on device
+    ReverseRemove { remote: String },
+    /// Runs a binder-based 'cmd' service call on device
+    Abb { args: Vec<String> },
+    /// Generates an ADB key pair, private key in 'path' and public key in 'path'.pub
+    Keygen { path: String },
+    /// Lists fingerprints of ADB keys
+    Fingerprints,
+    /// Authorizes this host's ADB key on a rooted device
+    AuthorizeKey,
+    /// Prints the lines of file 'path' on device
+    Tail {
+        path: String,
+        /// Keeps printing lines appended to the file
+        #[clap(short = 'f', long = "follow")]
+        follow: bool,
+    },
+    /// Prints the kernel log of the device
+    Dmesg {
+        /// Prints the kernel log of the previous boot instead
+        #[clap(long = "last")]
+        last: bool,
+    },
+    /// Installs the APK file 'filename' on device
+    Install {
+        filename: String,
+        /// Uses incremental installation, requires 'filename'.idsig signature file
+        #[clap(long = "incremental")]
+        incremental: bool,
+        /// Grants all runtime permissions requested by the APK
+        #[clap(short = 'g', long = "grant-all", conflicts_with = "incremental")]
+        grant_all: bool,
+    },
+    /// Prints the installed version of 'package' on device
+    PackageVersion { package: String },
+    /// Prints uid, install times, permissions, signatures and enabled state of 'package' installed on the device
+    PackageInfo { package: String },
     /// Reboots the device
     Reboot {
         #[clap(subcommand)]
@@ -62,6 +338,54 @@ pub enum RebootTypeCommand {
     SideloadAutoReboot,
 }
 
+#[derive(Parser, Debug)]
+pub struct FilterArgs {
+    /// Only transfers files matching this glob pattern, can be repeated
+    #[clap(long = "include")]
+    include: Vec<String>,
+    /// Skips files matching this glob pattern, can be repeated
+    #[clap(long = "exclude")]
+    exclude: Vec<String>,
+    /// Skips files matching the patterns of this ignore file (e.g. .adbignore)
+    #[clap(long = "ignore-file")]
+    ignore_file: Option<String>,
+}
+
+impl TryFrom<FilterArgs> for TransferFilter {
+    type Error = RustADBError;
+
+    fn try_from(value: FilterArgs) -> Result<Self, Self::Error> {
+        let mut filter = TransferFilter::new();
+        for pattern in &value.include {
+            filter = filter.include(pattern)?;
+        }
+        for pattern in &value.exclude {
+            filter = filter.exclude(pattern)?;
+        }
+        if let Some(ignore_file) = &value.ignore_file {
+            filter = filter.ignore_file(ignore_file)?;
+        }
+
+        Ok(filter)
+    }
+}
+
+/// Parses a 'NAME=value' environment variable of shell command.
+fn parse_variable(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=value, got '{value}'"))
+}
+
+#[derive(Parser, Debug)]
+pub enum ReconnectTargetCommand {
+    /// Asks the device to reset its connection
+    Device,
+    /// Resets connections to all offline devices
+    Offline,
+}
+
 impl From<RebootTypeCommand> for RebootType {
     fn from(value: RebootTypeCommand) -> Self {
         match value {
@@ -74,74 +398,578 @@ impl From<RebootTypeCommand> for RebootType {
     }
 }
 
+/// Results printed by the CLI, serializable to JSON when `serde` feature is enabled.
+#[cfg(feature = "serde")]
+trait Printable: serde::Serialize {}
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + ?Sized> Printable for T {}
+#[cfg(not(feature = "serde"))]
+trait Printable {}
+#[cfg(not(feature = "serde"))]
+impl<T: ?Sized> Printable for T {}
+
+/// Prints results as text, or as JSON with `--json`.
+#[derive(Debug)]
+struct Printer {
+    #[cfg(feature = "serde")]
+    json: bool,
+}
+
+impl Printer {
+    /// Prints `value` as JSON, or `text` otherwise.
+    #[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+    fn print<T: Printable + ?Sized>(
+        &self,
+        value: &T,
+        text: impl Display,
+    ) -> Result<(), RustADBError> {
+        #[cfg(feature = "serde")]
+        if self.json {
+            serde_json::to_writer(std::io::stdout(), value).map_err(std::io::Error::from)?;
+            println!();
+            return Ok(());
+        }
+
+        println!("{text}");
+        Ok(())
+    }
+
+    /// Prints `text` unless results are printed as JSON.
+    fn text(&self, text: impl Display) {
+        #[cfg(feature = "serde")]
+        if self.json {
+            return;
+        }
+
+        println!("{text}");
+    }
+
+    /// Prints `values` as a JSON array, or `header` followed by each value on its own line otherwise.
+    fn print_all<T: Printable + Display>(
+        &self,
+        header: Option<&str>,
+        values: &[T],
+    ) -> Result<(), RustADBError> {
+        #[cfg(feature = "serde")]
+        if self.json {
+            return self.print(values, "");
+        }
+
+        if let Some(header) = header {
+            self.text(header);
+        }
+        for value in values {
+            println!("{value}");
+        }
+        Ok(())
+    }
+}
+
 fn main() -> Result<(), RustADBError> {
     let opt = Args::parse();
+    let printer = Printer {
+        #[cfg(feature = "serde")]
+        json: opt.json,
+    };
 
-    let mut connexion = AdbTcpConnexion::new(opt.address, opt.port)?;
+    // Key management does not involve ADB server
+    match &opt.command {
+        Command::Keygen { path } => return adb_client::generate_adb_keypair(path),
+        Command::Fingerprints => {
+            return printer.print_all(None, &adb_client::list_key_fingerprints()?);
+        }
+        _ => {}
+    }
+
+    // Tunnel is closed once dropped, at the end of the command
+    let tunnel = match &opt.ssh {
+        Some(destination) => Some(
+            AdbSshTunnel::builder(destination)
+                .server(SocketAddrV4::new(opt.address, opt.port))
+                .open()?,
+        ),
+        None => None,
+    };
+    let mut connexion = match (&opt.replay, &tunnel) {
+        (Some(session), _) => AdbTcpConnexion::replay(session)?,
+        (None, Some(tunnel)) => tunnel.connexion()?,
+        (None, None) => AdbTcpConnexion::new(opt.address, opt.port)?,
+    };
+    connexion.set_transfer_rate_limit(opt.rate_limit);
+    connexion.set_shell_fallback(opt.shell_fallback);
+    connexion.set_user(opt.target_user);
+    if let Some(session) = &opt.record {
+        connexion.start_recording(session)?;
+    }
+    if let Some(trace) = &opt.trace {
+        connexion.start_tracing(trace)?;
+    }
+
+    let device = match (opt.usb, opt.emulator, opt.transport_id) {
+        (true, _, _) => DeviceSelector::Usb,
+        (_, true, _) => DeviceSelector::Local,
+        (_, _, Some(transport_id)) => DeviceSelector::TransportId(transport_id),
+        _ => DeviceSelector::from(&opt.serial),
+    };
 
     match opt.command {
         Command::Version => {
             let version = connexion.version()?;
-            println!("Android Debug Bridge version {}", version);
-            println!("Package version {}-rust", std::env!("CARGO_PKG_VERSION"));
+            printer.print(
+                &version,
+                format!(
+                    "Android Debug Bridge version {version}\nPackage version {}-rust",
+                    std::env!("CARGO_PKG_VERSION")
+                ),
+            )?;
         }
         Command::Kill => {
             connexion.kill()?;
         }
+        Command::Reconnect { target } => {
+            let target = match target {
+                None => ReconnectTarget::Host,
+                Some(ReconnectTargetCommand::Device) => ReconnectTarget::Device,
+                Some(ReconnectTargetCommand::Offline) => ReconnectTarget::Offline,
+            };
+            connexion.reconnect(&device, target)?;
+        }
+        Command::DisconnectAll => {
+            connexion.disconnect_all()?;
+        }
+        Command::Connect { address } => {
+            connexion.connect(address)?;
+        }
+        Command::Pair { address, code } => {
+            println!("{}", connexion.pair(address, &code)?);
+        }
+        Command::PairQr { timeout } => {
+            let pairing = WifiPairing::generate();
+            eprintln!("Scan a QR code encoding this payload from 'Pair device with QR code':");
+            println!("{}", pairing.qr_payload());
+            let service = connexion.pair_with_qr(&pairing, Duration::from_secs(timeout))?;
+            printer.print(&service, format!("Paired with {}", service.address))?;
+        }
+        Command::MdnsServices => {
+            printer.print_all(None, &connexion.mdns_services()?)?;
+        }
         Command::Devices { long } => {
             if long {
-                println!("List of devices attached (extended)");
-                for device in connexion.devices_long()? {
-                    println!("{}", device);
-                }
+                let devices = connexion.devices_long()?;
+                printer.print_all(Some("List of devices attached (extended)"), &devices)?;
             } else {
-                println!("List of devices attached");
-                for device in connexion.devices()? {
-                    println!("{}", device);
-                }
+                let devices = connexion.devices()?;
+                printer.print_all(Some("List of devices attached"), &devices)?;
             }
         }
-        Command::TrackDevices => {
-            let callback = |device: Device| {
-                println!("{}", device);
-                Ok(())
-            };
-            println!("Live list of devices attached");
+        Command::TrackDevices {
+            debounce: None,
+            liveness,
+        } => {
+            connexion.set_tracking_liveness(liveness.map(Duration::from_secs));
+            let callback = |device: Device| printer.print(&device, &device);
+            printer.text("Live list of devices attached");
             connexion.track_devices(callback)?;
         }
-        Command::Pull { path, filename } => {
-            let mut output = File::create(Path::new(&filename)).unwrap(); // TODO: Better error handling
-            connexion.recv(opt.serial, &path, &mut output)?;
-            println!("Downloaded {path} as {filename}");
+        Command::TrackDevices {
+            debounce: Some(debounce),
+            liveness,
+        } => {
+            connexion.set_tracking_liveness(liveness.map(Duration::from_secs));
+            connexion.track_device_events(Duration::from_millis(debounce), |event| {
+                printer.print(&event, &event)
+            })?;
         }
-        Command::Push { filename, path } => {
-            let mut input = File::open(Path::new(&filename)).unwrap(); // TODO: Better error handling
-            connexion.send(opt.serial, &mut input, &path)?;
-            println!("Uploaded {filename} to {path}");
+        Command::Pull {
+            path,
+            filename,
+            resume: true,
+            ..
+        } => {
+            let report = connexion.recv_resumable(&device, &path, &filename)?;
+            printer.print(
+                &report,
+                format!("Downloaded {path} as {filename}: {report}"),
+            )?;
+        }
+        Command::Pull {
+            path,
+            filename,
+            parallel: Some(streams),
+            ..
+        } => {
+            let report = connexion.recv_parallel(&device, &path, &filename, streams)?;
+            printer.print(
+                &report,
+                format!("Downloaded {path} as {filename}: {report}"),
+            )?;
+        }
+        Command::Pull { path, filename, .. } => {
+            let mut output = File::create(Path::new(&filename))?;
+            let report = connexion.recv(&device, &path, &mut output)?;
+            printer.print(
+                &report,
+                format!("Downloaded {path} as {filename}: {report}"),
+            )?;
+        }
+        Command::Push {
+            filename,
+            path,
+            atomic,
+            sparse,
+        } => {
+            let report = match (atomic, sparse) {
+                (true, _) => connexion.push_file_atomic(&device, &filename, &path)?,
+                (false, true) => connexion.push_sparse(&device, &filename, &path)?,
+                (false, false) => connexion.push_file(&device, &filename, &path)?,
+            };
+            printer.print(&report, format!("Uploaded {filename} to {path}: {report}"))?;
+        }
+        Command::PullTar {
+            path,
+            directory,
+            filter,
+        } => {
+            let filter = TransferFilter::try_from(filter)?;
+            let report = connexion.pull_dir_tar_filtered(&device, &path, &directory, &filter)?;
+            printer.print(
+                &report,
+                format!("Downloaded {path} into {directory}: {report}"),
+            )?;
+        }
+        Command::PushTar {
+            directory,
+            path,
+            filter,
+        } => {
+            let filter = TransferFilter::try_from(filter)?;
+            let report = connexion.push_dir_tar_filtered(&device, &directory, &path, &filter)?;
+            printer.print(
+                &report,
+                format!("Uploaded {directory} into {path}: {report}"),
+            )?;
+        }
+        Command::Df { path: Some(path) } => {
+            let usage = connexion.disk_usage(&device, &path)?;
+            printer.print(&usage, &usage)?;
+        }
+        Command::Df { path: None } => {
+            printer.print_all(None, &connexion.filesystems_usage(&device)?)?;
         }
         Command::List { path } => {
-            connexion.list(opt.serial, path)?;
+            printer.print_all(None, &connexion.list(&device, path)?)?;
         }
         Command::Stat { path } => {
-            let stat_response = connexion.stat(opt.serial, path)?;
-            println!("{}", stat_response);
+            let stat_response = connexion.stat(&device, path)?;
+            printer.print(&stat_response, &stat_response)?;
         }
-        Command::Shell { command } => {
-            if command.is_empty() {
-                connexion.shell(&opt.serial)?;
-            } else {
-                connexion.shell_command(&opt.serial, command)?;
+        Command::Shell {
+            command,
+            input,
+            cwd,
+            env,
+        } if cwd.is_some() || !env.is_empty() => {
+            let environment = ShellEnvironment {
+                working_directory: cwd,
+                variables: env,
+            };
+            let mut input: Box<dyn std::io::Read> = match input.as_deref() {
+                None => Box::new(std::io::empty()),
+                Some("-") => Box::new(std::io::stdin()),
+                Some(path) => Box::new(File::open(Path::new(path))?),
+            };
+            let exit_code = connexion.shell_command_in(
+                &device,
+                &environment,
+                command,
+                &mut input,
+                &mut std::io::stdout(),
+            )?;
+            if let Some(code) = exit_code.filter(|code| *code != 0) {
+                std::process::exit(code.into());
+            }
+        }
+        Command::Shell { command, input, .. } => match input {
+            Some(input) => {
+                let mut input: Box<dyn std::io::Read> = match input.as_str() {
+                    "-" => Box::new(std::io::stdin()),
+                    path => Box::new(File::open(Path::new(path))?),
+                };
+                let exit_code = connexion.shell_command_with_input(
+                    &device,
+                    command,
+                    &mut input,
+                    &mut std::io::stdout(),
+                )?;
+                if let Some(code) = exit_code.filter(|code| *code != 0) {
+                    std::process::exit(code.into());
+                }
             }
+            None if command.is_empty() => connexion.shell(&device)?,
+            None => connexion.shell_command(&device, command)?,
+        },
+        Command::Bridge { command } => {
+            connexion.bridge_stream(
+                &device,
+                command.join(" "),
+                std::io::stdin(),
+                &mut std::io::stdout(),
+            )?;
         }
         Command::HostFeatures => {
             println!("Available host features");
-            for feature in connexion.host_features(&opt.serial)? {
+            for feature in connexion.host_features(&device)? {
                 println!("- {}", feature);
             }
         }
+        Command::PushObb { package, filename } => {
+            let path = connexion.push_obb(&device, &package, &filename)?;
+            println!("Uploaded {filename} to {path}");
+        }
+        Command::Forward {
+            local,
+            remote,
+            no_rebind,
+        } => {
+            let local = connexion.forward(&device, local.parse()?, remote.parse()?, no_rebind)?;
+            println!("Forwarded {local} to {remote}");
+        }
+        Command::Debug {
+            binary,
+            port,
+            command,
+            pid,
+            lldb,
+        } => {
+            let server = match lldb {
+                true => DebugServer::LldbServer,
+                false => DebugServer::Gdbserver,
+            };
+            let target = match pid {
+                Some(pid) => DebugTarget::Pid(pid),
+                None => DebugTarget::Command(command.join(" ")),
+            };
+            let local = connexion.start_debug_server(&device, server, &binary, target, port)?;
+            println!("{server} listening on {local}");
+        }
+        Command::Simpleperf {
+            filename,
+            pid,
+            package,
+            events,
+            duration,
+            call_graph,
+            report,
+        } => {
+            let target = match (pid, package) {
+                (Some(pid), _) => ProfileTarget::Pid(pid),
+                (None, package) => ProfileTarget::Package(package.unwrap_or_default()),
+            };
+            let options = SimpleperfOptions {
+                events,
+                duration: Duration::from_secs(duration),
+                call_graph,
+                report,
+                ..Default::default()
+            };
+            if let Some(report) =
+                connexion.simpleperf_record(&device, target, &options, &filename)?
+            {
+                print!("{report}");
+            }
+            println!("Downloaded recording as {filename}");
+        }
+        Command::MemInfo { process } => {
+            let mem_info = connexion.mem_info(&device, &process)?;
+            printer.print(&mem_info, &mem_info)?;
+        }
+        Command::DumpHeap { process, filename } => {
+            let report = connexion.dump_heap(&device, &process, &filename)?;
+            printer.print(
+                &report,
+                format!("Dumped heap of {process} as {filename}: {report}"),
+            )?;
+        }
+        Command::CrashArtifacts { directory, since } => {
+            let since = connexion.get_time(&device)? - chrono::Duration::seconds(since as i64);
+            let artifacts = connexion.collect_crash_artifacts(&device, since, &directory)?;
+            printer.print(
+                &artifacts,
+                format!("Collected into {directory}: {artifacts}"),
+            )?;
+        }
+        Command::WaitForBoot { timeout } => {
+            connexion.wait_for_boot(&device, Duration::from_secs(timeout))?;
+        }
+        Command::WaitForAuthorization { timeout } => {
+            connexion.wait_for_authorization(&device, Duration::from_secs(timeout), || {
+                eprintln!("Please accept the RSA key prompt on the device");
+            })?;
+        }
+        Command::SetLocation {
+            latitude,
+            longitude,
+            altitude,
+        } => {
+            connexion.set_location(&device, latitude, longitude, altitude)?;
+        }
+        Command::Emu { command } => {
+            let output = connexion
+                .emulator_console(&device)?
+                .command(&command.join(" "))?;
+            print!("{output}");
+        }
+        Command::Users => {
+            printer.print_all(None, &connexion.list_users(&device)?)?;
+        }
+        Command::CreateUser { name, profile_of } => {
+            let user = match profile_of {
+                Some(parent) => connexion.create_work_profile(&device, parent, &name)?,
+                None => connexion.create_user(&device, &name)?,
+            };
+            printer.print(&user, format!("Created user {user}"))?;
+        }
+        Command::RemoveUser { user } => {
+            connexion.remove_user(&device, user)?;
+        }
+        Command::SwitchUser { user } => {
+            connexion.switch_user(&device, user)?;
+        }
+        Command::CurrentUser => {
+            let user = connexion.current_user(&device)?;
+            printer.print(&user, user)?;
+        }
+        Command::InstallExisting { package, user } => {
+            connexion.install_existing(&device, &package, user)?;
+        }
+        Command::Grant {
+            package,
+            permissions,
+        } => {
+            let permissions: Vec<&str> = permissions.iter().map(String::as_str).collect();
+            let failed = connexion.grant_permissions(&device, &package, &permissions)?;
+            let text = failed
+                .iter()
+                .map(|(permission, message)| format!("{permission}: {message}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            printer.print(&failed, text)?;
+        }
+        Command::UiDump => {
+            let root = connexion.ui_dump(&device)?;
+            printer.print(&root, &root)?;
+        }
+        Command::Tunnel { host, port } => {
+            let tunnel = connexion.tunnel(&device, &host, port)?;
+            println!("Tunnelling {} to {host}:{port}", tunnel.local_addr());
+            loop {
+                std::thread::park();
+            }
+        }
+        Command::ForwardListener { remote } => {
+            let tunnel = connexion.forward_to_listener(&device, remote.parse()?)?;
+            println!("Proxying {} to {remote}", tunnel.local_addr());
+            loop {
+                std::thread::park();
+            }
+        }
+        Command::ExternalStorage => {
+            let path = connexion.external_storage_path(&device)?;
+            printer.print(&path, &path)?;
+        }
+        Command::SecurityState => {
+            let state = connexion.security_state(&device)?;
+            printer.print(&state, state.to_string())?;
+        }
+        Command::Locale { locale } => match locale {
+            Some(locale) => connexion.set_locale(&device, &locale)?,
+            None => {
+                let locale = connexion.locale(&device)?;
+                printer.print(&locale, &locale)?;
+            }
+        },
+        Command::Reverse {
+            remote,
+            local,
+            no_rebind,
+        } => {
+            let remote = connexion.reverse(&device, remote.parse()?, local.parse()?, no_rebind)?;
+            println!("Forwarded {remote} to {local}");
+        }
+        Command::ReverseRemove { remote } => {
+            connexion.kill_reverse(&device, remote.parse()?)?;
+        }
+        Command::ForwardList => {
+            printer.print_all(None, &connexion.list_forward()?)?;
+        }
+        Command::ForwardRemove { local } => {
+            connexion.kill_forward(&device, local.parse()?)?;
+        }
+        Command::ForwardRemoveAll => {
+            connexion.kill_forward_all()?;
+        }
+        Command::NetworkStatus => {
+            let status = connexion.network_status(&device)?;
+            printer.print(&status, &status)?;
+        }
+        Command::ApiLevel => {
+            println!("API level: {}", connexion.api_level(&device)?);
+            println!("Settings: {}", connexion.settings_strategy(&device)?);
+            println!("Install: {}", connexion.install_strategy(&device)?);
+        }
+        Command::Keygen { .. } | Command::Fingerprints => unreachable!(),
+        Command::Tail { path, follow } => {
+            for line in connexion.tail_file(&device, &path, follow)? {
+                println!("{}", line?);
+            }
+        }
+        Command::Dmesg { last: false } => {
+            std::io::stdout().write_all(&connexion.dmesg(&device)?)?;
+        }
+        Command::Dmesg { last: true } => match connexion.pull_last_kmsg(&device)? {
+            Some(log) => std::io::stdout().write_all(&log)?,
+            None => println!("No kernel log kept from previous boot"),
+        },
+        Command::AuthorizeKey => {
+            connexion.authorize_host_key(&device)?;
+            println!("Host key authorized, adbd restarted");
+        }
+        Command::Abb { args } => {
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            let output = connexion.abb(&device, &args)?;
+            print!("{}", String::from_utf8_lossy(&output));
+        }
+        Command::Install {
+            filename,
+            incremental,
+            grant_all,
+        } => {
+            let report = if incremental {
+                connexion.install_incremental(&device, &filename)?
+            } else {
+                connexion.install_with_permissions(&device, &filename, grant_all)?
+            };
+            printer.print(&report, format!("Installed {filename}: {report}"))?;
+        }
+        Command::PackageVersion { package } => {
+            let version = connexion.package_version(&device, &package)?;
+            let text = match &version {
+                Some(version) => format!("{package} {version}"),
+                None => format!("{package} is not installed"),
+            };
+            printer.print(&version, text)?;
+        }
+        Command::PackageInfo { package } => {
+            let info = connexion.package_info(&device, &package)?;
+            let text = match &info {
+                Some(info) => info.to_string(),
+                None => format!("{package} is not installed"),
+            };
+            printer.print(&info, text)?;
+        }
         Command::Reboot { sub_command } => {
             println!("Reboots device");
-            connexion.reboot(&opt.serial, sub_command.into())?
+            connexion.reboot(&device, sub_command.into())?
         }
     }
 
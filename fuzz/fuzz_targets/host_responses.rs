@@ -0,0 +1,16 @@
+#![no_main]
+
+use adb_client::AdbTcpConnexion;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let session =
+        std::env::temp_dir().join(format!("adb_client_fuzz_host_{}.txt", std::process::id()));
+    let received = format!("connection\n< OKAY{}\n", data.escape_ascii());
+    std::fs::write(&session, received.repeat(3)).unwrap();
+
+    let mut adb = AdbTcpConnexion::replay(&session).unwrap();
+    let _ = adb.version();
+    let _ = adb.devices();
+    let _ = adb.devices_long();
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use adb_client::{AdbTcpConnexion, DeviceSelector};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let session =
+        std::env::temp_dir().join(format!("adb_client_fuzz_shell_{}.txt", std::process::id()));
+    let received = format!(
+        "connection\n< OKAY0008shell_v2\nconnection\n< OKAYOKAY{}\n",
+        data.escape_ascii()
+    );
+    std::fs::write(&session, received).unwrap();
+
+    let mut adb = AdbTcpConnexion::replay(&session).unwrap();
+    let _ = adb.shell_command_with_input(
+        DeviceSelector::Any,
+        ["cat"],
+        &mut b"input".as_slice(),
+        &mut std::io::sink(),
+    );
+});
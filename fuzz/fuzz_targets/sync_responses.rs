@@ -0,0 +1,15 @@
+#![no_main]
+
+use adb_client::{AdbTcpConnexion, DeviceSelector};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let session =
+        std::env::temp_dir().join(format!("adb_client_fuzz_sync_{}.txt", std::process::id()));
+    let received = format!("connection\n< OKAYOKAY{}\n", data.escape_ascii());
+    std::fs::write(&session, received.repeat(2)).unwrap();
+
+    let mut adb = AdbTcpConnexion::replay(&session).unwrap();
+    let _ = adb.stat(DeviceSelector::Any, "/sdcard");
+    let _ = adb.list(DeviceSelector::Any, "/sdcard");
+});
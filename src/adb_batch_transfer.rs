@@ -0,0 +1,112 @@
+use std::{collections::VecDeque, fs::File, path::PathBuf, time::Instant};
+
+use crate::{
+    adb_rate_limiter::RateLimiter,
+    models::{AdbCommand, SyncCommand},
+    utils::open_local_file,
+    AdbTcpConnexion, BatchEntry, DeviceSelector, Result, TransferDirection, TransferReport,
+};
+
+/// Direction of the files of a batch transfer.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BatchDirection {
+    /// Local files are sent to the device.
+    Push,
+    /// Device files are received into local files.
+    Pull,
+}
+
+/// Files transferred one after the other in a single sync session, as returned by [AdbTcpConnexion::push_many] and [AdbTcpConnexion::pull_many].
+///
+/// Each iteration transfers the next file and returns its [BatchEntry].
+/// Device closes the session when a transfer fails, a new one is then opened for the following files.
+#[derive(Debug)]
+pub struct AdbBatchTransfer<'a> {
+    connexion: &'a mut AdbTcpConnexion,
+    device: DeviceSelector,
+    direction: BatchDirection,
+    files: VecDeque<(PathBuf, String)>,
+    continue_on_error: bool,
+    session_open: bool,
+}
+
+impl<'a> AdbBatchTransfer<'a> {
+    pub(crate) fn new(
+        connexion: &'a mut AdbTcpConnexion,
+        device: DeviceSelector,
+        direction: BatchDirection,
+        files: VecDeque<(PathBuf, String)>,
+        continue_on_error: bool,
+    ) -> Self {
+        Self {
+            connexion,
+            device,
+            direction,
+            files,
+            continue_on_error,
+            session_open: false,
+        }
+    }
+
+    /// Transfers `file` to or from `remote`, opening a sync session first if needed.
+    fn transfer(&mut self, file: &mut File, remote: &str) -> Result<u64> {
+        if !self.session_open {
+            self.connexion.connect_device(&self.device)?;
+            self.connexion.send_adb_request(AdbCommand::Sync)?;
+        }
+
+        // Device closes the session when a transfer fails
+        self.session_open = false;
+        let rate_limiter = RateLimiter::new(self.connexion.transfer_rate_limit);
+        let bytes = match self.direction {
+            BatchDirection::Push => {
+                self.connexion.send_sync_request(SyncCommand::Send)?;
+                self.connexion
+                    .handle_send_command(file, remote, rate_limiter)?
+            }
+            BatchDirection::Pull => {
+                self.connexion.send_sync_request(SyncCommand::Recv)?;
+                self.connexion
+                    .handle_recv_command(remote, file, rate_limiter)?
+            }
+        };
+        self.session_open = true;
+
+        Ok(bytes)
+    }
+}
+
+impl Iterator for AdbBatchTransfer<'_> {
+    type Item = BatchEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (local, remote) = self.files.pop_front()?;
+        let start = Instant::now();
+
+        // Local files are opened before anything is sent, failing to do so keeps the session usable
+        let file = match self.direction {
+            BatchDirection::Push => open_local_file(&local),
+            BatchDirection::Pull => File::create(&local).map_err(Into::into),
+        };
+        let result = file
+            .and_then(|mut file| self.transfer(&mut file, &remote))
+            .map(|bytes| {
+                let direction = match self.direction {
+                    BatchDirection::Push => TransferDirection::Sent,
+                    BatchDirection::Pull => TransferDirection::Received,
+                };
+                self.connexion
+                    .transferred(direction, TransferReport::file(bytes, start))
+            });
+
+        if result.is_err() && !self.continue_on_error {
+            self.files.clear();
+        }
+
+        Some(BatchEntry {
+            local,
+            remote,
+            result,
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{protocol::service::ServiceStream, Result};
+
+/// Represents a command not provided by this crate, run with [AdbTcpConnexion::run_command](crate::AdbTcpConnexion::run_command).
+///
+/// Downstream crates implement it to add their own device or host services without forking this crate.
+///
+/// ```no_run
+/// use adb_client::{protocol::service::ServiceStream, AdbCommandExt, AdbTcpConnexion, DeviceSelector, Result};
+/// use std::net::Ipv4Addr;
+///
+/// struct Uptime;
+///
+/// impl AdbCommandExt for Uptime {
+///     type Output = String;
+///
+///     fn service(&self) -> String {
+///         "shell,raw:cat /proc/uptime".into()
+///     }
+///
+///     fn read_response(&self, stream: &mut ServiceStream) -> Result<String> {
+///         Ok(String::from_utf8(stream.read_to_end()?)?)
+///     }
+/// }
+///
+/// let mut connexion = AdbTcpConnexion::new(Ipv4Addr::from([127, 0, 0, 1]), 5037).unwrap();
+/// let uptime = connexion.run_command(DeviceSelector::Any, &Uptime).unwrap();
+/// ```
+pub trait AdbCommandExt {
+    /// Value returned by the command.
+    type Output;
+
+    /// Service requested, e.g. `shell,raw:<command>` for a device service, or `features` for a host service.
+    fn service(&self) -> String;
+
+    /// Whether the command is a host service, sent to the ADB server with the prefix selecting the device
+    /// (e.g. `host-serial:<serial>:features`) instead of through a connection switched to the device.
+    fn is_host_service(&self) -> bool {
+        false
+    }
+
+    /// Reads the response of the service, once it has been opened.
+    fn read_response(&self, stream: &mut ServiceStream) -> Result<Self::Output>;
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use crate::{Result, TransferReport};
+
+/// Observes every request sent to the ADB server, e.g. for metrics or audit logging, given to [AdbTcpConnexionBuilder::hook](crate::AdbTcpConnexionBuilder::hook).
+///
+/// Requests are the services opened by commands (e.g. `host:version`, `host:transport:<serial>` or `shell:ls`),
+/// a command sending several of them calls hooks for each.
+///
+/// ```no_run
+/// use adb_client::{AdbCommandHook, AdbTcpConnexion, CommandEvent, HookAction};
+/// use std::net::Ipv4Addr;
+///
+/// struct Logger;
+///
+/// impl AdbCommandHook for Logger {
+///     fn after(&self, event: &CommandEvent) -> HookAction {
+///         println!("{} took {:?}", event.request, event.duration);
+///         HookAction::Continue
+///     }
+/// }
+///
+/// let mut connexion = AdbTcpConnexion::builder(Ipv4Addr::from([127, 0, 0, 1]), 5037)
+///     .hook(Logger)
+///     .build()
+///     .unwrap();
+/// ```
+pub trait AdbCommandHook: Send + Sync {
+    /// Called before `request` is sent.
+    fn before(&self, _request: &str) {}
+
+    /// Called once ADB server answered the request, or failed to.
+    ///
+    /// Returning [HookAction::Retry] for a failed request sends it again on a new connection, if it opens one
+    /// (host services and device selection, e.g. while a device reboots). Other failed requests fail as usual.
+    fn after(&self, _event: &CommandEvent) -> HookAction {
+        HookAction::Continue
+    }
+
+    /// Called once a file transfer (push, pull or tree transfer) completed, with its statistics.
+    fn transfer(&self, _direction: TransferDirection, _report: &TransferReport) {}
+}
+
+impl std::fmt::Debug for dyn AdbCommandHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AdbCommandHook")
+    }
+}
+
+/// Request answered by the ADB server, given to [AdbCommandHook::after].
+#[derive(Debug)]
+pub struct CommandEvent<'a> {
+    /// Request sent, e.g. `host:version`.
+    pub request: &'a str,
+    /// Number of times the request has been sent, starting at 1.
+    pub attempt: u32,
+    /// Time taken by ADB server to answer the request.
+    pub duration: Duration,
+    /// Whether the request has been accepted.
+    pub result: &'a Result<()>,
+}
+
+/// What to do once a request has been answered, returned by [AdbCommandHook::after].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    /// Returns the result of the request to the command.
+    Continue,
+    /// Sends the request again if it failed.
+    Retry,
+}
+
+/// Direction of a file transfer, given to [AdbCommandHook::transfer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// Data sent to the device.
+    Sent,
+    /// Data received from the device.
+    Received,
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::{SocketAddrV4, TcpStream},
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::models::{AdbCommand, ConnexionState};
+
+/// Type of service first requested on a connection, connections opened in advance being kept for each one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ServiceType {
+    /// Services answered by ADB server itself (e.g. `host:version`, `host:devices`).
+    Host,
+    /// Requests switching the connection to a device, to reach its services.
+    Transport,
+}
+
+impl From<&AdbCommand> for ServiceType {
+    fn from(command: &AdbCommand) -> Self {
+        match command.next_state() {
+            ConnexionState::Device => ServiceType::Transport,
+            _ => ServiceType::Host,
+        }
+    }
+}
+
+/// Keeps a few connections to the ADB server opened in advance, for each [ServiceType] requested.
+///
+/// ADB server closes a connection once it has answered a request, so connections cannot be reused.
+/// Instead, a background thread connects ahead of time so that commands do not pay the connection cost.
+/// Connections are kept per type of service so that pollers of host services (e.g. device trackers, dashboards)
+/// still find some while device commands are run in bulk.
+#[derive(Debug)]
+pub(crate) struct ConnectionPool {
+    shared: Arc<PoolShared>,
+}
+
+#[derive(Debug)]
+struct PoolShared {
+    socket_addr: SocketAddrV4,
+    size: usize,
+    idle_timeout: Duration,
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    /// Connections opened in advance for each type of service requested so far.
+    connections: HashMap<ServiceType, VecDeque<(TcpStream, Instant)>>,
+    stopped: bool,
+}
+
+impl ConnectionPool {
+    /// Starts a pool keeping `size` connections to `socket_addr` per type of service, each one being dropped after `idle_timeout`.
+    pub(crate) fn new(socket_addr: SocketAddrV4, size: usize, idle_timeout: Duration) -> Self {
+        // Prevents reconnecting in a loop with connections expiring immediately
+        let idle_timeout = idle_timeout.max(Duration::from_millis(100));
+        let shared = Arc::new(PoolShared {
+            socket_addr,
+            size,
+            idle_timeout,
+            state: Mutex::new(PoolState::default()),
+            condvar: Condvar::new(),
+        });
+
+        let thread_shared = shared.clone();
+        std::thread::spawn(move || thread_shared.refill());
+
+        Self { shared }
+    }
+
+    /// Takes a connection opened in advance for `service_type`, if one is available.
+    ///
+    /// Connections start being opened in advance for a type of service once it has been requested.
+    pub(crate) fn take(&self, service_type: ServiceType) -> Option<TcpStream> {
+        let mut state = self.shared.state.lock().ok()?;
+        let idle_timeout = self.shared.idle_timeout;
+
+        let connections = state.connections.entry(service_type).or_default();
+        let connection = loop {
+            match connections.pop_front() {
+                Some((stream, created)) if created.elapsed() < idle_timeout => break Some(stream),
+                Some(_) => continue,
+                None => break None,
+            }
+        };
+        self.shared.condvar.notify_one();
+
+        connection
+    }
+
+    /// Closes all connections opened in advance, e.g. because the server has been stopped.
+    pub(crate) fn clear(&self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.connections.values_mut().for_each(VecDeque::clear);
+        }
+        self.shared.condvar.notify_one();
+    }
+}
+
+impl Drop for ConnectionPool {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.stopped = true;
+            state.connections.clear();
+        }
+        self.shared.condvar.notify_one();
+    }
+}
+
+impl PoolShared {
+    /// Keeps the pool filled with fresh connections until it is stopped.
+    fn refill(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        loop {
+            if state.stopped {
+                return;
+            }
+
+            let idle_timeout = self.idle_timeout;
+            for connections in state.connections.values_mut() {
+                connections.retain(|(_, created)| created.elapsed() < idle_timeout);
+            }
+
+            let missing = state
+                .connections
+                .iter()
+                .find(|(_, connections)| connections.len() < self.size)
+                .map(|(service_type, _)| *service_type);
+            if let Some(service_type) = missing {
+                // Connects without holding the lock, commands can still take connections meanwhile
+                drop(state);
+                let connection = TcpStream::connect(self.socket_addr);
+                state = match self.state.lock() {
+                    Ok(state) => state,
+                    Err(_) => return,
+                };
+
+                match connection {
+                    Ok(stream) => state
+                        .connections
+                        .entry(service_type)
+                        .or_default()
+                        .push_back((stream, Instant::now())),
+                    // Server is not reachable for now, retries later
+                    Err(_) => {
+                        state = match self.condvar.wait_timeout(state, idle_timeout) {
+                            Ok((state, _)) => state,
+                            Err(_) => return,
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Wakes up when a connection is taken, or when the oldest one expires
+            let wait = state
+                .connections
+                .values()
+                .filter_map(VecDeque::front)
+                .map(|(_, created)| idle_timeout.saturating_sub(created.elapsed()))
+                .min()
+                .unwrap_or(idle_timeout);
+            state = match self.condvar.wait_timeout(state, wait) {
+                Ok((state, _)) => state,
+                Err(_) => return,
+            };
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddrV4, TcpStream},
+    path::PathBuf,
+};
+
+use crate::{Result, RustADBError};
+
+/// File holding the token authenticating to emulator consoles, in the home directory.
+const AUTH_TOKEN_FILE: &str = ".emulator_console_auth_token";
+
+/// Represents a connection to the console of an emulator, as returned by [AdbTcpConnexion::emulator_console](crate::AdbTcpConnexion::emulator_console).
+///
+/// Console accepts text commands (e.g. `geo fix`, `power capacity` or `network speed`), as `adb emu` does.
+#[derive(Debug)]
+pub struct AdbEmulatorConsole {
+    reader: BufReader<TcpStream>,
+}
+
+impl AdbEmulatorConsole {
+    /// Connects to the console listening on `socket_addr`, authenticating with the token of the user when required.
+    pub fn connect(socket_addr: SocketAddrV4) -> Result<Self> {
+        let mut console = Self {
+            reader: BufReader::new(TcpStream::connect(socket_addr)?),
+        };
+
+        // Banner asks for authentication on emulators requiring it
+        let banner = console.read_response()?;
+        if banner.contains("Authentication required") {
+            let token = auth_token_path()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .ok_or_else(|| {
+                    RustADBError::ShellCommandFailed(format!(
+                        "emulator console requires the token of ~/{AUTH_TOKEN_FILE}"
+                    ))
+                })?;
+            console.command(&format!("auth {}", token.trim()))?;
+        }
+
+        Ok(console)
+    }
+
+    /// Runs `command` on the console, and returns its output.
+    ///
+    /// Fails with [RustADBError::ShellCommandFailed] and the message of the console if the command is refused.
+    pub fn command(&mut self, command: &str) -> Result<String> {
+        let stream = self.reader.get_mut();
+        stream.write_all(format!("{command}\r\n").as_bytes())?;
+        stream.flush()?;
+
+        self.read_response()
+    }
+
+    /// Reads lines until the final `OK` or `KO: <message>` one.
+    fn read_response(&mut self) -> Result<String> {
+        let mut output = String::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(RustADBError::ConnectionClosedByPeer);
+            }
+
+            match line.trim_end() {
+                "OK" => return Ok(output),
+                line => match line.strip_prefix("KO:") {
+                    Some(message) => {
+                        return Err(RustADBError::ShellCommandFailed(message.trim().to_string()))
+                    }
+                    None => {
+                        output.push_str(line);
+                        output.push('\n');
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn auth_token_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(AUTH_TOKEN_FILE))
+}
@@ -0,0 +1,184 @@
+use std::{collections::VecDeque, io::Read, time::Duration};
+
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How new content of the file is obtained.
+#[derive(Debug)]
+enum TailMode {
+    /// A command (`tail -F` or `cat`) streams the content on the connexion.
+    Stream,
+    /// File size is polled, and bytes past `offset` are read when it grows.
+    Polling { offset: u64 },
+    /// No more lines will be read.
+    Done,
+}
+
+/// Lines of a file on the device, as returned by [AdbTcpConnexion::tail_file].
+///
+/// Iterating blocks until a new line is available when following the file.
+/// Dropping it stops the command reading the file on the device.
+#[derive(Debug)]
+pub struct AdbFileTail<'a> {
+    connexion: &'a mut AdbTcpConnexion,
+    device: DeviceSelector,
+    path: String,
+    follow: bool,
+    mode: TailMode,
+    lines: VecDeque<String>,
+    partial: Vec<u8>,
+}
+
+impl<'a> AdbFileTail<'a> {
+    pub(crate) fn new(
+        connexion: &'a mut AdbTcpConnexion,
+        device: DeviceSelector,
+        path: &str,
+        follow: bool,
+    ) -> Result<Self> {
+        let mode = if !follow {
+            connexion.open_exec(&device, format!("cat {}", shell_quote(path)))?;
+            TailMode::Stream
+        } else if supports_follow_by_name(connexion, &device)? {
+            // Following by name reopens the file when it is rotated or truncated
+            connexion.open_exec(&device, format!("tail -n +1 -F {}", shell_quote(path)))?;
+            TailMode::Stream
+        } else {
+            TailMode::Polling { offset: 0 }
+        };
+
+        Ok(Self {
+            connexion,
+            device,
+            path: path.to_string(),
+            follow,
+            mode,
+            lines: VecDeque::new(),
+            partial: vec![],
+        })
+    }
+
+    /// Stops reading the file, closing the connection of the command reading it on the device.
+    pub fn close(mut self) -> Result<()> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        let mode = std::mem::replace(&mut self.mode, TailMode::Done);
+        if let TailMode::Stream = mode {
+            self.connexion.close()?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads more content, returns whether the file may still have lines to read.
+    fn fill(&mut self) -> Result<bool> {
+        match self.mode {
+            TailMode::Stream => {
+                let mut buffer = [0_u8; 4096];
+                match self.connexion.tcp_stream.read(&mut buffer)? {
+                    0 => {
+                        self.mode = TailMode::Done;
+                        self.flush_partial();
+                    }
+                    size => self.push_bytes(&buffer[..size]),
+                }
+            }
+            TailMode::Polling { offset } => {
+                let size = self.file_size()?;
+                // File shrank: it has been truncated or rotated, reads it from the start again
+                let offset = if size < offset { 0 } else { offset };
+
+                if size > offset {
+                    let content = self.connexion.exec_command(
+                        &self.device,
+                        format!(
+                            "dd if={} bs=1 skip={offset} count={} 2>/dev/null",
+                            shell_quote(&self.path),
+                            size - offset
+                        ),
+                    )?;
+                    self.push_bytes(&content);
+                    self.mode = TailMode::Polling {
+                        offset: offset + content.len() as u64,
+                    };
+                } else if self.follow {
+                    self.mode = TailMode::Polling { offset };
+                    std::thread::sleep(POLL_INTERVAL);
+                } else {
+                    self.mode = TailMode::Done;
+                    self.flush_partial();
+                }
+            }
+            TailMode::Done => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    fn file_size(&mut self) -> Result<u64> {
+        let output = self.connexion.exec_command(
+            &self.device,
+            format!("wc -c < {} 2>/dev/null", shell_quote(&self.path)),
+        )?;
+
+        // Missing file (e.g. while being rotated) is considered empty
+        Ok(String::from_utf8_lossy(&output).trim().parse().unwrap_or(0))
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.partial.extend_from_slice(bytes);
+        while let Some(position) = self.partial.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=position).collect();
+            self.lines
+                .push_back(String::from_utf8_lossy(line.trim_ascii_end()).to_string());
+        }
+    }
+
+    fn flush_partial(&mut self) {
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            self.lines
+                .push_back(String::from_utf8_lossy(line.trim_ascii_end()).to_string());
+        }
+    }
+}
+
+impl Iterator for AdbFileTail<'_> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.lines.pop_front() {
+                return Some(Ok(line));
+            }
+
+            match self.fill() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => {
+                    self.mode = TailMode::Done;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for AdbFileTail<'_> {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Whether `tail` on the device can follow a file by name (`-F`), as toybox does.
+fn supports_follow_by_name(
+    connexion: &mut AdbTcpConnexion,
+    device: &DeviceSelector,
+) -> Result<bool> {
+    let help = connexion.exec_command(device, "tail --help 2>&1")?;
+
+    Ok(help.windows(2).any(|option| option == b"-F"))
+}
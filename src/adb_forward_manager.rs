@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use crate::{AdbServerHandle, DeviceEvent, DeviceState, Endpoint, Result, RustADBError};
+
+/// Time a device must stay connected before its forwards are set up again.
+const RECONNECT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Period after which a silent tracking connection is re-established.
+const TRACKING_LIVENESS: Duration = Duration::from_secs(30);
+/// Time waited before tracking devices again once it failed.
+const TRACKING_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Forwarded endpoints (local, remote) by device serial.
+type Forwards = Arc<Mutex<HashMap<String, Vec<(Endpoint, Endpoint)>>>>;
+
+/// Forwards remembered by device serial, set up again when their device reconnects, as returned by [AdbTcpConnexion::forward_manager](crate::AdbTcpConnexion::forward_manager).
+///
+/// ADB server removes the forwards of a device once it disconnects. Devices are tracked in the background over a
+/// dedicated connexion, which stops with the next change in connected devices after the manager is dropped.
+/// Tracking is started again whenever it fails, the last failure being available with [ForwardManager::take_error].
+#[derive(Debug)]
+pub struct ForwardManager {
+    server: AdbServerHandle,
+    forwards: Forwards,
+    stopped: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<RustADBError>>>,
+}
+
+impl ForwardManager {
+    pub(crate) fn new(server: AdbServerHandle) -> Result<Self> {
+        let mut tracker = Some(server.connexion()?);
+        let forwards = Forwards::default();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let error: Arc<Mutex<Option<RustADBError>>> = Arc::default();
+
+        let (tracked, tracking, tracking_error) =
+            (forwards.clone(), stopped.clone(), error.clone());
+        std::thread::spawn(move || loop {
+            let mut on_event = |event| {
+                if tracking.load(Ordering::SeqCst) {
+                    // Any error stops tracking
+                    return Err(RustADBError::ConnectionClosedByPeer);
+                }
+                let serial = match event {
+                    DeviceEvent::DeviceAdded {
+                        identifier,
+                        state: DeviceState::Device,
+                    }
+                    | DeviceEvent::StateChanged {
+                        identifier,
+                        to: DeviceState::Device,
+                        ..
+                    } => identifier,
+                    _ => return Ok(()),
+                };
+
+                let rules = tracked
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .get(&serial)
+                    .cloned()
+                    .unwrap_or_default();
+                if !rules.is_empty() {
+                    // Device may disconnect again meanwhile, it is then set up on its next return
+                    let _ = server.connexion().and_then(|mut connexion| {
+                        rules.into_iter().try_for_each(|(local, remote)| {
+                            connexion
+                                .forward(serial.as_str(), local, remote, false)
+                                .map(|_| ())
+                        })
+                    });
+                }
+                Ok(())
+            };
+
+            let result = match tracker.take() {
+                Some(tracker) => Ok(tracker),
+                None => server.connexion(),
+            }
+            .and_then(|mut tracker| {
+                tracker.set_tracking_liveness(Some(TRACKING_LIVENESS));
+                tracker.track_device_events(RECONNECT_DEBOUNCE, &mut on_event)
+            });
+            if tracking.load(Ordering::SeqCst) {
+                return;
+            }
+            if let Err(e) = result {
+                *tracking_error
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(e);
+            }
+
+            std::thread::sleep(TRACKING_RETRY_DELAY);
+        });
+
+        Ok(Self {
+            server,
+            forwards,
+            stopped,
+            error,
+        })
+    }
+
+    /// Takes the last error that interrupted device tracking, if any.
+    ///
+    /// Forwards are not set up again until tracking has been started again, which is retried in the background.
+    pub fn take_error(&self) -> Option<RustADBError> {
+        self.error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    /// Forwards connections to `local` on the host to `remote` on device `serial`, and remembers it to set it up again
+    /// whenever the device reconnects.
+    ///
+    /// Returns the local endpoint, whose port is the one chosen by ADB server for [Endpoint::Tcp] port `0`.
+    pub fn forward(&self, serial: &str, local: Endpoint, remote: Endpoint) -> Result<Endpoint> {
+        let local = self
+            .server
+            .connexion()?
+            .forward(serial, local, remote.clone(), false)?;
+
+        let mut forwards = self.forwards.lock().unwrap_or_else(PoisonError::into_inner);
+        let rules = forwards.entry(serial.to_string()).or_default();
+        rules.retain(|(existing, _)| *existing != local);
+        rules.push((local.clone(), remote));
+
+        Ok(local)
+    }
+
+    /// Removes the forwarding of `local` to device `serial`, which is not set up again afterwards.
+    pub fn kill_forward(&self, serial: &str, local: &Endpoint) -> Result<()> {
+        if let Some(rules) = self
+            .forwards
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(serial)
+        {
+            rules.retain(|(existing, _)| existing != local);
+        }
+
+        self.server.connexion()?.kill_forward(serial, local.clone())
+    }
+
+    /// Forwards remembered for device `serial`, as (local, remote) endpoints.
+    pub fn forwards(&self, serial: &str) -> Vec<(Endpoint, Endpoint)> {
+        self.forwards
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(serial)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for ForwardManager {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,245 @@
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use byteorder::{ByteOrder, LittleEndian};
+use md5::{Digest, Md5};
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, EncodeRsaPrivateKey, LineEnding},
+    pkcs8::DecodePrivateKey,
+    traits::PublicKeyParts,
+    BigUint, RsaPrivateKey, RsaPublicKey,
+};
+
+use crate::{AdbKeyFingerprint, Result, RustADBError};
+
+const KEY_SIZE_BITS: usize = 2048;
+const KEY_SIZE_WORDS: usize = KEY_SIZE_BITS / 32;
+const USER_KEY_FILE: &str = "adbkey";
+const VENDOR_KEY_EXTENSION: &str = "adb_key";
+/// DER prefix of SHA-1 digests in PKCS#1 v1.5 signatures, ADB signing tokens as if they were SHA-1 digests.
+#[cfg(feature = "adbd")]
+const SHA1_DIGEST_PREFIX: [u8; 15] = [
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+
+/// Generates a new RSA key pair usable by ADB, as `adb keygen` does.
+///
+/// Private key is written to `path` in PEM format, and public key to `<path>.pub` in the format expected in devices' `adb_keys` file.
+pub fn generate_adb_keypair<P: AsRef<Path>>(path: P) -> Result<()> {
+    let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), KEY_SIZE_BITS)?;
+    let pem = private_key
+        .to_pkcs1_pem(LineEnding::LF)
+        .map_err(|e| RustADBError::InvalidAdbKey(e.to_string()))?;
+
+    let path = path.as_ref();
+    std::fs::write(path, pem.as_bytes())?;
+    std::fs::write(public_key_path(path), encode_public_key(&private_key)?)?;
+
+    Ok(())
+}
+
+/// Lists fingerprints of the keys ADB authenticates with: user key in `~/.android`, and vendor keys from `ANDROID_VENDOR_KEYS`.
+pub fn list_key_fingerprints() -> Result<Vec<AdbKeyFingerprint>> {
+    key_paths()
+        .into_iter()
+        .map(|path| {
+            let key = load_private_key(&path)?;
+            Ok(AdbKeyFingerprint {
+                fingerprint: fingerprint(&key)?,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Directory where ADB stores user configuration, `$ANDROID_USER_HOME` or `~/.android`.
+pub(crate) fn android_user_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("ANDROID_USER_HOME") {
+        return Some(PathBuf::from(dir));
+    }
+
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".android"))
+}
+
+/// Paths of the existing private keys: user key, then vendor keys.
+///
+/// `ANDROID_VENDOR_KEYS` lists files, or directories containing `*.adb_key` files, separated as `PATH` is.
+pub(crate) fn key_paths() -> Vec<PathBuf> {
+    let mut paths = vec![];
+    if let Some(user_key) = android_user_dir().map(|dir| dir.join(USER_KEY_FILE)) {
+        if user_key.is_file() {
+            paths.push(user_key);
+        }
+    }
+
+    if let Some(vendor_keys) = std::env::var_os("ANDROID_VENDOR_KEYS") {
+        for path in std::env::split_paths(&vendor_keys) {
+            if path.is_dir() {
+                let mut keys = std::fs::read_dir(&path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.extension().and_then(|e| e.to_str()) == Some(VENDOR_KEY_EXTENSION)
+                    })
+                    .collect::<Vec<_>>();
+                keys.sort();
+                paths.extend(keys);
+            } else if path.is_file() {
+                paths.push(path);
+            }
+        }
+    }
+
+    paths
+}
+
+/// Public key of the user, as a line of `adb_keys`.
+///
+/// Read from `adbkey.pub` when present, derived from `adbkey` private key otherwise.
+pub(crate) fn user_public_key() -> Result<String> {
+    let user_key = android_user_dir()
+        .map(|dir| dir.join(USER_KEY_FILE))
+        .ok_or_else(|| RustADBError::InvalidAdbKey("no ADB user directory".into()))?;
+
+    match std::fs::read_to_string(public_key_path(&user_key)) {
+        Ok(public_key) => Ok(public_key),
+        Err(_) if user_key.is_file() => encode_public_key(&load_private_key(&user_key)?),
+        Err(_) => Err(RustADBError::InvalidAdbKey(format!(
+            "no key found at {}",
+            user_key.display()
+        ))),
+    }
+}
+
+/// Loads a PEM private key, either in PKCS#1 (as written by ADB) or PKCS#8 format.
+pub(crate) fn load_private_key<P: AsRef<Path>>(path: P) -> Result<RsaPrivateKey> {
+    let pem = std::fs::read_to_string(path)?;
+
+    RsaPrivateKey::from_pkcs1_pem(&pem)
+        .or_else(|_| RsaPrivateKey::from_pkcs8_pem(&pem))
+        .map_err(|e| RustADBError::InvalidAdbKey(e.to_string()))
+}
+
+/// Encodes the public part of `key` as a line of `adb_keys`: base64 public key followed by `user@host`.
+pub(crate) fn encode_public_key(key: &RsaPrivateKey) -> Result<String> {
+    Ok(format!(
+        "{} {}\n",
+        STANDARD.encode(public_key_blob(&key.to_public_key())?),
+        user_info()
+    ))
+}
+
+/// Decodes a line of `adb_keys`: base64 public key, optionally followed by `user@host`.
+#[cfg(feature = "adbd")]
+pub(crate) fn decode_public_key(line: &str) -> Result<RsaPublicKey> {
+    let encoded = line.split_whitespace().next().unwrap_or_default();
+    let blob = STANDARD
+        .decode(encoded.trim_end_matches('\0'))
+        .map_err(|e| RustADBError::InvalidAdbKey(e.to_string()))?;
+    if blob.len() != 4 * (3 + 2 * KEY_SIZE_WORDS)
+        || LittleEndian::read_u32(&blob[0..4]) as usize != KEY_SIZE_WORDS
+    {
+        return Err(RustADBError::InvalidAdbKey(format!(
+            "key size must be {KEY_SIZE_BITS} bits"
+        )));
+    }
+
+    let modulus = BigUint::from_bytes_le(&blob[8..8 + 4 * KEY_SIZE_WORDS]);
+    let exponent = BigUint::from_bytes_le(&blob[8 + 8 * KEY_SIZE_WORDS..]);
+    RsaPublicKey::new(modulus, exponent).map_err(|e| RustADBError::InvalidAdbKey(e.to_string()))
+}
+
+/// Whether `signature` is the signature of authentication `token` by `key`.
+#[cfg(feature = "adbd")]
+pub(crate) fn verify_token(key: &RsaPublicKey, token: &[u8], signature: &[u8]) -> bool {
+    let scheme = rsa::Pkcs1v15Sign {
+        hash_len: Some(token.len()),
+        prefix: SHA1_DIGEST_PREFIX.into(),
+    };
+
+    key.verify(scheme, token, signature).is_ok()
+}
+
+/// Fingerprint of `key`, as displayed by devices.
+pub(crate) fn fingerprint(key: &RsaPrivateKey) -> Result<String> {
+    let digest = Md5::digest(public_key_blob(&key.to_public_key())?);
+
+    Ok(digest
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+/// Encodes `key` in the binary format used by Android: modulus size in words, `-1 / n[0] mod 2^32`,
+/// modulus and `R^2 mod n` (R being 2^2048) as little-endian words, and exponent.
+fn public_key_blob(key: &RsaPublicKey) -> Result<Vec<u8>> {
+    let modulus = key.n();
+    if modulus.bits() != KEY_SIZE_BITS {
+        return Err(RustADBError::InvalidAdbKey(format!(
+            "key size must be {KEY_SIZE_BITS} bits"
+        )));
+    }
+
+    let n0 = LittleEndian::read_u32(&modulus.to_bytes_le());
+    // Newton's iteration doubles correct low bits of the inverse at each step
+    let mut n0_inverse: u32 = 1;
+    for _ in 0..5 {
+        n0_inverse = n0_inverse.wrapping_mul(2_u32.wrapping_sub(n0.wrapping_mul(n0_inverse)));
+    }
+
+    let r = BigUint::from(1_u8) << KEY_SIZE_BITS;
+    let rr = (&r * &r) % modulus;
+
+    let mut exponent = key.e().to_bytes_le();
+    if exponent.len() > 4 {
+        return Err(RustADBError::InvalidAdbKey("invalid exponent".into()));
+    }
+    exponent.resize(4, 0);
+
+    let mut blob = vec![0_u8; 4 * (3 + 2 * KEY_SIZE_WORDS)];
+    LittleEndian::write_u32(&mut blob[0..4], KEY_SIZE_WORDS as u32);
+    LittleEndian::write_u32(&mut blob[4..8], n0_inverse.wrapping_neg());
+    write_words(&mut blob[8..8 + 4 * KEY_SIZE_WORDS], modulus);
+    write_words(
+        &mut blob[8 + 4 * KEY_SIZE_WORDS..8 + 8 * KEY_SIZE_WORDS],
+        &rr,
+    );
+    blob[8 + 8 * KEY_SIZE_WORDS..].copy_from_slice(&exponent);
+
+    Ok(blob)
+}
+
+/// Writes `value` as little-endian bytes, padded with zeros to fill `buffer`.
+fn write_words(buffer: &mut [u8], value: &BigUint) {
+    let bytes = value.to_bytes_le();
+    buffer[..bytes.len()].copy_from_slice(&bytes);
+}
+
+fn public_key_path(path: &Path) -> PathBuf {
+    let mut public_key_path = path.as_os_str().to_owned();
+    public_key_path.push(".pub");
+    PathBuf::from(public_key_path)
+}
+
+/// `user@host` comment appended to public keys, identifying where they come from.
+fn user_info() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into());
+    let host = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|host| host.trim().to_string())
+        })
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "unknown".into());
+
+    format!("{user}@{host}")
+}
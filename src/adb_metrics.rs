@@ -0,0 +1,73 @@
+use metrics::{counter, histogram};
+
+use crate::{
+    AdbCommandHook, CommandEvent, HookAction, RustADBError, TransferDirection, TransferReport,
+};
+
+/// Hook recording metrics of requests and file transfers through the [metrics] facade, for long-running services
+/// (e.g. device farms) exposing them with an exporter such as `metrics-exporter-prometheus`.
+///
+/// Recorded metrics:
+/// - `adb_requests_total`: counter of requests, by `service` (e.g. `host:version` or `shell`)
+/// - `adb_request_errors_total`: counter of failed requests, by `service` and `kind` of error (e.g. `DeviceNotFound`)
+/// - `adb_request_duration_seconds`: histogram of the time taken by ADB server to answer requests, by `service`
+/// - `adb_transferred_bytes_total`: counter of bytes of file transfers, by `direction` (`sent` or `received`)
+///
+/// ```no_run
+/// use adb_client::{AdbMetrics, AdbTcpConnexion};
+/// use std::net::Ipv4Addr;
+///
+/// let mut connexion = AdbTcpConnexion::builder(Ipv4Addr::from([127, 0, 0, 1]), 5037)
+///     .hook(AdbMetrics)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdbMetrics;
+
+impl AdbCommandHook for AdbMetrics {
+    fn after(&self, event: &CommandEvent) -> HookAction {
+        let service = service_name(event.request);
+        counter!("adb_requests_total", "service" => service.clone()).increment(1);
+        histogram!("adb_request_duration_seconds", "service" => service.clone())
+            .record(event.duration.as_secs_f64());
+        if let Err(error) = event.result {
+            counter!("adb_request_errors_total", "service" => service, "kind" => error_kind(error))
+                .increment(1);
+        }
+
+        HookAction::Continue
+    }
+
+    fn transfer(&self, direction: TransferDirection, report: &TransferReport) {
+        let direction = match direction {
+            TransferDirection::Sent => "sent",
+            TransferDirection::Received => "received",
+        };
+        counter!("adb_transferred_bytes_total", "direction" => direction).increment(report.bytes);
+    }
+}
+
+/// Service of `request` without its arguments, so that labels have a bounded cardinality.
+///
+/// Host services keep their name (e.g. `host:version` for `host-serial:<serial>:version`), device services
+/// only their type (e.g. `shell` for `shell,v2,raw:ls`).
+fn service_name(request: &str) -> String {
+    let mut parts = request.split(':');
+    match parts.next().unwrap_or_default() {
+        "host" | "host-usb" | "host-local" => format!("host:{}", parts.next().unwrap_or_default()),
+        "host-serial" | "host-transport-id" => {
+            format!("host:{}", parts.nth(1).unwrap_or_default())
+        }
+        service => service.split(',').next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Variant of `error`, e.g. `DeviceNotFound`.
+fn error_kind(error: &RustADBError) -> String {
+    format!("{error:?}")
+        .split(['(', ' ', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+/// Limits the throughput of a transfer to a number of bytes per second.
+///
+/// Transfers wait after each chunk until their average throughput since start is back under the limit.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bytes_per_second: u64,
+    start: Instant,
+    transferred: u64,
+}
+
+impl RateLimiter {
+    /// Returns a limiter for a new transfer, or `None` if `bytes_per_second` does not limit anything.
+    pub(crate) fn new(bytes_per_second: Option<u64>) -> Option<Self> {
+        bytes_per_second
+            .filter(|bytes_per_second| *bytes_per_second > 0)
+            .map(|bytes_per_second| Self {
+                bytes_per_second,
+                start: Instant::now(),
+                transferred: 0,
+            })
+    }
+
+    /// Size of chunks to transfer, at most `max`, so that a chunk does not take more than a quarter of second at the limit.
+    ///
+    /// Keeps the throughput smooth instead of sending large bursts followed by long pauses.
+    pub(crate) fn chunk_size(&self, max: usize) -> usize {
+        let quarter = usize::try_from(self.bytes_per_second / 4).unwrap_or(usize::MAX);
+        quarter.clamp(1, max)
+    }
+
+    /// Accounts for `bytes` just transferred, waiting as long as the limit has been exceeded.
+    pub(crate) fn throttle(&mut self, bytes: usize) {
+        self.transferred += bytes as u64;
+
+        let expected =
+            Duration::from_secs_f64(self.transferred as f64 / self.bytes_per_second as f64);
+        if let Some(wait) = expected.checked_sub(self.start.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+}
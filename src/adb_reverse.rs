@@ -0,0 +1,75 @@
+use std::{
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::{AdbServerHandle, DeviceSelector, Endpoint, Result};
+
+/// Device endpoint whose connections are handled in this process, as returned by [AdbTcpConnexion::reverse_to_handler](crate::AdbTcpConnexion::reverse_to_handler).
+///
+/// Dropping it removes the reverse forwarding and stops accepting connections, connections already handled are left open.
+#[derive(Debug)]
+pub struct AdbReverse {
+    server: AdbServerHandle,
+    device: DeviceSelector,
+    remote: Endpoint,
+    local_addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+}
+
+impl AdbReverse {
+    pub(crate) fn new(
+        server: AdbServerHandle,
+        device: DeviceSelector,
+        remote: Endpoint,
+        listener: TcpListener,
+        handler: impl Fn(TcpStream) + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let accepting = stopped.clone();
+        let handler = Arc::new(handler);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let handler = handler.clone();
+                std::thread::spawn(move || handler(stream));
+            }
+        });
+
+        Ok(Self {
+            server,
+            device,
+            remote,
+            local_addr,
+            stopped,
+        })
+    }
+
+    /// Endpoint on the device, with the port it chose for [Endpoint::Tcp] port `0`.
+    pub fn remote(&self) -> &Endpoint {
+        &self.remote
+    }
+}
+
+impl Drop for AdbReverse {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes the accepting thread up so that it notices
+        let _ = TcpStream::connect(self.local_addr);
+
+        let _ = self
+            .server
+            .connexion()
+            .and_then(|mut connexion| connexion.kill_reverse(&self.device, self.remote.clone()));
+    }
+}
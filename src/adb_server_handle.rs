@@ -0,0 +1,33 @@
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use crate::{AdbTcpConnexion, Result};
+
+/// Represents an ADB server that can be shared between threads.
+///
+/// Handle is cheap to copy: it only holds the server address, and opens a new [AdbTcpConnexion] each time one is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdbServerHandle {
+    socket_addr: SocketAddrV4,
+}
+
+impl AdbServerHandle {
+    /// Instantiates a new instance of [AdbServerHandle]. No connection is made until [AdbServerHandle::connexion] is called.
+    pub fn new(address: Ipv4Addr, port: u16) -> Self {
+        Self {
+            socket_addr: SocketAddrV4::new(address, port),
+        }
+    }
+
+    /// Opens a new connexion to the ADB server.
+    pub fn connexion(&self) -> Result<AdbTcpConnexion> {
+        AdbTcpConnexion::new(*self.socket_addr.ip(), self.socket_addr.port())
+    }
+}
+
+impl From<&AdbTcpConnexion> for AdbServerHandle {
+    fn from(value: &AdbTcpConnexion) -> Self {
+        Self {
+            socket_addr: value.socket_addr,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    io::ErrorKind,
+    net::{SocketAddrV4, TcpStream},
+    path::PathBuf,
+    process::Command,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{Result, RustADBError};
+
+/// Time given to the ADB server to accept connections once started.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Way of starting the ADB server when it is not reachable.
+#[derive(Clone)]
+pub(crate) enum ServerStarter {
+    /// Runs `<adb binary> -P <port> start-server`.
+    Binary(PathBuf),
+    /// Calls a user-provided hook.
+    Hook(Arc<dyn Fn() -> Result<()> + Send + Sync>),
+}
+
+impl std::fmt::Debug for ServerStarter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerStarter::Binary(path) => f.debug_tuple("Binary").field(path).finish(),
+            ServerStarter::Hook(_) => f.debug_tuple("Hook").finish(),
+        }
+    }
+}
+
+impl ServerStarter {
+    fn start(&self, port: u16) -> Result<()> {
+        match self {
+            ServerStarter::Binary(path) => {
+                let output = Command::new(path)
+                    .args(["-P", &port.to_string(), "start-server"])
+                    .output()?;
+                if !output.status.success() {
+                    return Err(RustADBError::ADBServerStartFailed(
+                        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                    ));
+                }
+
+                Ok(())
+            }
+            ServerStarter::Hook(hook) => hook(),
+        }
+    }
+}
+
+/// Connects to the ADB server listening on `socket_addr`.
+///
+/// If the server is not running and a `starter` is given, starts it and waits for it to accept connections.
+pub(crate) fn connect(
+    socket_addr: SocketAddrV4,
+    starter: Option<&ServerStarter>,
+) -> Result<TcpStream> {
+    match (TcpStream::connect(socket_addr), starter) {
+        (Err(e), Some(starter)) if e.kind() == ErrorKind::ConnectionRefused => {
+            starter.start(socket_addr.port())?;
+
+            let started = Instant::now();
+            loop {
+                match TcpStream::connect(socket_addr) {
+                    Err(e)
+                        if e.kind() == ErrorKind::ConnectionRefused
+                            && started.elapsed() < STARTUP_TIMEOUT =>
+                    {
+                        std::thread::sleep(Duration::from_millis(100))
+                    }
+                    result => return Ok(result?),
+                }
+            }
+        }
+        (result, _) => Ok(result?),
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{Result, RustADBError};
+
+const SESSION_HEADER: &str = "# adb_client recorded session";
+const CONNECTION_MARKER: &str = "connection";
+const SENT_PREFIX: &str = "> ";
+const RECEIVED_PREFIX: &str = "< ";
+
+/// Number given to the first connection in traces, as the file descriptor adb would likely use.
+const FIRST_TRACE_FD: u32 = 3;
+/// Number of bytes dumped per trace line, as adb does.
+const TRACE_DUMP_SIZE: usize = 16;
+
+/// Format of recorded files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecordFormat {
+    /// Session file, which can be replayed.
+    Session,
+    /// Lines similar to the `writex`/`readx` lines logged by adb with `ADB_TRACE=all`, to compare with it.
+    AdbTrace,
+}
+
+/// Records bytes exchanged with the ADB server into a session file.
+///
+/// Each connection starts with a `connection` line, followed by one line per exchange:
+/// `> ` for bytes sent to the server and `< ` for bytes received from it, escaped as printable ASCII.
+///
+/// In [RecordFormat::AdbTrace] format, connections are numbered as file descriptors instead.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionRecorder {
+    file: Arc<Mutex<File>>,
+    format: RecordFormat,
+    connections: Arc<AtomicU32>,
+    fd: u32,
+}
+
+impl SessionRecorder {
+    /// Creates the file located at `path` recording in `format`, replacing it if it exists.
+    pub(crate) fn create<P: AsRef<Path>>(path: P, format: RecordFormat) -> Result<Self> {
+        let mut file = File::create(path)?;
+        if format == RecordFormat::Session {
+            writeln!(file, "{SESSION_HEADER}")?;
+        }
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            format,
+            connections: Arc::new(AtomicU32::new(0)),
+            fd: FIRST_TRACE_FD,
+        })
+    }
+
+    /// Records that a new connection to the ADB server has been opened, and returns the recorder of this connection.
+    pub(crate) fn connection(&self) -> Self {
+        let fd = FIRST_TRACE_FD + self.connections.fetch_add(1, Ordering::Relaxed);
+        let recorder = Self { fd, ..self.clone() };
+        match self.format {
+            RecordFormat::Session => recorder.write_line(CONNECTION_MARKER),
+            RecordFormat::AdbTrace => recorder.trace(&format!("adb_connect: fd={fd}")),
+        }
+
+        recorder
+    }
+
+    /// Records bytes sent to the ADB server.
+    pub(crate) fn sent(&self, bytes: &[u8]) {
+        match self.format {
+            RecordFormat::Session => {
+                self.write_line(&format!("{SENT_PREFIX}{}", bytes.escape_ascii()))
+            }
+            RecordFormat::AdbTrace => self.trace_bytes("writex", bytes),
+        }
+    }
+
+    /// Records bytes received from the ADB server.
+    pub(crate) fn received(&self, bytes: &[u8]) {
+        match self.format {
+            RecordFormat::Session => {
+                self.write_line(&format!("{RECEIVED_PREFIX}{}", bytes.escape_ascii()))
+            }
+            RecordFormat::AdbTrace => self.trace_bytes("readx", bytes),
+        }
+    }
+
+    /// Traces `bytes` as adb does, e.g. `writex: fd=3 len=12: 303030636... 000chost:ver [truncated]`.
+    fn trace_bytes(&self, operation: &str, bytes: &[u8]) {
+        let dumped = &bytes[..bytes.len().min(TRACE_DUMP_SIZE)];
+        let hex = dumped
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        let printable = dumped
+            .iter()
+            .map(|byte| match byte.is_ascii_graphic() || *byte == b' ' {
+                true => *byte as char,
+                false => '.',
+            })
+            .collect::<String>();
+        let truncated = match bytes.len() > TRACE_DUMP_SIZE {
+            true => " [truncated]",
+            false => "",
+        };
+
+        self.trace(&format!(
+            "{operation}: fd={} len={}: {hex} {printable}{truncated}",
+            self.fd,
+            bytes.len()
+        ));
+    }
+
+    /// Writes `message` with the prefix of adb logs: tag, severity, time, process and thread ids, and source.
+    fn trace(&self, message: &str) {
+        let thread = format!("{:?}", std::thread::current().id());
+        let thread = thread.trim_start_matches("ThreadId(").trim_end_matches(')');
+        self.write_line(&format!(
+            "adb D {} {:5} {thread:>5} adb_io.cpp] {message}",
+            chrono::Local::now().format("%m-%d %H:%M:%S%.3f"),
+            std::process::id()
+        ));
+    }
+
+    fn write_line(&self, line: &str) {
+        // Recording is a debugging help, it must never make the command itself fail
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{line}");
+        }
+    }
+}
+
+/// Reads the session file located at `path`, and returns the bytes received from the ADB server on each connection.
+pub(crate) fn read_session<P: AsRef<Path>>(path: P) -> Result<VecDeque<Vec<u8>>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut connections = VecDeque::new();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line == CONNECTION_MARKER {
+            connections.push_back(Vec::new());
+        } else if let Some(received) = line.strip_prefix(RECEIVED_PREFIX) {
+            connections
+                .back_mut()
+                .ok_or_else(|| invalid_line(index, "exchange outside of a connection"))?
+                .extend(unescape(received).ok_or_else(|| invalid_line(index, "invalid escape"))?);
+        } else if !line.starts_with(SENT_PREFIX) {
+            return Err(invalid_line(index, "unknown line"));
+        }
+    }
+
+    Ok(connections)
+}
+
+fn invalid_line(index: usize, reason: &str) -> RustADBError {
+    RustADBError::RecordedSessionError(format!("line {}: {reason}", index + 1))
+}
+
+/// Reverts [escape_ascii](slice::escape_ascii).
+fn unescape(escaped: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut chars = escaped.bytes();
+
+    while let Some(c) = chars.next() {
+        if c != b'\\' {
+            bytes.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            b'n' => bytes.push(b'\n'),
+            b'r' => bytes.push(b'\r'),
+            b't' => bytes.push(b'\t'),
+            b'x' => {
+                let hex = [chars.next()?, chars.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            c @ (b'\\' | b'\'' | b'"') => bytes.push(c),
+            _ => return None,
+        }
+    }
+
+    Some(bytes)
+}
@@ -0,0 +1,227 @@
+use std::{
+    io::{Read, Write},
+    net::Shutdown,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{
+    adb_stream::AdbStream,
+    models::{ShellPacket, ShellPacketId},
+    Result, RustADBError, ShellOutput,
+};
+
+/// Maximum payload size of stdin packets, which adbd does not accept above its own buffer size.
+const STDIN_PACKET_SIZE: usize = 4096;
+
+/// Represents an interactive shell session running on a device.
+///
+/// Output is read in the background, and can be retrieved without blocking with [AdbShellSession::poll].
+/// Dropping the session closes its connection, which ends the remote process.
+#[derive(Debug)]
+pub struct AdbShellSession {
+    writer: AdbShellWriter,
+    receiver: Receiver<Result<ShellOutput>>,
+    finished: bool,
+}
+
+impl AdbShellSession {
+    /// Instantiates a new [AdbShellSession] from a connection on which a shell service has been opened.
+    pub(crate) fn new(stream: AdbStream, shell_v2: bool) -> Result<Self> {
+        let mut read_stream = stream.try_clone()?;
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || loop {
+            let output = read_output(&mut read_stream, shell_v2);
+            let over = !matches!(output, Ok(ShellOutput::Stdout(_) | ShellOutput::Stderr(_)));
+            if sender.send(output).is_err() || over {
+                break;
+            }
+        });
+
+        Ok(Self {
+            writer: AdbShellWriter {
+                stream: Arc::new(Mutex::new(stream)),
+                shell_v2,
+            },
+            receiver,
+            finished: false,
+        })
+    }
+
+    /// Returns a handle to write to the standard input of the remote process.
+    ///
+    /// Handles can be sent to other threads.
+    pub fn writer(&self) -> AdbShellWriter {
+        self.writer.clone()
+    }
+
+    /// Whether the remote process runs in a PTY, which handles control characters (e.g. Ctrl-C) and window size.
+    pub fn has_pty(&self) -> bool {
+        self.writer.shell_v2
+    }
+
+    /// Returns the next output of the session if some is available, without blocking.
+    pub fn poll(&mut self) -> Result<Option<ShellOutput>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        match self.receiver.try_recv() {
+            Ok(output) => self.handle_output(output).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                self.handle_output(Ok(ShellOutput::Exit(None))).map(Some)
+            }
+        }
+    }
+
+    /// Waits for the next output of the session.
+    ///
+    /// Returns `None` once [ShellOutput::Exit] has been returned.
+    pub fn wait(&mut self) -> Result<Option<ShellOutput>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let output = self.receiver.recv().unwrap_or(Ok(ShellOutput::Exit(None)));
+        self.handle_output(output).map(Some)
+    }
+
+    /// Waits for the next output of the session for at most `timeout`, failing with [RustADBError::Timeout] if none arrives.
+    ///
+    /// Returns `None` once [ShellOutput::Exit] has been returned.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<Option<ShellOutput>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let output = match self.receiver.recv_timeout(timeout) {
+            Ok(output) => output,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(RustADBError::Timeout(format!(
+                    "shell output after {timeout:?}"
+                )))
+            }
+            Err(RecvTimeoutError::Disconnected) => Ok(ShellOutput::Exit(None)),
+        };
+        self.handle_output(output).map(Some)
+    }
+
+    /// Closes the connection of the session, ending the remote process, and waits for the background reader to stop.
+    ///
+    /// Output not retrieved yet is discarded, and writers of the session fail afterwards.
+    pub fn close(mut self) -> Result<()> {
+        self.writer.lock()?.close()?;
+
+        // Reader stops on its own once the connection is closed
+        while self.receiver.recv().is_ok() {}
+        self.finished = true;
+
+        Ok(())
+    }
+
+    fn handle_output(&mut self, output: Result<ShellOutput>) -> Result<ShellOutput> {
+        if !matches!(output, Ok(ShellOutput::Stdout(_) | ShellOutput::Stderr(_))) {
+            self.finished = true;
+        }
+
+        output
+    }
+}
+
+impl Drop for AdbShellSession {
+    fn drop(&mut self) {
+        // Background reader holds a clone of the connection, which would otherwise stay open
+        if let Ok(stream) = self.writer.lock() {
+            let _ = stream.close();
+        }
+    }
+}
+
+/// Handle to write to the standard input of a remote process started by an [AdbShellSession].
+#[derive(Debug, Clone)]
+pub struct AdbShellWriter {
+    stream: Arc<Mutex<AdbStream>>,
+    shell_v2: bool,
+}
+
+impl AdbShellWriter {
+    /// Notifies the remote PTY that the terminal is now `rows` x `columns` characters.
+    ///
+    /// Has no effect if the session has no PTY.
+    pub fn resize(&self, rows: u16, columns: u16) -> Result<()> {
+        if self.shell_v2 {
+            self.lock()?
+                .write_all(&ShellPacket::window_size(rows, columns).to_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes the standard input of the remote process.
+    pub fn close(&self) -> Result<()> {
+        let mut stream = self.lock()?;
+        if self.shell_v2 {
+            stream.write_all(&ShellPacket::new(ShellPacketId::CloseStdin, vec![]).to_bytes())?;
+        } else {
+            stream.shutdown(Shutdown::Write)?;
+        }
+
+        Ok(())
+    }
+
+    fn lock(&self) -> std::io::Result<std::sync::MutexGuard<'_, AdbStream>> {
+        self.stream
+            .lock()
+            .map_err(|_| std::io::Error::other("shell session writer poisoned"))
+    }
+}
+
+impl Write for AdbShellWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut stream = self.lock()?;
+        if self.shell_v2 {
+            // Larger buffers are written in several packets by `write_all`
+            let buf = &buf[..buf.len().min(STDIN_PACKET_SIZE)];
+            stream.write_all(&ShellPacket::new(ShellPacketId::Stdin, buf.to_vec()).to_bytes())?;
+            return Ok(buf.len());
+        }
+
+        stream.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.lock()?.flush()
+    }
+}
+
+/// Reads the next output of a shell session from `stream`.
+fn read_output(stream: &mut AdbStream, shell_v2: bool) -> Result<ShellOutput> {
+    if !shell_v2 {
+        let mut buffer = [0_u8; 1024];
+        return match stream.read(&mut buffer)? {
+            0 => Ok(ShellOutput::Exit(None)),
+            size => Ok(ShellOutput::Stdout(buffer[..size].to_vec())),
+        };
+    }
+
+    loop {
+        let packet = match ShellPacket::read_from(stream) {
+            Ok(packet) => packet,
+            Err(RustADBError::ConnectionClosedByPeer) => return Ok(ShellOutput::Exit(None)),
+            Err(e) => return Err(e),
+        };
+
+        match packet.id {
+            ShellPacketId::Stdout => return Ok(ShellOutput::Stdout(packet.payload)),
+            ShellPacketId::Stderr => return Ok(ShellOutput::Stderr(packet.payload)),
+            ShellPacketId::Exit => return Ok(ShellOutput::Exit(packet.payload.first().copied())),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    io::Read,
+    net::{Ipv4Addr, SocketAddrV4, TcpListener, TcpStream},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use crate::{AdbServerHandle, AdbTcpConnexion, AdbTcpConnexionBuilder, Result, RustADBError};
+
+/// Time given to `ssh` to connect and set up the forwarding.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Builder of [AdbSshTunnel], returned by [AdbSshTunnel::builder].
+#[derive(Debug)]
+pub struct AdbSshTunnelBuilder {
+    destination: String,
+    ssh_binary: PathBuf,
+    server: SocketAddrV4,
+    args: Vec<String>,
+}
+
+impl AdbSshTunnelBuilder {
+    /// Runs the `ssh` binary located at `path` instead of the one found in `PATH`.
+    pub fn ssh_binary<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.ssh_binary = path.into();
+        self
+    }
+
+    /// Address of the ADB server as seen from the SSH host. Defaults to `127.0.0.1:5037`.
+    pub fn server(mut self, server: SocketAddrV4) -> Self {
+        self.server = server;
+        self
+    }
+
+    /// Passes `arg` to `ssh` (e.g. `-i` and a key path, or `-p` and a port), before the destination.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Opens the tunnel, and waits for the forwarding to accept connections.
+    ///
+    /// `ssh` runs in batch mode: authentication must not prompt, e.g. by relying on an SSH agent or a key without passphrase.
+    pub fn open(self) -> Result<AdbSshTunnel> {
+        // Port is released for ssh to bind it right after
+        let listener = TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))?;
+        let local_addr = SocketAddrV4::new(Ipv4Addr::LOCALHOST, listener.local_addr()?.port());
+        drop(listener);
+
+        let child = Command::new(&self.ssh_binary)
+            .args([
+                "-N",
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "ExitOnForwardFailure=yes",
+            ])
+            .arg("-L")
+            .arg(format!("{local_addr}:{}", self.server))
+            .args(&self.args)
+            .arg(&self.destination)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| RustADBError::SshTunnelFailed(e.to_string()))?;
+        let mut tunnel = AdbSshTunnel { local_addr, child };
+
+        let started = Instant::now();
+        loop {
+            if let Some(status) = tunnel.child.try_wait()? {
+                let mut message = String::new();
+                if let Some(stderr) = tunnel.child.stderr.as_mut() {
+                    stderr.read_to_string(&mut message)?;
+                }
+                let message = match message.trim() {
+                    "" => format!("ssh exited with {status}"),
+                    message => message.to_string(),
+                };
+                return Err(RustADBError::SshTunnelFailed(message));
+            }
+            if TcpStream::connect(local_addr).is_ok() {
+                return Ok(tunnel);
+            }
+            if started.elapsed() > CONNECT_TIMEOUT {
+                return Err(RustADBError::Timeout(format!(
+                    "SSH tunnel to {} not set up after {CONNECT_TIMEOUT:?}",
+                    self.destination
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// ADB server of a remote host, reached through an SSH tunnel run by the OpenSSH client.
+///
+/// Remote lab servers are often only reachable over SSH, rather than listening on the network.
+/// Connexions are made to a local port forwarded by `ssh`, which is stopped when the tunnel is dropped.
+#[derive(Debug)]
+pub struct AdbSshTunnel {
+    local_addr: SocketAddrV4,
+    child: Child,
+}
+
+impl AdbSshTunnel {
+    /// Opens a tunnel to the ADB server listening on `127.0.0.1:5037` of `destination` (`[user@]host`, or a host of the SSH config).
+    pub fn open(destination: &str) -> Result<Self> {
+        Self::builder(destination).open()
+    }
+
+    /// Returns a builder to configure a tunnel to `destination`.
+    pub fn builder(destination: &str) -> AdbSshTunnelBuilder {
+        AdbSshTunnelBuilder {
+            destination: destination.to_string(),
+            ssh_binary: "ssh".into(),
+            server: SocketAddrV4::new(Ipv4Addr::LOCALHOST, 5037),
+            args: vec![],
+        }
+    }
+
+    /// Local address forwarded to the remote ADB server.
+    pub fn local_addr(&self) -> SocketAddrV4 {
+        self.local_addr
+    }
+
+    /// Opens a new connexion to the remote ADB server.
+    pub fn connexion(&self) -> Result<AdbTcpConnexion> {
+        self.connexion_builder().build()
+    }
+
+    /// Returns a builder to configure a connexion to the remote ADB server.
+    pub fn connexion_builder(&self) -> AdbTcpConnexionBuilder {
+        AdbTcpConnexion::builder(*self.local_addr.ip(), self.local_addr.port())
+    }
+
+    /// Handle to the remote ADB server, valid as long as the tunnel is open.
+    pub fn handle(&self) -> AdbServerHandle {
+        AdbServerHandle::new(*self.local_addr.ip(), self.local_addr.port())
+    }
+}
+
+impl Drop for AdbSshTunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
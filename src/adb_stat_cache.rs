@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::{AdbStatResponse, DeviceSelector, DirEntry};
+
+/// Results of `stat` and `list` kept per device and path, see [AdbTcpConnexionBuilder::stat_cache](crate::AdbTcpConnexionBuilder::stat_cache).
+#[derive(Debug, Default)]
+pub(crate) struct StatCache {
+    stats: HashMap<DeviceSelector, HashMap<String, AdbStatResponse>>,
+    lists: HashMap<DeviceSelector, HashMap<String, Vec<DirEntry>>>,
+}
+
+impl StatCache {
+    pub(crate) fn stat(&self, device: &DeviceSelector, path: &str) -> Option<AdbStatResponse> {
+        self.stats.get(device)?.get(normalize(path)).cloned()
+    }
+
+    pub(crate) fn insert_stat(
+        &mut self,
+        device: &DeviceSelector,
+        path: &str,
+        stat: AdbStatResponse,
+    ) {
+        self.stats
+            .entry(device.clone())
+            .or_default()
+            .insert(normalize(path).to_string(), stat);
+    }
+
+    pub(crate) fn list(&self, device: &DeviceSelector, path: &str) -> Option<Vec<DirEntry>> {
+        self.lists.get(device)?.get(normalize(path)).cloned()
+    }
+
+    /// Keeps `entries` of directory `path`, and their metadata as results of `stat` on their paths.
+    pub(crate) fn insert_list(
+        &mut self,
+        device: &DeviceSelector,
+        path: &str,
+        entries: Vec<DirEntry>,
+    ) {
+        let path = normalize(path);
+        let stats = self.stats.entry(device.clone()).or_default();
+        for entry in entries.iter().filter(|e| e.name != "." && e.name != "..") {
+            let entry_path = match path.ends_with('/') {
+                true => format!("{path}{}", entry.name),
+                false => format!("{path}/{}", entry.name),
+            };
+            stats.insert(entry_path, entry.metadata.clone());
+        }
+
+        self.lists
+            .entry(device.clone())
+            .or_default()
+            .insert(path.to_string(), entries);
+    }
+
+    /// Forgets `path`, everything below it, and the listing of its parent directory.
+    pub(crate) fn invalidate(&mut self, device: &DeviceSelector, path: &str) {
+        let path = normalize(path);
+        let below = |cached: &String| {
+            cached == path
+                || cached
+                    .strip_prefix(path)
+                    .is_some_and(|rest| path.ends_with('/') || rest.starts_with('/'))
+        };
+        let parent = match path.rsplit_once('/') {
+            Some(("", _)) => "/",
+            Some((parent, _)) => parent,
+            None => "",
+        };
+
+        if let Some(stats) = self.stats.get_mut(device) {
+            stats.retain(|cached, _| !below(cached));
+        }
+        if let Some(lists) = self.lists.get_mut(device) {
+            lists.retain(|cached, _| !below(cached) && cached != parent);
+        }
+    }
+}
+
+/// Strips trailing slashes, so that `/sdcard/` and `/sdcard` share their cached results.
+fn normalize(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "" if path.starts_with('/') => "/",
+        trimmed => trimmed,
+    }
+}
@@ -0,0 +1,136 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError, StateKey};
+
+/// Original values of device settings and properties, restored when dropped, as returned by [AdbTcpConnexion::snapshot_state].
+///
+/// Values can be overridden with [AdbStateSnapshot::set], e.g. for a test, and are restored even if the test panics.
+/// The connexion remains usable through the snapshot, which dereferences to it.
+#[derive(Debug)]
+pub struct AdbStateSnapshot<'a> {
+    connexion: &'a mut AdbTcpConnexion,
+    device: DeviceSelector,
+    /// Captured values, `None` for settings that were not defined.
+    originals: Vec<(StateKey, Option<String>)>,
+}
+
+impl<'a> AdbStateSnapshot<'a> {
+    pub(crate) fn new(
+        connexion: &'a mut AdbTcpConnexion,
+        device: DeviceSelector,
+        keys: impl IntoIterator<Item = StateKey>,
+    ) -> Result<Self> {
+        let mut snapshot = Self {
+            connexion,
+            device,
+            originals: vec![],
+        };
+        for key in keys {
+            snapshot.capture(&key)?;
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Original value of `key`, `None` if it was not captured or, for settings, not defined.
+    pub fn original(&self, key: &StateKey) -> Option<&str> {
+        self.originals
+            .iter()
+            .find(|(captured, _)| captured == key)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Overrides `key` with `value`, capturing its original value first if it was not.
+    pub fn set(&mut self, key: &StateKey, value: impl ToString) -> Result<()> {
+        self.capture(key)?;
+        write_state(self.connexion, &self.device, key, Some(&value.to_string()))
+    }
+
+    /// Restores original values, and reports the first failure to do so.
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_all()
+    }
+
+    fn capture(&mut self, key: &StateKey) -> Result<()> {
+        if self.originals.iter().any(|(captured, _)| captured == key) {
+            return Ok(());
+        }
+
+        let value = match key {
+            StateKey::Setting(namespace, name) => {
+                self.connexion
+                    .settings_get(&self.device, *namespace, name)?
+            }
+            StateKey::Property(name) => Some(self.connexion.get_prop(&self.device, name)?),
+        };
+        self.originals.push((key.clone(), value));
+
+        Ok(())
+    }
+
+    /// Restores values in the reverse order of their capture, trying all of them even if some fail.
+    fn restore_all(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        while let Some((key, value)) = self.originals.pop() {
+            let restored = write_state(self.connexion, &self.device, &key, value.as_deref());
+            if result.is_ok() {
+                result = restored;
+            }
+        }
+
+        result
+    }
+}
+
+impl Deref for AdbStateSnapshot<'_> {
+    type Target = AdbTcpConnexion;
+
+    fn deref(&self) -> &Self::Target {
+        self.connexion
+    }
+}
+
+impl DerefMut for AdbStateSnapshot<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.connexion
+    }
+}
+
+impl Drop for AdbStateSnapshot<'_> {
+    fn drop(&mut self) {
+        let _ = self.restore_all();
+    }
+}
+
+/// Sets `key` to `value`, `None` deleting settings.
+fn write_state(
+    connexion: &mut AdbTcpConnexion,
+    device: &DeviceSelector,
+    key: &StateKey,
+    value: Option<&str>,
+) -> Result<()> {
+    match (key, value) {
+        (StateKey::Setting(namespace, name), Some(value)) => {
+            connexion.settings_put(device, *namespace, name, value)
+        }
+        (StateKey::Setting(namespace, name), None) => {
+            connexion.settings_delete(device, *namespace, name)
+        }
+        (StateKey::Property(name), value) => {
+            let output = connexion.exec_command(
+                device,
+                format!(
+                    "setprop {} {} 2>&1",
+                    shell_quote(name),
+                    shell_quote(value.unwrap_or_default())
+                ),
+            )?;
+            match output.trim_ascii().is_empty() {
+                true => Ok(()),
+                false => Err(RustADBError::ShellCommandFailed(
+                    String::from_utf8_lossy(&output).trim().to_string(),
+                )),
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{ErrorKind, Read, Write},
+    net::{Shutdown, TcpStream},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use socket2::{SockRef, TcpKeepalive};
+
+use crate::{adb_session_recorder::SessionRecorder, adb_transport::AdbTransportStream};
+
+/// Connection to the ADB server.
+///
+/// Exchanged bytes can be recorded, and a recorded session can be replayed instead of talking to a real server.
+#[derive(Debug)]
+pub(crate) enum AdbStream {
+    /// Connection to a running ADB server.
+    Tcp {
+        stream: TcpStream,
+        recorder: Option<SessionRecorder>,
+    },
+    /// Connection to an ADB server over a user-provided transport.
+    Custom {
+        stream: Box<dyn AdbTransportStream>,
+        recorder: Option<SessionRecorder>,
+    },
+    /// Bytes received from the ADB server on a recorded connection. Bytes written are discarded.
+    Replay(Arc<Mutex<VecDeque<u8>>>),
+}
+
+impl AdbStream {
+    /// Wraps a connection to the ADB server, recording it if `recorder` is set.
+    pub(crate) fn tcp(stream: TcpStream, recorder: Option<SessionRecorder>) -> Self {
+        Self::Tcp {
+            stream,
+            recorder: recorder.map(|recorder| recorder.connection()),
+        }
+    }
+
+    /// Wraps a connection to the ADB server over a user-provided transport, recording it if `recorder` is set.
+    pub(crate) fn custom(
+        stream: Box<dyn AdbTransportStream>,
+        recorder: Option<SessionRecorder>,
+    ) -> Self {
+        Self::Custom {
+            stream,
+            recorder: recorder.map(|recorder| recorder.connection()),
+        }
+    }
+
+    /// Replays `received` as if it came from the ADB server.
+    pub(crate) fn replay(received: Vec<u8>) -> Self {
+        Self::Replay(Arc::new(Mutex::new(received.into())))
+    }
+
+    pub(crate) fn try_clone(&self) -> std::io::Result<Self> {
+        Ok(match self {
+            Self::Tcp { stream, recorder } => Self::Tcp {
+                stream: stream.try_clone()?,
+                recorder: recorder.clone(),
+            },
+            Self::Custom { stream, recorder } => Self::Custom {
+                stream: stream.try_clone()?,
+                recorder: recorder.clone(),
+            },
+            Self::Replay(received) => Self::Replay(received.clone()),
+        })
+    }
+
+    pub(crate) fn shutdown(&self, how: Shutdown) -> std::io::Result<()> {
+        match self {
+            Self::Tcp { stream, .. } => stream.shutdown(how),
+            Self::Custom { stream, .. } => stream.shutdown(how),
+            Self::Replay(_) => Ok(()),
+        }
+    }
+
+    /// Closes both directions of the connection, which makes ADB server close the service on the device.
+    ///
+    /// Clones of the connection are closed too. Closing an already closed connection succeeds.
+    pub(crate) fn close(&self) -> std::io::Result<()> {
+        match self.shutdown(Shutdown::Both) {
+            Err(e) if e.kind() == ErrorKind::NotConnected => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Enables TCP keepalive probes, sent after `idle` without traffic and then every `idle` until the peer answers.
+    pub(crate) fn set_keepalive(&self, idle: Duration) -> std::io::Result<()> {
+        match self {
+            Self::Tcp { stream, .. } => SockRef::from(stream)
+                .set_tcp_keepalive(&TcpKeepalive::new().with_time(idle).with_interval(idle)),
+            Self::Custom { .. } | Self::Replay(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            Self::Tcp { stream, .. } => stream.set_read_timeout(timeout),
+            Self::Custom { .. } | Self::Replay(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn set_nodelay(&self, nodelay: bool) -> std::io::Result<()> {
+        match self {
+            Self::Tcp { stream, .. } => stream.set_nodelay(nodelay),
+            Self::Custom { .. } | Self::Replay(_) => Ok(()),
+        }
+    }
+}
+
+/// Marks errors caused by the peer closing a connection, see [ConnectionClosedByPeer](crate::RustADBError::ConnectionClosedByPeer).
+#[derive(Debug)]
+pub(crate) struct PeerClosed;
+
+impl Display for PeerClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "connection closed by peer")
+    }
+}
+
+impl std::error::Error for PeerClosed {}
+
+impl AdbStream {
+    /// Reads once from the connection, retrying interrupted reads.
+    ///
+    /// Spurious `WouldBlock` errors are retried as well, unless a read timeout is set: it is then reported as `TimedOut`, as on Windows.
+    fn read_once(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match self.read_raw(buf) {
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => match self {
+                    Self::Tcp { stream, .. } if stream.read_timeout()?.is_none() => continue,
+                    _ => return Err(std::io::Error::new(ErrorKind::TimedOut, e)),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    fn read_raw(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Tcp { stream, recorder } => {
+                let size = stream.read(buf)?;
+                if let Some(recorder) = recorder.as_ref().filter(|_| size > 0) {
+                    recorder.received(&buf[..size]);
+                }
+                Ok(size)
+            }
+            Self::Custom { stream, recorder } => {
+                let size = stream.read(buf)?;
+                if let Some(recorder) = recorder.as_ref().filter(|_| size > 0) {
+                    recorder.received(&buf[..size]);
+                }
+                Ok(size)
+            }
+            Self::Replay(received) => received
+                .lock()
+                .map_err(|_| std::io::Error::other("replayed connection poisoned"))?
+                .read(buf),
+        }
+    }
+}
+
+impl Read for AdbStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.read_once(buf)
+    }
+
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> std::io::Result<()> {
+        while !buf.is_empty() {
+            match self.read_once(buf)? {
+                0 => return Err(std::io::Error::new(ErrorKind::UnexpectedEof, PeerClosed)),
+                size => buf = &mut buf[size..],
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for AdbStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Tcp { stream, recorder } => {
+                let size = stream.write(buf)?;
+                if let Some(recorder) = recorder {
+                    recorder.sent(&buf[..size]);
+                }
+                Ok(size)
+            }
+            Self::Custom { stream, recorder } => {
+                let size = stream.write(buf)?;
+                if let Some(recorder) = recorder {
+                    recorder.sent(&buf[..size]);
+                }
+                Ok(size)
+            }
+            Self::Replay(_) => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Tcp { stream, .. } => stream.flush(),
+            Self::Custom { stream, .. } => stream.flush(),
+            Self::Replay(_) => Ok(()),
+        }
+    }
+}
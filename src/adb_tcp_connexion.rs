@@ -1,41 +1,453 @@
 use std::{
+    collections::{HashMap, VecDeque},
     io::{Read, Write},
-    net::{Ipv4Addr, SocketAddrV4, TcpStream},
+    net::{Ipv4Addr, SocketAddrV4},
+    path::{Path, PathBuf},
     str,
-    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use crate::{
-    models::{AdbCommand, AdbRequestStatus, SyncCommand},
-    Result, RustADBError,
+    adb_connection_pool::{ConnectionPool, ServiceType},
+    adb_server_starter::{connect, ServerStarter},
+    adb_session_recorder::{read_session, RecordFormat, SessionRecorder},
+    adb_stat_cache::StatCache,
+    adb_stream::AdbStream,
+    adb_transport::{AdbTransportStream, Transport},
+    adb_tunnel::NestedForward,
+    models::{AdbCommand, ConnexionState, HostFeatures, SyncCommand},
+    parser,
+    protocol::{codec, service},
+    AdbCommandHook, AdbVersion, ApiLevel, CommandEvent, DeviceSelector, HookAction, Result,
+    RustADBError, TransferDirection, TransferReport, UserId,
 };
 
 /// Represents an ADB-over-TCP connexion.
+///
+/// Each command opens a new connection to the ADB server, so a connexion can be reused for any number of commands.
+/// Use an [AdbServerHandle](crate::AdbServerHandle) to issue commands from several threads.
 #[derive(Debug)]
 pub struct AdbTcpConnexion {
     pub(crate) socket_addr: SocketAddrV4,
-    pub(crate) tcp_stream: TcpStream,
+    pub(crate) tcp_stream: AdbStream,
+    pub(crate) pool: Option<ConnectionPool>,
+    server_starter: Option<ServerStarter>,
+    transport: Option<Transport>,
+    pub(crate) default_device: Option<DeviceSelector>,
+    pub(crate) user: Option<UserId>,
+    recorder: Option<SessionRecorder>,
+    replayed_connections: Option<VecDeque<Vec<u8>>>,
+    pub(crate) capabilities: HashMap<DeviceSelector, Vec<HostFeatures>>,
+    pub(crate) device_commands: HashMap<DeviceSelector, HashMap<String, bool>>,
+    pub(crate) api_levels: HashMap<DeviceSelector, ApiLevel>,
+    pub(crate) stat_cache: Option<StatCache>,
+    pub(crate) shell_fallback: bool,
+    state: ConnexionState,
+    /// Whether the connection is opened by the next request, from the pool for its type of service.
+    pending_connection: bool,
+    pub(crate) transfer_rate_limit: Option<u64>,
+    pub(crate) server_version: Option<AdbVersion>,
+    minimum_server_version: Option<AdbVersion>,
+    tcp_keepalive: Option<Duration>,
+    pub(crate) tracking_liveness: Option<Duration>,
+    hooks: Vec<Arc<dyn AdbCommandHook>>,
+    /// Forwarding leading to the ADB server of a [nested connexion](AdbTcpConnexion::nested_connexion).
+    pub(crate) nested_forward: Option<NestedForward>,
+}
+
+/// Builder for [AdbTcpConnexion], allowing to configure how connections to the ADB server are made.
+#[derive(Debug)]
+pub struct AdbTcpConnexionBuilder {
+    socket_addr: SocketAddrV4,
+    pool_size: usize,
+    idle_timeout: Duration,
+    server_starter: Option<ServerStarter>,
+    transport: Option<Transport>,
+    default_device: Option<DeviceSelector>,
+    user: Option<UserId>,
+    transfer_rate_limit: Option<u64>,
+    minimum_server_version: Option<AdbVersion>,
+    tcp_keepalive: Option<Duration>,
+    tracking_liveness: Option<Duration>,
+    stat_cache: bool,
+    shell_fallback: bool,
+    hooks: Vec<Arc<dyn AdbCommandHook>>,
+}
+
+impl AdbTcpConnexionBuilder {
+    /// Number of connections opened in advance to the ADB server. Defaults to 0 (no pooling).
+    ///
+    /// Useful for high-frequency pollers (e.g. `devices` or `version` in a loop), whose commands then do not wait for connections.
+    pub fn pool_size(mut self, pool_size: usize) -> Self {
+        self.pool_size = pool_size;
+        self
+    }
+
+    /// Duration after which connections opened in advance are closed if unused. Defaults to 30 seconds.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Starts the ADB server with the `adb` binary found in `PATH` when it is not reachable, e.g. after a `kill`.
+    pub fn auto_start_server(self, enabled: bool) -> Self {
+        match enabled {
+            true => self.adb_binary("adb"),
+            false => Self {
+                server_starter: None,
+                ..self
+            },
+        }
+    }
+
+    /// Starts the ADB server with the `adb` binary located at `path` when it is not reachable.
+    pub fn adb_binary<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.server_starter = Some(ServerStarter::Binary(path.into()));
+        self
+    }
+
+    /// Calls `hook` to start the ADB server when it is not reachable.
+    ///
+    /// Hook should return once the server has been started. Connection is retried for a few seconds afterwards.
+    pub fn start_server_hook<F: Fn() -> Result<()> + Send + Sync + 'static>(
+        mut self,
+        hook: F,
+    ) -> Self {
+        self.server_starter = Some(ServerStarter::Hook(Arc::new(hook)));
+        self
+    }
+
+    /// Opens connections to the ADB server by calling `connect`, instead of connecting to the address of the builder.
+    ///
+    /// Each command opens a new connection. Connections are neither pooled nor tuned with TCP options,
+    /// and [AdbServerHandle](crate::AdbServerHandle)s of the connexion do not use the transport.
+    pub fn transport<S, F>(mut self, connect: F) -> Self
+    where
+        S: AdbTransportStream,
+        F: Fn() -> std::io::Result<S> + Send + Sync + 'static,
+    {
+        self.transport = Some(Transport::new(connect));
+        self
+    }
+
+    /// Device targeted by commands called with [DeviceSelector::Any], instead of the one given by `ANDROID_SERIAL` environment variable.
+    pub fn default_device<D: Into<DeviceSelector>>(mut self, device: D) -> Self {
+        self.default_device = Some(device.into());
+        self
+    }
+
+    /// User targeted by package manager and application helpers (installs, `run-as`, heap dumps, application storage), with `--user` flags.
+    ///
+    /// By default, APKs are installed for all users and application storage is the one of the foreground user.
+    pub fn user(mut self, user: UserId) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Limits the throughput of file transfers (e.g. `send`, `recv`) to `bytes_per_second`. Defaults to no limit.
+    ///
+    /// Useful for background transfers on shared networks, which should not starve interactive sessions.
+    pub fn transfer_rate_limit(mut self, bytes_per_second: u64) -> Self {
+        self.transfer_rate_limit = Some(bytes_per_second);
+        self
+    }
+
+    /// Fails commands with [RustADBError::IncompatibleServerVersion] before sending them if ADB server is older than `version`.
+    ///
+    /// Useful when relying on services that older servers lack, which would otherwise fail with unclear errors.
+    pub fn minimum_server_version(mut self, version: AdbVersion) -> Self {
+        self.minimum_server_version = Some(version);
+        self
+    }
+
+    /// Enables TCP keepalive on connections to the ADB server, probing the peer after `idle` without traffic.
+    ///
+    /// Keeps long-lived connections (e.g. `track_devices`) open through NAT and firewalls dropping idle connections.
+    pub fn tcp_keepalive(mut self, idle: Duration) -> Self {
+        self.tcp_keepalive = Some(idle);
+        self
+    }
+
+    /// Re-establishes device tracking connections receiving no update for `period`, or lost, instead of failing.
+    ///
+    /// The list of devices sent again by the server on the new connection is only reported if it changed.
+    pub fn tracking_liveness(mut self, period: Duration) -> Self {
+        self.tracking_liveness = Some(period);
+        self
+    }
+
+    /// Keeps results of [AdbTcpConnexion::stat] and [AdbTcpConnexion::list] for the lifetime of the connexion. Defaults to disabled.
+    ///
+    /// Saves round trips when walking deep trees, whose directories are checked repeatedly. Cached results are not updated
+    /// when files change on the device, use [AdbTcpConnexion::invalidate_stat_cache] after changing them.
+    pub fn stat_cache(mut self, enabled: bool) -> Self {
+        self.stat_cache = enabled;
+        self
+    }
+
+    /// Falls back to `cat` run with shell v2 protocol when the sync service is denied access to a file that the shell user can access,
+    /// e.g. in directories restricted by SELinux. Defaults to disabled.
+    ///
+    /// Applies to [AdbTcpConnexion::recv] and [AdbTcpConnexion::push_file], when the device supports shell v2 protocol.
+    pub fn shell_fallback(mut self, enabled: bool) -> Self {
+        self.shell_fallback = enabled;
+        self
+    }
+
+    /// Calls `hook` around every request sent to the ADB server, after hooks already added.
+    pub fn hook(mut self, hook: impl AdbCommandHook + 'static) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Builds the [AdbTcpConnexion].
+    pub fn build(self) -> Result<AdbTcpConnexion> {
+        let tcp_stream = match &self.transport {
+            Some(transport) => AdbStream::custom(transport.connect()?, None),
+            None => AdbStream::tcp(
+                connect(self.socket_addr, self.server_starter.as_ref())?,
+                None,
+            ),
+        };
+        if let Some(idle) = self.tcp_keepalive {
+            tcp_stream.set_keepalive(idle)?;
+        }
+
+        Ok(AdbTcpConnexion {
+            socket_addr: self.socket_addr,
+            tcp_stream,
+            pool: (self.pool_size > 0 && self.transport.is_none())
+                .then(|| ConnectionPool::new(self.socket_addr, self.pool_size, self.idle_timeout)),
+            server_starter: self.server_starter,
+            transport: self.transport,
+            default_device: self.default_device,
+            user: self.user,
+            recorder: None,
+            replayed_connections: None,
+            capabilities: HashMap::new(),
+            device_commands: HashMap::new(),
+            api_levels: HashMap::new(),
+            stat_cache: self.stat_cache.then(StatCache::default),
+            shell_fallback: self.shell_fallback,
+            state: ConnexionState::Host,
+            pending_connection: false,
+            transfer_rate_limit: self.transfer_rate_limit,
+            server_version: None,
+            minimum_server_version: self.minimum_server_version,
+            tcp_keepalive: self.tcp_keepalive,
+            tracking_liveness: self.tracking_liveness,
+            hooks: self.hooks,
+            nested_forward: None,
+        })
+    }
 }
 
 impl AdbTcpConnexion {
     /// Instantiates a new instance of [AdbTcpConnexion]
     pub fn new(address: Ipv4Addr, port: u16) -> Result<Self> {
-        let addr = SocketAddrV4::new(address, port);
+        Self::builder(address, port).build()
+    }
+
+    /// Returns a builder to configure a new instance of [AdbTcpConnexion].
+    pub fn builder(address: Ipv4Addr, port: u16) -> AdbTcpConnexionBuilder {
+        AdbTcpConnexionBuilder {
+            socket_addr: SocketAddrV4::new(address, port),
+            pool_size: 0,
+            idle_timeout: Duration::from_secs(30),
+            server_starter: None,
+            transport: None,
+            default_device: None,
+            user: None,
+            transfer_rate_limit: None,
+            minimum_server_version: None,
+            tcp_keepalive: None,
+            tracking_liveness: None,
+            stat_cache: false,
+            shell_fallback: false,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Instantiates an [AdbTcpConnexion] opening connections to the ADB server by calling `connect`,
+    /// e.g. to go through a TLS wrapper or an in-memory pipe. See [AdbTcpConnexionBuilder::transport].
+    pub fn from_transport<S, F>(connect: F) -> Result<Self>
+    where
+        S: AdbTransportStream,
+        F: Fn() -> std::io::Result<S> + Send + Sync + 'static,
+    {
+        Self::builder(Ipv4Addr::LOCALHOST, 0)
+            .transport(connect)
+            .build()
+    }
+
+    /// Instantiates an [AdbTcpConnexion] replaying the session recorded at `path` by [AdbTcpConnexion::start_recording].
+    ///
+    /// Each connection a command opens is served the bytes received on the next recorded connection,
+    /// and no ADB server is involved. Bytes sent by commands are discarded.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self> {
         Ok(Self {
-            socket_addr: addr,
-            tcp_stream: TcpStream::connect(addr)?,
+            socket_addr: SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
+            tcp_stream: AdbStream::replay(vec![]),
+            pool: None,
+            server_starter: None,
+            transport: None,
+            default_device: None,
+            user: None,
+            recorder: None,
+            replayed_connections: Some(read_session(path)?),
+            capabilities: HashMap::new(),
+            device_commands: HashMap::new(),
+            api_levels: HashMap::new(),
+            stat_cache: None,
+            shell_fallback: false,
+            state: ConnexionState::Host,
+            pending_connection: false,
+            transfer_rate_limit: None,
+            server_version: None,
+            minimum_server_version: None,
+            tcp_keepalive: None,
+            tracking_liveness: None,
+            hooks: Vec::new(),
+            nested_forward: None,
         })
     }
 
-    /// Creates a new connection to ADB server.
+    /// Records every byte exchanged with ADB server by following commands into a session file located at `path`.
+    ///
+    /// Session file can be attached to bug reports, and replayed with [AdbTcpConnexion::replay].
+    pub fn start_recording<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.recorder = Some(SessionRecorder::create(path, RecordFormat::Session)?);
+        Ok(())
+    }
+
+    /// Logs every byte exchanged with ADB server by following commands into the file located at `path`, in a format similar to
+    /// the `writex`/`readx` lines of adb with `ADB_TRACE=all`.
+    ///
+    /// Both logs can then be compared when reporting a behavior differing from adb. Stopped by [AdbTcpConnexion::stop_recording].
+    pub fn start_tracing<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.recorder = Some(SessionRecorder::create(path, RecordFormat::AdbTrace)?);
+        Ok(())
+    }
+
+    /// Stops recording exchanged bytes started by [AdbTcpConnexion::start_recording] or [AdbTcpConnexion::start_tracing].
+    pub fn stop_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    /// Closes the connection of the last command, stopping the service it left running (e.g. `track_devices` or a command run on the device).
+    ///
+    /// Following commands open new connections as usual.
+    pub fn close(&mut self) -> Result<()> {
+        Ok(self.tcp_stream.close()?)
+    }
+
+    /// Re-establishes device tracking connections receiving no update for `period`, or lost, instead of failing.
+    /// `None` lets tracking fail when its connection is lost.
+    pub fn set_tracking_liveness(&mut self, period: Option<Duration>) {
+        self.tracking_liveness = period;
+    }
+
+    /// Limits the throughput of following file transfers to `bytes_per_second`, or removes the limit with `None`.
+    pub fn set_transfer_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.transfer_rate_limit = bytes_per_second;
+    }
+
+    /// Calls `hook` around following requests sent to the ADB server, see [AdbTcpConnexionBuilder::hook].
+    pub fn add_hook(&mut self, hook: impl AdbCommandHook + 'static) {
+        self.hooks.push(Arc::new(hook));
+    }
+
+    /// Reports a completed file transfer to hooks, and returns its `report`.
+    pub(crate) fn transferred(
+        &self,
+        direction: TransferDirection,
+        report: TransferReport,
+    ) -> TransferReport {
+        for hook in &self.hooks {
+            hook.transfer(direction, &report);
+        }
+        report
+    }
+
+    /// Targets `user` with package manager and application helpers, or all users and the foreground one with `None`.
+    /// See [AdbTcpConnexionBuilder::user].
+    pub fn set_user(&mut self, user: Option<UserId>) {
+        self.user = user;
+    }
+
+    /// Enables or disables the fallback of file transfers denied by the sync service, see [AdbTcpConnexionBuilder::shell_fallback].
+    pub fn set_shell_fallback(&mut self, enabled: bool) {
+        self.shell_fallback = enabled;
+    }
+
+    /// Fails following commands with [RustADBError::IncompatibleServerVersion] if ADB server is older than `version`,
+    /// or removes the requirement with `None`.
+    pub fn set_minimum_server_version(&mut self, version: Option<AdbVersion>) {
+        self.minimum_server_version = version;
+    }
+
+    /// Creates a new connection to ADB server, after checking its version if a minimum one is configured.
     ///
     /// Can be used after requests that closes connection.
     pub(crate) fn new_connection(&mut self) -> Result<()> {
-        self.tcp_stream = TcpStream::connect(self.socket_addr)?;
+        if let Some(required) = self.minimum_server_version {
+            let actual = self.server_version()?;
+            if actual < required {
+                return Err(RustADBError::IncompatibleServerVersion { required, actual });
+            }
+        }
+
+        self.open_connection()
+    }
+
+    /// Creates a new connection to ADB server, without any check.
+    pub(crate) fn open_connection(&mut self) -> Result<()> {
+        if let Some(replayed_connections) = self.replayed_connections.as_mut() {
+            let received = replayed_connections.pop_front().ok_or_else(|| {
+                RustADBError::RecordedSessionError("no more recorded connection".into())
+            })?;
+            self.tcp_stream = AdbStream::replay(received);
+            self.state = ConnexionState::Host;
+            return Ok(());
+        }
+
+        if let Some(transport) = &self.transport {
+            self.tcp_stream = AdbStream::custom(transport.connect()?, self.recorder.clone());
+            self.state = ConnexionState::Host;
+            return Ok(());
+        }
+
+        self.state = ConnexionState::Host;
+        // Connections opened in advance are taken once the type of service requested is known
+        if self.pool.is_some() {
+            self.pending_connection = true;
+            return Ok(());
+        }
+        self.connect_tcp(None)?;
 
         Ok(())
     }
 
+    /// Connects to ADB server, with a connection opened in advance for `service_type` if there is one.
+    ///
+    /// Returns whether the connection has been taken from the pool.
+    fn connect_tcp(&mut self, service_type: Option<ServiceType>) -> Result<bool> {
+        self.pending_connection = false;
+        let pooled = service_type.and_then(|service_type| self.pool.as_ref()?.take(service_type));
+        let from_pool = pooled.is_some();
+        let tcp_stream = match pooled {
+            Some(tcp_stream) => tcp_stream,
+            None => connect(self.socket_addr, self.server_starter.as_ref())?,
+        };
+        self.tcp_stream = AdbStream::tcp(tcp_stream, self.recorder.clone());
+        if let Some(idle) = self.tcp_keepalive {
+            self.tcp_stream.set_keepalive(idle)?;
+        }
+
+        Ok(from_pool)
+    }
+
     pub(crate) fn proxy_connexion(
         &mut self,
         adb_command: AdbCommand,
@@ -44,18 +456,7 @@ impl AdbTcpConnexion {
         self.send_adb_request(adb_command)?;
 
         if with_response {
-            let length = self.get_body_length()?;
-            let mut body = vec![
-                0;
-                length
-                    .try_into()
-                    .map_err(|_| RustADBError::ConvertionError)?
-            ];
-            if length > 0 {
-                self.tcp_stream.read_exact(&mut body)?;
-            }
-
-            Ok(body)
+            service::read_length_prefixed(&mut self.tcp_stream)
         } else {
             Ok(vec![])
         }
@@ -65,46 +466,109 @@ impl AdbTcpConnexion {
     /// If an error occured, a [RustADBError] is returned with the response error string.
     pub(crate) fn send_adb_request(&mut self, command: AdbCommand) -> Result<()> {
         let adb_command_string = command.to_string();
-        let adb_request = format!("{:04x}{}", adb_command_string.len(), adb_command_string);
-
-        self.tcp_stream.write_all(adb_request.as_bytes())?;
-
-        // Reads returned status code from ADB server
-        let mut request_status = [0; 4];
-        self.tcp_stream.read_exact(&mut request_status)?;
-
-        match AdbRequestStatus::from_str(str::from_utf8(request_status.as_ref())?)? {
-            AdbRequestStatus::Fail => {
-                // We can keep reading to get further details
-                let length = self.get_body_length()?;
-
-                let mut body = vec![
-                    0;
-                    length
-                        .try_into()
-                        .map_err(|_| RustADBError::ConvertionError)?
-                ];
-                if length > 0 {
-                    self.tcp_stream.read_exact(&mut body)?;
+        let mut attempt = 1;
+        loop {
+            self.check_state(command.required_state(), &adb_command_string)?;
+            for hook in &self.hooks {
+                hook.before(&adb_command_string);
+            }
+
+            let pooled = match self.pending_connection {
+                true => self.connect_tcp(Some(ServiceType::from(&command)))?,
+                false => false,
+            };
+            let start = Instant::now();
+            let mut result = self.send_request(&adb_command_string);
+            if pooled
+                && matches!(
+                    result,
+                    Err(RustADBError::IOError(_) | RustADBError::ConnectionClosedByPeer)
+                )
+            {
+                // Connections opened in advance are stale once ADB server has been restarted
+                if let Some(pool) = &self.pool {
+                    pool.clear();
                 }
+                self.connect_tcp(None)?;
+                result = self.send_request(&adb_command_string);
+            }
+            let event = CommandEvent {
+                request: &adb_command_string,
+                attempt,
+                duration: start.elapsed(),
+                result: &result,
+            };
+            // Every hook is called, whatever the others return
+            let retries = self
+                .hooks
+                .iter()
+                .filter(|hook| hook.after(&event) == HookAction::Retry)
+                .count();
+
+            // Only requests opening a connection can be sent again on a new one
+            if retries > 0 && result.is_err() && command.required_state() == ConnexionState::Host {
+                self.open_connection()?;
+                attempt += 1;
+                continue;
+            }
+
+            result?;
+            self.state = command.next_state();
+            return Ok(());
+        }
+    }
 
-                Err(RustADBError::ADBRequestFailed(String::from_utf8(body)?))
+    /// Writes `request` and reads the status ADB server answers.
+    fn send_request(&mut self, request: &str) -> Result<()> {
+        self.tcp_stream.write_all(&codec::encode_request(request))?;
+        match self.read_request_status() {
+            Err(RustADBError::AmbiguousDeviceSelector(selector)) => {
+                Err(self.multiple_devices(selector))
             }
-            AdbRequestStatus::Okay => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Error reporting connected devices when ADB server refused a request matching several devices with `selector`.
+    ///
+    /// Falls back to [RustADBError::AmbiguousDeviceSelector] if devices cannot be listed.
+    fn multiple_devices(&mut self, selector: String) -> RustADBError {
+        match self.devices_long() {
+            Ok(devices) => RustADBError::MultipleDevices { selector, devices },
+            Err(_) => RustADBError::AmbiguousDeviceSelector(selector),
         }
     }
 
+    /// Reads a status code sent by ADB server, returning the error matching its message on failure.
+    pub(crate) fn read_request_status(&mut self) -> Result<()> {
+        service::read_status(&mut self.tcp_stream)
+    }
+
     /// Sends the given [SyncCommand] to ADB server, and checks that the request has been taken in consideration.
     pub(crate) fn send_sync_request(&mut self, command: SyncCommand) -> Result<()> {
         // First 4 bytes are the name of the command we want to send
         // (e.g. "SEND", "RECV", "STAT", "LIST")
-        Ok(self.tcp_stream.write_all(command.to_string().as_bytes())?)
+        let command = command.to_string();
+        self.check_state(ConnexionState::Sync, &command)?;
+        Ok(self.tcp_stream.write_all(command.as_bytes())?)
+    }
+
+    /// Checks that the connection is in `required` state before sending `request`.
+    fn check_state(&self, required: ConnexionState, request: &str) -> Result<()> {
+        if self.state != required {
+            return Err(RustADBError::InvalidConnexionState(format!(
+                "{request} requires {required}, connection is in {}",
+                self.state
+            )));
+        }
+
+        Ok(())
     }
 
-    pub(crate) fn get_body_length(&mut self) -> Result<u32> {
+    pub(crate) fn get_body_length(&mut self) -> Result<usize> {
         let mut length = [0; 4];
         self.tcp_stream.read_exact(&mut length)?;
 
-        Ok(u32::from_str_radix(str::from_utf8(&length)?, 16)?)
+        parser::hex_length(&length)
     }
 }
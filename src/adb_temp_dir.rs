@@ -0,0 +1,102 @@
+use std::ops::{Deref, DerefMut};
+
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+/// Directory temporary files are created in, writable by the shell user.
+const TEMP_ROOT: &str = "/data/local/tmp";
+/// Length of the random suffix of temporary directory names.
+const SUFFIX_LENGTH: usize = 10;
+
+/// Uniquely named directory under `/data/local/tmp` on the device, removed with its content when dropped, as returned by [AdbTcpConnexion::temp_dir].
+///
+/// Files pushed by automation can be placed in it instead of ad-hoc paths, which are left behind when a run fails.
+/// The connexion remains usable through the directory, which dereferences to it.
+#[derive(Debug)]
+pub struct AdbTempDir<'a> {
+    connexion: &'a mut AdbTcpConnexion,
+    device: DeviceSelector,
+    path: String,
+    removed: bool,
+}
+
+impl<'a> AdbTempDir<'a> {
+    pub(crate) fn new(connexion: &'a mut AdbTcpConnexion, device: DeviceSelector) -> Result<Self> {
+        let suffix: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(SUFFIX_LENGTH)
+            .map(char::from)
+            .collect();
+        let path = format!("{TEMP_ROOT}/adb_client.{suffix}");
+
+        // Fails if the directory exists, which must not be removed once done
+        let output =
+            connexion.exec_command(&device, format!("mkdir {} 2>&1", shell_quote(&path)))?;
+        if !output.trim_ascii().is_empty() {
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(Self {
+            connexion,
+            device,
+            path,
+            removed: false,
+        })
+    }
+
+    /// Path of the directory on the device.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Path of `name` in the directory on the device.
+    pub fn join(&self, name: &str) -> String {
+        format!("{}/{name}", self.path)
+    }
+
+    /// Removes the directory and its content, and reports failures to do so, unlike dropping it.
+    pub fn remove(mut self) -> Result<()> {
+        self.remove_dir()
+    }
+
+    fn remove_dir(&mut self) -> Result<()> {
+        if self.removed {
+            return Ok(());
+        }
+        self.removed = true;
+
+        let output = self.connexion.exec_command(
+            &self.device,
+            format!("rm -rf {} 2>&1", shell_quote(&self.path)),
+        )?;
+        match output.trim_ascii().is_empty() {
+            true => Ok(()),
+            false => Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            )),
+        }
+    }
+}
+
+impl Deref for AdbTempDir<'_> {
+    type Target = AdbTcpConnexion;
+
+    fn deref(&self) -> &Self::Target {
+        self.connexion
+    }
+}
+
+impl DerefMut for AdbTempDir<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.connexion
+    }
+}
+
+impl Drop for AdbTempDir<'_> {
+    fn drop(&mut self) {
+        let _ = self.remove_dir();
+    }
+}
@@ -1,20 +1,30 @@
+#[cfg(unix)]
 use std::os::unix::prelude::{AsRawFd, RawFd};
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
 
-use termios::{tcsetattr, Termios, TCSANOW, VMIN, VTIME};
+#[cfg(unix)]
+use termios::{cfmakeraw, tcsetattr, Termios, OPOST, TCSANOW, VMIN, VTIME};
 
 use crate::Result;
 
+#[cfg(unix)]
 pub struct ADBTermios {
     fd: RawFd,
     old_termios: Termios,
     new_termios: Termios,
 }
 
+#[cfg(unix)]
 impl ADBTermios {
     pub fn new(fd: impl AsRawFd) -> Result<Self> {
         let mut new_termios = Termios::from_fd(fd.as_raw_fd())?;
-        let old_termios = new_termios; // Saves previous state
-        new_termios.c_lflag = 0;
+        // Saves previous state
+        let old_termios = new_termios;
+        // No line editing, signal generation (Ctrl-C, Ctrl-Z...) nor flow control
+        cfmakeraw(&mut new_termios);
+        // Output processing is kept, so that `\n` of raw shells without PTY still returns to the start of the line
+        new_termios.c_oflag |= OPOST;
         new_termios.c_cc[VTIME] = 0;
         new_termios.c_cc[VMIN] = 1;
 
@@ -30,9 +40,43 @@ impl ADBTermios {
     }
 }
 
+#[cfg(unix)]
 impl Drop for ADBTermios {
     fn drop(&mut self) {
         // Custom drop implementation, restores previous termios structure.
-        tcsetattr(self.fd, TCSANOW, &self.old_termios).unwrap();
+        // Errors cannot be reported from drop, terminal is left as is if it fails.
+        let _ = tcsetattr(self.fd, TCSANOW, &self.old_termios);
+    }
+}
+
+/// Windows console equivalent of termios raw mode.
+#[cfg(windows)]
+pub struct ADBTermios {
+    enabled: bool,
+}
+
+#[cfg(windows)]
+impl ADBTermios {
+    pub fn new(_handle: impl AsRawHandle) -> Result<Self> {
+        // Console mode is process-wide, crossterm handles the console input handle itself
+        Ok(Self { enabled: false })
+    }
+
+    pub fn set_adb_termios(&mut self) -> Result<()> {
+        // No line input, echo nor Ctrl-C processing, and virtual terminal input sequences
+        crossterm::terminal::enable_raw_mode()?;
+        self.enabled = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ADBTermios {
+    fn drop(&mut self) {
+        // Custom drop implementation, restores previous console mode.
+        if self.enabled {
+            let _ = crossterm::terminal::disable_raw_mode();
+        }
     }
 }
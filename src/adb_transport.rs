@@ -0,0 +1,80 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{Shutdown, TcpStream},
+    sync::Arc,
+};
+
+/// Connection to an ADB server over a transport unknown to this crate (e.g. a TLS wrapper, a tunnel or an in-memory pipe).
+///
+/// Given to [AdbTcpConnexionBuilder::transport](crate::AdbTcpConnexionBuilder::transport), which opens one per command.
+pub trait AdbTransportStream: Read + Write + Send + 'static {
+    /// Returns another handle to the same connection, written to by a thread while another one reads (e.g. in shell sessions).
+    ///
+    /// Commands needing it fail on transports that do not support it, which is the default.
+    fn try_clone(&self) -> std::io::Result<Box<dyn AdbTransportStream>> {
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "transport cannot be cloned",
+        ))
+    }
+
+    /// Closes `how` directions of the connection, for all handles. Connections are otherwise closed once dropped.
+    fn shutdown(&self, _how: Shutdown) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for dyn AdbTransportStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AdbTransportStream")
+    }
+}
+
+impl AdbTransportStream for TcpStream {
+    fn try_clone(&self) -> std::io::Result<Box<dyn AdbTransportStream>> {
+        Ok(Box::new(TcpStream::try_clone(self)?))
+    }
+
+    fn shutdown(&self, how: Shutdown) -> std::io::Result<()> {
+        TcpStream::shutdown(self, how)
+    }
+}
+
+#[cfg(unix)]
+impl AdbTransportStream for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> std::io::Result<Box<dyn AdbTransportStream>> {
+        Ok(Box::new(std::os::unix::net::UnixStream::try_clone(self)?))
+    }
+
+    fn shutdown(&self, how: Shutdown) -> std::io::Result<()> {
+        std::os::unix::net::UnixStream::shutdown(self, how)
+    }
+}
+
+/// User-provided way of opening connections to the ADB server.
+#[derive(Clone)]
+pub(crate) struct Transport(
+    Arc<dyn Fn() -> std::io::Result<Box<dyn AdbTransportStream>> + Send + Sync>,
+);
+
+impl Transport {
+    pub(crate) fn new<S, F>(connect: F) -> Self
+    where
+        S: AdbTransportStream,
+        F: Fn() -> std::io::Result<S> + Send + Sync + 'static,
+    {
+        Self(Arc::new(move || {
+            Ok(Box::new(connect()?) as Box<dyn AdbTransportStream>)
+        }))
+    }
+
+    pub(crate) fn connect(&self) -> std::io::Result<Box<dyn AdbTransportStream>> {
+        (self.0)()
+    }
+}
+
+impl std::fmt::Debug for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Transport").finish()
+    }
+}
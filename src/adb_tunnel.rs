@@ -0,0 +1,107 @@
+use std::{
+    net::{Ipv4Addr, Shutdown, SocketAddrV4, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::{
+    models::AdbCommand, AdbServerHandle, AdbTcpConnexion, DeviceSelector, Endpoint, Result,
+};
+
+/// Local TCP port tunnelled to a device, as returned by [AdbTcpConnexion::tunnel] and [AdbTcpConnexion::forward_to_listener].
+///
+/// Each connection accepted opens a device service (e.g. `nc` run on the device, or a device socket) over a new connexion to ADB server.
+/// Dropping it stops accepting connections, connections already tunnelled are left open until either side closes.
+#[derive(Debug)]
+pub struct AdbTunnel {
+    local_addr: SocketAddrV4,
+    stopped: Arc<AtomicBool>,
+}
+
+impl AdbTunnel {
+    /// Starts accepting connections, each one opening `service` on `device` (e.g. `exec:<command>` or `tcp:<port>`).
+    pub(crate) fn new(
+        server: AdbServerHandle,
+        device: DeviceSelector,
+        service: String,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))?;
+        let local_addr = SocketAddrV4::new(Ipv4Addr::LOCALHOST, listener.local_addr()?.port());
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let accepting = stopped.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let (device, service) = (device.clone(), service.clone());
+                std::thread::spawn(move || {
+                    let _ = forward_connection(server, device, service, stream);
+                });
+            }
+        });
+
+        Ok(Self {
+            local_addr,
+            stopped,
+        })
+    }
+
+    /// Local address forwarded through the device.
+    pub fn local_addr(&self) -> SocketAddrV4 {
+        self.local_addr
+    }
+
+    /// Opens a new connexion to the ADB server the tunnel leads to (e.g. one running on a lab host reachable from the device).
+    pub fn connexion(&self) -> Result<AdbTcpConnexion> {
+        AdbTcpConnexion::new(*self.local_addr.ip(), self.local_addr.port())
+    }
+}
+
+impl Drop for AdbTunnel {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes the accepting thread up so that it notices
+        let _ = TcpStream::connect(self.local_addr);
+    }
+}
+
+/// Forwarding of a local port to the ADB server a nested connexion talks to, removed once dropped.
+#[derive(Debug)]
+pub(crate) struct NestedForward {
+    pub(crate) server: AdbServerHandle,
+    pub(crate) device: DeviceSelector,
+    pub(crate) local: Endpoint,
+}
+
+impl Drop for NestedForward {
+    fn drop(&mut self) {
+        let _ = self
+            .server
+            .connexion()
+            .and_then(|mut connexion| connexion.kill_forward(&self.device, self.local.clone()));
+    }
+}
+
+fn forward_connection(
+    server: AdbServerHandle,
+    device: DeviceSelector,
+    service: String,
+    mut stream: TcpStream,
+) -> Result<()> {
+    let input = stream.try_clone()?;
+    let result = server.connexion().and_then(|mut connexion| {
+        connexion.connect_device(&device)?;
+        connexion.send_adb_request(AdbCommand::Service(service))?;
+        connexion.bridge_connection(input, &mut stream)
+    });
+    let _ = stream.shutdown(Shutdown::Both);
+
+    result.map(|_| ())
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    net::{Shutdown, TcpStream},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+};
+
+use rand::RngCore;
+
+use super::{adbd_server::AdbdConfig, adbd_stream::AdbdStream, shell_service, sync_service};
+use crate::{
+    adb_keys,
+    protocol::codec::{MessageHeader, MESSAGE_HEADER_SIZE},
+    Result, RustADBError,
+};
+
+/// Protocol version spoken, the first one allowing to skip data checksums.
+const A_VERSION: u32 = 0x0100_0001;
+/// Maximum data length of messages accepted from the host.
+const MAX_PAYLOAD: usize = 1024 * 1024;
+/// Type of AUTH messages carrying a token for the host to sign.
+const AUTH_TOKEN: u32 = 1;
+/// Type of AUTH messages carrying the signature of the token by the host.
+const AUTH_SIGNATURE: u32 = 2;
+/// Length of tokens signed by hosts.
+const TOKEN_SIZE: usize = 20;
+
+/// Channels to a stream opened by the host, fed by the messages read on the connection.
+#[derive(Debug)]
+struct StreamChannels {
+    /// Id of the stream on host side.
+    remote_id: u32,
+    /// Data written by the host.
+    input: Sender<Vec<u8>>,
+    /// Acknowledgments of data written to the host.
+    acks: Sender<()>,
+}
+
+/// Represents a connection from an ADB server, shared by the services of the streams it opened.
+#[derive(Debug)]
+pub(crate) struct AdbdConnection {
+    writer: Mutex<TcpStream>,
+    /// Maximum data length of messages accepted by the host.
+    pub(crate) max_payload: usize,
+    streams: Mutex<HashMap<u32, StreamChannels>>,
+}
+
+impl AdbdConnection {
+    /// Sends a message made of `command`, its arguments and `data`.
+    pub(crate) fn send(&self, command: &[u8; 4], arg0: u32, arg1: u32, data: &[u8]) -> Result<()> {
+        write_message(&mut *self.lock_writer()?, command, arg0, arg1, data)
+    }
+
+    /// Closes stream `local_id` if it is still open, notifying the host.
+    pub(crate) fn close_stream(&self, local_id: u32) -> Result<()> {
+        if let Some(channels) = self.lock_streams()?.remove(&local_id) {
+            self.send(b"CLSE", local_id, channels.remote_id, &[])?;
+        }
+
+        Ok(())
+    }
+
+    fn lock_writer(&self) -> std::io::Result<MutexGuard<'_, TcpStream>> {
+        self.writer
+            .lock()
+            .map_err(|_| std::io::Error::other("adbd connection writer poisoned"))
+    }
+
+    fn lock_streams(&self) -> std::io::Result<MutexGuard<'_, HashMap<u32, StreamChannels>>> {
+        self.streams
+            .lock()
+            .map_err(|_| std::io::Error::other("adbd connection streams poisoned"))
+    }
+}
+
+/// Serves the connection `tcp_stream` from an ADB server until it is closed.
+pub(crate) fn serve(mut tcp_stream: TcpStream, config: Arc<AdbdConfig>) -> Result<()> {
+    let mut reader = tcp_stream.try_clone()?;
+
+    let max_payload = authenticate(&mut reader, &mut tcp_stream, &config)?;
+    let connection = Arc::new(AdbdConnection {
+        writer: Mutex::new(tcp_stream),
+        max_payload,
+        streams: Mutex::new(HashMap::new()),
+    });
+    connection.send(
+        b"CNXN",
+        A_VERSION,
+        MAX_PAYLOAD as u32,
+        config.banner().as_bytes(),
+    )?;
+
+    let result = dispatch_messages(&mut reader, &connection, &config);
+
+    // Services see the end of their input and stop
+    connection.lock_streams()?.clear();
+    let _ = reader.shutdown(Shutdown::Both);
+
+    match result {
+        Err(RustADBError::IOError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(()),
+        result => result,
+    }
+}
+
+/// Waits for the host to connect, and to authenticate if keys are authorized, returning the maximum data length it accepts.
+///
+/// Hosts failing to authenticate are left waiting, as devices do until the user allows them.
+fn authenticate(
+    reader: &mut TcpStream,
+    writer: &mut TcpStream,
+    config: &AdbdConfig,
+) -> Result<usize> {
+    // Maximum data length of the host and token it has to sign, once it connected
+    let mut pending: Option<(usize, [u8; TOKEN_SIZE])> = None;
+    loop {
+        let (header, data) = read_message(reader)?;
+        match (&header.command, &config.authorized_keys) {
+            (b"CNXN", None) => return Ok((header.arg1 as usize).min(MAX_PAYLOAD)),
+            (b"CNXN", Some(_)) => {
+                let max_payload = (header.arg1 as usize).min(MAX_PAYLOAD);
+                pending = Some((max_payload, send_token(writer)?));
+            }
+            (b"AUTH", Some(keys)) if header.arg0 == AUTH_SIGNATURE => {
+                let Some((max_payload, token)) = pending else {
+                    continue;
+                };
+                if keys
+                    .iter()
+                    .any(|key| adb_keys::verify_token(key, &token, &data))
+                {
+                    return Ok(max_payload);
+                }
+
+                // Host signs again with its next key, or sends its public key to be allowed
+                pending = Some((max_payload, send_token(writer)?));
+            }
+            // Public keys sent by hosts are never allowed, and streams cannot be opened yet
+            _ => {}
+        }
+    }
+}
+
+/// Sends a new random token for the host to sign, and returns it.
+fn send_token(writer: &mut TcpStream) -> Result<[u8; TOKEN_SIZE]> {
+    let mut token = [0_u8; TOKEN_SIZE];
+    rand::thread_rng().fill_bytes(&mut token);
+    write_message(writer, b"AUTH", AUTH_TOKEN, 0, &token)?;
+
+    Ok(token)
+}
+
+/// Handles messages sent by the host on an established connection.
+fn dispatch_messages(
+    reader: &mut TcpStream,
+    connection: &Arc<AdbdConnection>,
+    config: &Arc<AdbdConfig>,
+) -> Result<()> {
+    let mut next_id = 1;
+    loop {
+        let (header, data) = read_message(reader)?;
+        match &header.command {
+            b"OPEN" => {
+                let remote_id = header.arg0;
+                let service = String::from_utf8_lossy(&data);
+                let service = service.trim_end_matches('\0').to_string();
+                if !is_supported(&service) {
+                    connection.send(b"CLSE", 0, remote_id, &[])?;
+                    continue;
+                }
+
+                let local_id = next_id;
+                next_id += 1;
+                let (input, input_receiver) = mpsc::channel();
+                let (acks, acks_receiver) = mpsc::channel();
+                connection.lock_streams()?.insert(
+                    local_id,
+                    StreamChannels {
+                        remote_id,
+                        input,
+                        acks,
+                    },
+                );
+                connection.send(b"OKAY", local_id, remote_id, &[])?;
+
+                let stream = AdbdStream::new(
+                    connection.clone(),
+                    local_id,
+                    remote_id,
+                    input_receiver,
+                    acks_receiver,
+                );
+                let config = config.clone();
+                thread::spawn(move || run_service(&service, stream, &config));
+            }
+            b"WRTE" => {
+                let local_id = header.arg1;
+                let channels = connection
+                    .lock_streams()?
+                    .get(&local_id)
+                    .map(|channels| (channels.remote_id, channels.input.clone()));
+
+                // Acknowledged first, so that responses of the service come after
+                if let Some((remote_id, input)) = channels {
+                    connection.send(b"OKAY", local_id, remote_id, &[])?;
+                    let _ = input.send(data);
+                }
+            }
+            b"OKAY" => {
+                if let Some(channels) = connection.lock_streams()?.get(&header.arg1) {
+                    let _ = channels.acks.send(());
+                }
+            }
+            b"CLSE" => connection.close_stream(header.arg1)?,
+            // Host connecting again, e.g. after a restart of the ADB server
+            b"CNXN" => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// Whether `service` is provided: raw shell, exec or sync.
+fn is_supported(service: &str) -> bool {
+    service == "sync:" || shell_service::command(service).is_some()
+}
+
+/// Runs `service` on `stream` until it ends, services closing the stream themselves.
+fn run_service(service: &str, stream: AdbdStream, config: &AdbdConfig) -> Result<()> {
+    match shell_service::command(service) {
+        Some(command) => shell_service::run(stream, command, config),
+        None => sync_service::run(stream),
+    }
+}
+
+/// Writes a message made of `command`, its arguments and `data`.
+fn write_message(
+    writer: &mut TcpStream,
+    command: &[u8; 4],
+    arg0: u32,
+    arg1: u32,
+    data: &[u8],
+) -> Result<()> {
+    let header = MessageHeader::new(command, arg0, arg1, data);
+    writer.write_all(&header.encode())?;
+    writer.write_all(data)?;
+
+    Ok(())
+}
+
+/// Reads a message: its header, then its data, checking its checksum.
+fn read_message(reader: &mut TcpStream) -> Result<(MessageHeader, Vec<u8>)> {
+    let mut header = [0_u8; MESSAGE_HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    let header = MessageHeader::decode(&header)?;
+    if header.data_length as usize > MAX_PAYLOAD {
+        return Err(RustADBError::UnknownResponseType(format!(
+            "message of {} bytes",
+            header.data_length
+        )));
+    }
+
+    let mut data = vec![0; header.data_length as usize];
+    reader.read_exact(&mut data)?;
+    if !header.matches(&data) {
+        return Err(RustADBError::UnknownResponseType(format!(
+            "message {} with invalid checksum",
+            header.command.escape_ascii()
+        )));
+    }
+
+    Ok((header, data))
+}
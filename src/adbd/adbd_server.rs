@@ -0,0 +1,135 @@
+use std::{
+    net::{SocketAddr, SocketAddrV4, TcpListener},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use rsa::RsaPublicKey;
+
+use super::adbd_connection;
+use crate::{adb_keys, Result};
+
+/// Delay before accepting connections again after a failure, e.g. when running out of file descriptors.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Settings shared by the connections of an [AdbdServer].
+#[derive(Debug)]
+pub(crate) struct AdbdConfig {
+    /// Properties advertised to hosts, e.g. `ro.product.model`.
+    properties: Vec<(String, String)>,
+    /// Shell running `shell:` and `exec:` commands.
+    pub(crate) shell: PathBuf,
+    /// Public keys of hosts allowed to connect, or `None` if connections are not authenticated.
+    pub(crate) authorized_keys: Option<Vec<RsaPublicKey>>,
+}
+
+impl AdbdConfig {
+    /// Banner sent to hosts when they connect: `device::<name>=<value>;...`.
+    pub(crate) fn banner(&self) -> String {
+        let mut banner = String::from("device::");
+        for (name, value) in &self.properties {
+            banner.push_str(&format!("{name}={value};"));
+        }
+
+        banner
+    }
+}
+
+/// Represents the device side of ADB, making this host reachable by ADB servers like an Android device (e.g. with `adb connect`).
+///
+/// Raw shells (`shell:`), `exec:` and file transfers (`sync:`) are served with the permissions of the current process.
+/// Hosts have to authenticate with one of the keys authorized by [AdbdServer::authorized_key] or [AdbdServer::authorized_keys_file],
+/// others staying unauthorized as devices waiting for the user to allow them.
+#[derive(Debug)]
+pub struct AdbdServer {
+    listener: TcpListener,
+    config: AdbdConfig,
+}
+
+impl AdbdServer {
+    /// Listens for ADB servers on `address`, usually on port 5555.
+    pub fn bind(address: SocketAddrV4) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            config: AdbdConfig {
+                properties: [
+                    ("ro.product.name", "adbd"),
+                    ("ro.product.model", "adb_client"),
+                    ("ro.product.device", std::env::consts::ARCH),
+                ]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+                shell: PathBuf::from("sh"),
+                authorized_keys: Some(Vec::new()),
+            },
+        })
+    }
+
+    /// Advertises property `name` to hosts, e.g. `ro.product.model` shown by `adb devices -l`.
+    pub fn property<S: ToString>(mut self, name: &str, value: S) -> Self {
+        self.config
+            .properties
+            .retain(|(existing, _)| existing != name);
+        self.config
+            .properties
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Shell running commands of `shell:` and `exec:` services. Defaults to `sh`.
+    pub fn shell<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config.shell = path.into();
+        self
+    }
+
+    /// Allows hosts authenticating with `public_key`, a line of `adb_keys` such as the content of `~/.android/adbkey.pub`.
+    pub fn authorized_key(mut self, public_key: &str) -> Result<Self> {
+        let key = adb_keys::decode_public_key(public_key)?;
+        self.config
+            .authorized_keys
+            .get_or_insert_with(Vec::new)
+            .push(key);
+        Ok(self)
+    }
+
+    /// Allows hosts authenticating with the keys of file `path`, in the format of `/data/misc/adb/adb_keys` on devices.
+    pub fn authorized_keys_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        for line in std::fs::read_to_string(path)?.lines() {
+            if !line.trim().is_empty() {
+                self = self.authorized_key(line)?;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Serves any host without authentication, as adbd does with `ro.adb.secure=0`: only use it on trusted networks.
+    pub fn without_authentication(mut self) -> Self {
+        self.config.authorized_keys = None;
+        self
+    }
+
+    /// Address the server listens on, e.g. to find the port chosen when binding port 0.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves ADB servers connecting, each one on its own thread.
+    ///
+    /// Failures to accept a connection only drop it, e.g. when the host resets it meanwhile.
+    pub fn serve(self) -> Result<()> {
+        let config = Arc::new(self.config);
+        for tcp_stream in self.listener.incoming() {
+            let Ok(tcp_stream) = tcp_stream else {
+                thread::sleep(ACCEPT_RETRY_DELAY);
+                continue;
+            };
+            let config = config.clone();
+            thread::spawn(move || adbd_connection::serve(tcp_stream, config));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    io::{Read, Write},
+    sync::{mpsc::Receiver, Arc},
+};
+
+use super::adbd_connection::AdbdConnection;
+use crate::Result;
+
+/// Represents a stream opened by the host, carrying the data of a service in both directions.
+#[derive(Debug)]
+pub(crate) struct AdbdStream {
+    input: AdbdInput,
+    output: AdbdOutput,
+}
+
+impl AdbdStream {
+    pub(crate) fn new(
+        connection: Arc<AdbdConnection>,
+        local_id: u32,
+        remote_id: u32,
+        input: Receiver<Vec<u8>>,
+        acks: Receiver<()>,
+    ) -> Self {
+        Self {
+            input: AdbdInput {
+                receiver: input,
+                pending: vec![],
+                position: 0,
+            },
+            output: AdbdOutput {
+                connection,
+                local_id,
+                remote_id,
+                acks,
+            },
+        }
+    }
+
+    /// Splits the stream, to read and write from different threads.
+    pub(crate) fn split(self) -> (AdbdInput, AdbdOutput) {
+        (self.input, self.output)
+    }
+
+    /// Closes the stream, notifying the host unless it closed it first.
+    pub(crate) fn close(&self) -> Result<()> {
+        self.output.close()
+    }
+}
+
+impl Read for AdbdStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for AdbdStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Data written by the host on a stream, ending once the host closes it.
+#[derive(Debug)]
+pub(crate) struct AdbdInput {
+    receiver: Receiver<Vec<u8>>,
+    /// Data received and not read yet.
+    pending: Vec<u8>,
+    position: usize,
+}
+
+impl Read for AdbdInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.pending.len() {
+            let Ok(data) = self.receiver.recv() else {
+                return Ok(0);
+            };
+            self.pending = data;
+            self.position = 0;
+        }
+
+        let length = buf.len().min(self.pending.len() - self.position);
+        buf[..length].copy_from_slice(&self.pending[self.position..self.position + length]);
+        self.position += length;
+
+        Ok(length)
+    }
+}
+
+/// Writes data to the host on a stream, each message being acknowledged by the host before the next one.
+#[derive(Debug)]
+pub(crate) struct AdbdOutput {
+    connection: Arc<AdbdConnection>,
+    local_id: u32,
+    remote_id: u32,
+    acks: Receiver<()>,
+}
+
+impl AdbdOutput {
+    /// Closes the stream, notifying the host unless it closed it first.
+    pub(crate) fn close(&self) -> Result<()> {
+        self.connection.close_stream(self.local_id)
+    }
+}
+
+impl Write for AdbdOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Larger buffers are written in several messages by `write_all`
+        let buf = &buf[..buf.len().min(self.connection.max_payload)];
+        self.connection
+            .send(b"WRTE", self.local_id, self.remote_id, buf)
+            .map_err(std::io::Error::other)?;
+        self.acks.recv().map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::BrokenPipe, "stream closed by the host")
+        })?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+mod adbd_connection;
+mod adbd_server;
+mod adbd_stream;
+mod shell_service;
+mod sync_service;
+
+pub use adbd_server::AdbdServer;
@@ -0,0 +1,88 @@
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    thread,
+};
+
+use super::{
+    adbd_server::AdbdConfig,
+    adbd_stream::{AdbdInput, AdbdOutput, AdbdStream},
+};
+use crate::Result;
+
+/// Command run by a `shell:` or `exec:` service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ShellCommand<'a> {
+    /// Command line, empty for an interactive shell.
+    pub(crate) command: &'a str,
+    /// Whether standard error is sent along standard output (`shell:`), or discarded (`exec:`).
+    pub(crate) with_stderr: bool,
+}
+
+/// Parses `service` as a raw shell (e.g. `shell,TERM=xterm,raw:<command>`) or exec (`exec:<command>`) service.
+pub(crate) fn command(service: &str) -> Option<ShellCommand<'_>> {
+    if let Some(command) = service.strip_prefix("exec:") {
+        return Some(ShellCommand {
+            command,
+            with_stderr: false,
+        });
+    }
+
+    // Shell v2 is not advertised, so hosts only ask for raw shells
+    let (name, command) = service.split_once(':')?;
+    let mut args = name.split(',');
+    if args.next() != Some("shell") || args.any(|arg| arg == "v2") {
+        return None;
+    }
+
+    Some(ShellCommand {
+        command,
+        with_stderr: true,
+    })
+}
+
+/// Runs `command` with its standard input and output bound to `stream`, then closes the stream.
+pub(crate) fn run(stream: AdbdStream, command: ShellCommand, config: &AdbdConfig) -> Result<()> {
+    let (input, mut output) = stream.split();
+    let result = run_process(input, &mut output, command, config);
+    output.close()?;
+
+    result
+}
+
+fn run_process(
+    mut input: AdbdInput,
+    output: &mut AdbdOutput,
+    command: ShellCommand,
+    config: &AdbdConfig,
+) -> Result<()> {
+    let mut process = Command::new(&config.shell);
+    if !command.command.is_empty() {
+        process.arg("-c").arg(command.command);
+    }
+
+    // Standard error shares the pipe of standard output, keeping their order
+    let (mut reader, writer) = std::io::pipe()?;
+    let stderr = match command.with_stderr {
+        true => Stdio::from(writer.try_clone()?),
+        false => Stdio::null(),
+    };
+    process.stdin(Stdio::piped()).stdout(writer).stderr(stderr);
+    let mut child = process.spawn()?;
+    // Ends of the pipe kept by the command would prevent reaching end of output
+    drop(process);
+
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || std::io::copy(&mut input, &mut stdin));
+    }
+
+    let copied = std::io::copy(&mut reader.by_ref(), output);
+    if copied.is_err() {
+        // Host closed the stream
+        let _ = child.kill();
+    }
+    child.wait()?;
+    copied?;
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{Read, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::adbd_stream::AdbdStream;
+use crate::{
+    protocol::{
+        codec,
+        sync::{write_header, write_packet, MAX_PAYLOAD_SIZE},
+    },
+    AdbStatResponse, Result, RustADBError,
+};
+
+/// Serves sync requests sent on `stream` until the host quits, then closes the stream.
+pub(crate) fn run(mut stream: AdbdStream) -> Result<()> {
+    let result = serve(&mut stream);
+    stream.close()?;
+
+    result
+}
+
+fn serve(stream: &mut AdbdStream) -> Result<()> {
+    loop {
+        let mut header = [0_u8; 8];
+        match stream.read_exact(&mut header) {
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            result => result?,
+        }
+
+        let (id, length) = codec::decode_sync_header(&header)?;
+        if length as usize > MAX_PAYLOAD_SIZE {
+            return Err(RustADBError::UnknownResponseType(format!(
+                "sync request {} of {length} bytes",
+                id.escape_ascii()
+            )));
+        }
+        let mut path = vec![0_u8; length as usize];
+        stream.read_exact(&mut path)?;
+        let path = String::from_utf8(path)?;
+
+        match &id {
+            b"STAT" => {
+                stream.write_all(b"STAT")?;
+                write_stat(stream, &stat(fs::symlink_metadata(&path).ok().as_ref()))?;
+            }
+            b"LIST" => list(stream, &path)?,
+            b"RECV" => recv(stream, &path)?,
+            b"SEND" => send(stream, &path)?,
+            b"QUIT" => return Ok(()),
+            _ => {
+                return Err(RustADBError::UnknownResponseType(format!(
+                    "sync request {}",
+                    id.escape_ascii()
+                )))
+            }
+        }
+    }
+}
+
+/// Sends a `DENT` packet for each entry of directory `path`, then `DONE`.
+fn list(stream: &mut AdbdStream, path: &str) -> Result<()> {
+    // Directories which cannot be read are listed as empty
+    for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        stream.write_all(b"DENT")?;
+        write_stat(stream, &stat(entry.metadata().ok().as_ref()))?;
+        stream.write_all(&(name.len() as u32).to_le_bytes())?;
+        stream.write_all(name.as_bytes())?;
+    }
+
+    stream.write_all(b"DONE")?;
+    write_stat(stream, &stat(None))?;
+    stream.write_all(&[0; 4])?;
+
+    Ok(())
+}
+
+/// Sends the content of file `path` in `DATA` packets, then `DONE`.
+fn recv(stream: &mut AdbdStream, path: &str) -> Result<()> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => return write_packet(stream, b"FAIL", e.to_string().as_bytes()),
+    };
+
+    let mut buffer = vec![0_u8; MAX_PAYLOAD_SIZE];
+    loop {
+        let length = file.read(&mut buffer)?;
+        if length == 0 {
+            return write_header(stream, b"DONE", 0);
+        }
+        write_packet(stream, b"DATA", &buffer[..length])?;
+    }
+}
+
+/// Writes `DATA` packets to file `path_and_mode` (`<path>,<mode>`) until `DONE`, then acknowledges them with `OKAY`.
+fn send(stream: &mut AdbdStream, path_and_mode: &str) -> Result<()> {
+    let (path, mode) = path_and_mode
+        .rsplit_once(',')
+        .unwrap_or((path_and_mode, "420"));
+    let path = Path::new(path);
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    // Data is read until the end even if the file cannot be written, to keep the stream usable
+    let mut file = File::create(path);
+    let mut buffer = vec![0_u8; MAX_PAYLOAD_SIZE];
+    let mod_time = loop {
+        let mut header = [0_u8; 8];
+        stream.read_exact(&mut header)?;
+        match codec::decode_sync_header(&header)? {
+            (id, length) if &id == b"DATA" && length as usize <= MAX_PAYLOAD_SIZE => {
+                let data = &mut buffer[..length as usize];
+                stream.read_exact(data)?;
+                if let Ok(output) = file.as_mut() {
+                    if let Err(e) = output.write_all(data) {
+                        file = Err(e);
+                    }
+                }
+            }
+            (id, mod_time) if &id == b"DONE" => break mod_time,
+            (id, _) => {
+                return Err(RustADBError::UnknownResponseType(format!(
+                    "sync send packet {}",
+                    id.escape_ascii()
+                )))
+            }
+        }
+    };
+
+    let result = file.and_then(|file| {
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(mod_time.into()))?;
+        set_mode(&file, mode.parse().unwrap_or(0o644))
+    });
+    match result {
+        Ok(()) => write_header(stream, b"OKAY", 0),
+        Err(e) => write_packet(stream, b"FAIL", e.to_string().as_bytes()),
+    }
+}
+
+/// Metadata sent in sync responses, all zeros for files which do not exist.
+fn stat(metadata: Option<&Metadata>) -> AdbStatResponse {
+    let Some(metadata) = metadata else {
+        return AdbStatResponse {
+            file_perm: 0,
+            file_size: 0,
+            mod_time: 0,
+        };
+    };
+
+    AdbStatResponse {
+        file_perm: mode(metadata),
+        file_size: metadata.len(),
+        mod_time: metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |elapsed| elapsed.as_secs()),
+    }
+}
+
+fn write_stat(stream: &mut AdbdStream, stat: &AdbStatResponse) -> Result<()> {
+    // Sizes and times are truncated to 32 bits in version 1 of sync responses
+    for value in [stat.file_perm, stat.file_size as u32, stat.mod_time as u32] {
+        stream.write_all(&value.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> u32 {
+    std::os::unix::fs::MetadataExt::mode(metadata)
+}
+
+#[cfg(not(unix))]
+fn mode(metadata: &Metadata) -> u32 {
+    match metadata.file_type() {
+        file_type if file_type.is_dir() => 0o40755,
+        file_type if file_type.is_symlink() => 0o120777,
+        _ => 0o100644,
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(file: &File, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    file.set_permissions(fs::Permissions::from_mode(mode & 0o7777))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_file: &File, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
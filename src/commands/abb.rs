@@ -0,0 +1,44 @@
+use std::io::{self, Read};
+
+use crate::{
+    models::{AdbCommand, HostFeatures},
+    utils::shell_quote,
+    AdbTcpConnexion, DeviceSelector, Result,
+};
+
+impl AdbTcpConnexion {
+    /// Runs a binder-based `cmd` service call (e.g. `["package", "list", "packages"]`) on the device and returns its output.
+    ///
+    /// Uses the `abb_exec` service when supported by the device, and falls back to running `cmd` otherwise.
+    pub fn abb(&mut self, device: impl Into<DeviceSelector>, args: &[&str]) -> Result<Vec<u8>> {
+        let device = device.into();
+        self.abb_with_input(&device, args, &mut io::empty())
+    }
+
+    /// Runs a binder-based `cmd` service call on the device, feeding `input` to its standard input.
+    pub(crate) fn abb_with_input(
+        &mut self,
+        device: &DeviceSelector,
+        args: &[&str],
+        input: &mut dyn Read,
+    ) -> Result<Vec<u8>> {
+        if !self.supports(device, HostFeatures::AbbExec)? {
+            self.require_command(device, "cmd")?;
+            // Arguments are kept as is, as they are by `abb_exec`
+            let args = args.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>();
+            return self.exec_command_with_input(device, format!("cmd {}", args.join(" ")), input);
+        }
+
+        self.connect_device(device)?;
+        self.send_adb_request(AdbCommand::AbbExec(
+            args.iter().map(|arg| arg.to_string()).collect(),
+        ))?;
+
+        io::copy(input, &mut self.tcp_stream)?;
+
+        let mut output = Vec::new();
+        self.tcp_stream.read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::{AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+impl AdbTcpConnexion {
+    /// Pushes the OBB file located at `local_obb` to the expansion files directory of `package` on the device.
+    ///
+    /// OBB file must be named `[main|patch].<version code>.<package>.obb`, and is pushed to the external storage of the user set with
+    /// [AdbTcpConnexion::set_user], or of the foreground user (see [AdbTcpConnexion::external_storage_path]). Returns the path of the file on the device.
+    pub fn push_obb<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        local_obb: P,
+    ) -> Result<String> {
+        let device = device.into();
+        let file_name = local_file_name(local_obb.as_ref())?;
+
+        let obb_regex = Regex::new(&format!(
+            r"^(main|patch)\.\d+\.{}\.obb$",
+            regex::escape(package)
+        ))?;
+        if !obb_regex.is_match(&file_name) {
+            return Err(RustADBError::InvalidObbName(file_name));
+        }
+
+        let storage = self.user_storage_path(&device)?;
+        let remote_path = format!("{storage}/Android/obb/{package}/{file_name}");
+        self.push_file(&device, local_obb, &remote_path)?;
+
+        Ok(remote_path)
+    }
+
+    /// Pushes `local_files` to the external files directory of `package` (`<storage>/Android/data/<package>/files`) on the device,
+    /// in the external storage of the user set with [AdbTcpConnexion::set_user], or of the foreground user (see [AdbTcpConnexion::external_storage_path]).
+    ///
+    /// Returns the paths of the files on the device.
+    pub fn push_app_files<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        local_files: &[P],
+    ) -> Result<Vec<String>> {
+        let device = device.into();
+        let storage = self.user_storage_path(&device)?;
+        let mut remote_paths = vec![];
+        for local_file in local_files {
+            let file_name = local_file_name(local_file.as_ref())?;
+            let remote_path = format!("{storage}/Android/data/{package}/files/{file_name}");
+
+            self.push_file(&device, local_file, &remote_path)?;
+            remote_paths.push(remote_path);
+        }
+
+        Ok(remote_paths)
+    }
+}
+
+fn local_file_name(path: &Path) -> Result<String> {
+    Ok(path
+        .file_name()
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a file", path.display()),
+            )
+        })?
+        .to_string_lossy()
+        .into_owned())
+}
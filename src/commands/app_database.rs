@@ -0,0 +1,52 @@
+use crate::{utils::shell_quote, AdbTcpConnexion, AppDatabase, DeviceSelector, Result};
+
+impl AdbTcpConnexion {
+    /// Pulls the SQLite database `name` (e.g. `app.db`) from the `databases` directory of the debuggable application `package`.
+    ///
+    /// Write-ahead log is checkpointed first when `sqlite3` is available on the device. Otherwise `-wal` and `-shm`
+    /// files are pulled along with the database so that no committed data is missing.
+    pub fn pull_app_database(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        name: &str,
+    ) -> Result<AppDatabase> {
+        let device = device.into();
+        let path = format!("databases/{name}");
+
+        let checkpoint = self.run_as(
+            &device,
+            package,
+            &format!(
+                "sqlite3 {} 'PRAGMA wal_checkpoint(TRUNCATE);'",
+                shell_quote(&path)
+            ),
+        )?;
+        // Result is "<busy>|<log frames>|<checkpointed frames>", busy being 0 on success
+        let checkpointed = checkpoint.starts_with(b"0|");
+
+        let mut database = vec![];
+        self.pull_app_file(&device, package, &path, &mut database)?;
+
+        let mut companions = [None, None];
+        for (suffix, content) in ["-wal", "-shm"].iter().zip(companions.iter_mut()) {
+            let companion_path = format!("{path}{suffix}");
+            if self
+                .app_file_size(&device, package, &companion_path)?
+                .is_some()
+            {
+                let mut companion = vec![];
+                self.pull_app_file(&device, package, &companion_path, &mut companion)?;
+                *content = Some(companion);
+            }
+        }
+        let [wal, shm] = companions;
+
+        Ok(AppDatabase {
+            database,
+            wal,
+            shm,
+            checkpointed,
+        })
+    }
+}
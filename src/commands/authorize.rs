@@ -0,0 +1,108 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    adb_keys::user_public_key, utils::shell_quote, AdbTcpConnexion, DeviceSelector, DeviceState,
+    Result, RustADBError,
+};
+
+const ADB_KEYS_PATH: &str = "/data/misc/adb/adb_keys";
+
+/// Delay between checks of the connection state.
+const AUTHORIZATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl AdbTcpConnexion {
+    /// Authorizes the ADB key of this host on the device, without accepting the confirmation dialog. Requires root privileges.
+    ///
+    /// Key is read from `~/.android/adbkey.pub`. See [AdbTcpConnexion::authorize_adb_key].
+    pub fn authorize_host_key(&mut self, device: impl Into<DeviceSelector>) -> Result<()> {
+        let public_key = user_public_key()?;
+        self.authorize_adb_key(device, &public_key)
+    }
+
+    /// Waits for debugging from this host to be allowed on the device, for at most `timeout`.
+    ///
+    /// `on_unauthorized` is called once if the device is found unauthorized, e.g. to ask the user to accept the RSA key prompt.
+    /// Device is allowed to be missing or offline meanwhile. Fails with [RustADBError::Timeout] if it is not authorized in time.
+    pub fn wait_for_authorization(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        timeout: Duration,
+        on_unauthorized: impl FnOnce(),
+    ) -> Result<()> {
+        let device = device.into();
+        let deadline = Instant::now() + timeout;
+        let mut on_unauthorized = Some(on_unauthorized);
+        loop {
+            match self.get_state(&device) {
+                Ok(DeviceState::Device) => return Ok(()),
+                Ok(DeviceState::Unauthorized) => {
+                    if let Some(on_unauthorized) = on_unauthorized.take() {
+                        on_unauthorized();
+                    }
+                }
+                Ok(_)
+                | Err(RustADBError::DeviceNotFound(_) | RustADBError::ADBRequestFailed(_)) => {}
+                Err(e) if e.is_connection_lost() => {}
+                Err(e) => return Err(e),
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RustADBError::Timeout(
+                    "authorization of the device".to_string(),
+                ));
+            }
+            thread::sleep(AUTHORIZATION_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// Authorizes `public_key` (a line of an `adb_keys` file, as written in `adbkey.pub`) on the device. Requires root privileges.
+    ///
+    /// Key is appended to `/data/misc/adb/adb_keys` if not already present, and adbd is restarted to take it into account.
+    /// Works on a rooted device or in recovery mode, as long as `/data` is mounted.
+    pub fn authorize_adb_key(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        public_key: &str,
+    ) -> Result<()> {
+        let device = device.into();
+        if !self.is_root(&device)? {
+            return Err(RustADBError::RootRequired);
+        }
+
+        let public_key = public_key.trim();
+        let encoded_key = public_key
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| RustADBError::InvalidAdbKey("empty public key".into()))?;
+
+        let authorized_keys =
+            self.exec_command(&device, format!("cat {ADB_KEYS_PATH} 2>/dev/null"))?;
+        let already_authorized = String::from_utf8_lossy(&authorized_keys)
+            .lines()
+            .any(|line| line.split_whitespace().next() == Some(encoded_key));
+
+        if !already_authorized {
+            // adbd reads keys as system:shell, SELinux label is restored as well when supported
+            let command = format!(
+                "mkdir -p /data/misc/adb && echo {} >> {ADB_KEYS_PATH} && chown system:shell {ADB_KEYS_PATH} && chmod 640 {ADB_KEYS_PATH} && (restorecon {ADB_KEYS_PATH} 2>/dev/null; true) && echo done",
+                shell_quote(public_key)
+            );
+            let output = self.exec_command(&device, format!("sh -c {}", shell_quote(&command)))?;
+            if output.trim_ascii_end() != b"done" {
+                return Err(RustADBError::ShellCommandFailed(
+                    String::from_utf8_lossy(&output).trim().to_string(),
+                ));
+            }
+        }
+
+        // adbd stops while restarting, the connection running the command may be closed before answering
+        match self.exec_command(&device, "setprop ctl.restart adbd") {
+            Ok(_) | Err(RustADBError::IOError(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use crate::{
+    adb_batch_transfer::BatchDirection, AdbBatchTransfer, AdbTcpConnexion, DeviceSelector,
+};
+
+impl AdbTcpConnexion {
+    /// Pushes each local file to its remote path on the device, one after the other in a single sync session.
+    ///
+    /// Files are transferred while iterating, each iteration returns the outcome of one file.
+    /// When `continue_on_error` is not set, iteration stops after the first failed file.
+    pub fn push_many<I, L, R>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        files: I,
+        continue_on_error: bool,
+    ) -> AdbBatchTransfer<'_>
+    where
+        I: IntoIterator<Item = (L, R)>,
+        L: Into<PathBuf>,
+        R: Into<String>,
+    {
+        let files = files
+            .into_iter()
+            .map(|(local, remote)| (local.into(), remote.into()))
+            .collect();
+
+        AdbBatchTransfer::new(
+            self,
+            device.into(),
+            BatchDirection::Push,
+            files,
+            continue_on_error,
+        )
+    }
+
+    /// Pulls each remote file on the device to its local path, one after the other in a single sync session.
+    ///
+    /// Files are transferred while iterating, each iteration returns the outcome of one file.
+    /// When `continue_on_error` is not set, iteration stops after the first failed file.
+    pub fn pull_many<I, R, L>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        files: I,
+        continue_on_error: bool,
+    ) -> AdbBatchTransfer<'_>
+    where
+        I: IntoIterator<Item = (R, L)>,
+        R: Into<String>,
+        L: Into<PathBuf>,
+    {
+        let files = files
+            .into_iter()
+            .map(|(remote, local)| (local.into(), remote.into()))
+            .collect();
+
+        AdbBatchTransfer::new(
+            self,
+            device.into(),
+            BatchDirection::Pull,
+            files,
+            continue_on_error,
+        )
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+/// Delay between checks of the boot state.
+const BOOT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl AdbTcpConnexion {
+    /// Waits for the device to be fully booted, e.g. after a reboot or flashing, for at most `timeout`.
+    ///
+    /// Device must have reported `sys.boot_completed` and its package manager must answer,
+    /// as the device shows up long before it can be used. Device is allowed to be missing or offline meanwhile.
+    /// Fails with [RustADBError::Timeout] if the device is not ready in time.
+    pub fn wait_for_boot(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let device = device.into();
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.is_boot_completed(&device) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(
+                    RustADBError::DeviceNotFound(_)
+                    | RustADBError::DeviceOffline
+                    | RustADBError::ADBRequestFailed(_),
+                ) => {}
+                Err(e) if e.is_connection_lost() => {}
+                Err(e) => return Err(e),
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RustADBError::Timeout("boot of the device".to_string()));
+            }
+            thread::sleep(BOOT_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    fn is_boot_completed(&mut self, device: &DeviceSelector) -> Result<bool> {
+        if self.get_prop(device, "sys.boot_completed")? != "1" {
+            return Ok(false);
+        }
+
+        // Package manager starts answering a bit after the boot is reported completed
+        let output = self.exec_command(device, "pm path android 2>&1")?;
+        Ok(output.starts_with(b"package:"))
+    }
+}
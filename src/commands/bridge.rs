@@ -0,0 +1,83 @@
+use std::{
+    io::{self, Read, Write},
+    sync::mpsc,
+};
+
+use crate::{AdbTcpConnexion, DeviceSelector, Result};
+
+impl AdbTcpConnexion {
+    /// Runs `command` on the device using the `exec:` service, with `input` streamed to its standard input
+    /// and its standard output streamed to `output`, and returns the number of bytes written to `output`.
+    ///
+    /// Allows streaming data (e.g. audio or custom RPC) to and from a device process without port forwards.
+    /// `input` is read on a separate thread as data comes. Its end is not signaled to the command,
+    /// the bridge ends when the command closes its output.
+    pub fn bridge_stream<R: Read + Send + 'static>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: impl ToString,
+        input: R,
+        output: &mut dyn Write,
+    ) -> Result<u64> {
+        let device = device.into();
+        self.open_exec(&device, command)?;
+        self.bridge_connection(input, output)
+    }
+
+    /// Streams `input` to the service opened on the current connection as data comes, and its output to `output`,
+    /// until the service closes the connection. Returns the number of bytes written to `output`.
+    pub(crate) fn bridge_connection<R: Read + Send + 'static>(
+        &mut self,
+        mut input: R,
+        output: &mut dyn Write,
+    ) -> Result<u64> {
+        self.tcp_stream.set_nodelay(true)?;
+
+        let mut write_stream = self.tcp_stream.try_clone()?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buffer = [0_u8; 16 * 1024];
+            loop {
+                let size = match input.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(size) => size,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        let _ = sender.send(e);
+                        break;
+                    }
+                };
+                // Failures to write are those of the connection, reported when reading output
+                if write_stream.write_all(&buffer[..size]).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut buffer = [0_u8; 16 * 1024];
+        let mut bytes = 0;
+        let result = loop {
+            match self.tcp_stream.read(&mut buffer) {
+                Ok(0) => break Ok(bytes),
+                Ok(size) => {
+                    if let Err(e) = output
+                        .write_all(&buffer[..size])
+                        .and_then(|_| output.flush())
+                    {
+                        break Err(e.into());
+                    }
+                    bytes += size as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e.into()),
+            }
+        };
+
+        // Stops the input thread at its next write
+        let _ = self.close();
+        match receiver.try_recv() {
+            Ok(e) => Err(e.into()),
+            Err(_) => result,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use crate::{
+    models::HostFeatures, utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError,
+};
+
+/// Binaries high-level helpers depend on, probed together the first time a command is looked up on a device.
+const PROBED_COMMANDS: [&str; 5] = ["toybox", "cmd", "bugreportz", "perfetto", "zstd"];
+
+impl AdbTcpConnexion {
+    /// Forgets server version, features, commands and API levels probed so far, e.g. after ADB server or a device has been updated.
+    ///
+    /// They are otherwise probed once per device for the lifetime of the connexion.
+    pub fn clear_capabilities(&mut self) {
+        self.capabilities.clear();
+        self.device_commands.clear();
+        self.api_levels.clear();
+        self.server_version = None;
+    }
+
+    /// Whether `command` (e.g. `perfetto`) can be run on the device, as a binary or a shell builtin.
+    pub fn has_command(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: &str,
+    ) -> Result<bool> {
+        let device = device.into();
+        if let Some(available) = self
+            .device_commands
+            .get(&device)
+            .and_then(|commands| commands.get(command))
+        {
+            return Ok(*available);
+        }
+
+        let known = self.device_commands.entry(device.clone()).or_default();
+        let mut names = PROBED_COMMANDS
+            .into_iter()
+            .filter(|name| !known.contains_key(*name))
+            .collect::<Vec<_>>();
+        if !names.contains(&command) {
+            names.push(command);
+        }
+
+        let script = names
+            .iter()
+            .map(|name| {
+                let name = shell_quote(name);
+                format!("command -v {name} >/dev/null && echo {name}")
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let output = self.exec_command(&device, script)?;
+        let output = String::from_utf8_lossy(&output);
+        let found = output.lines().map(str::trim).collect::<HashSet<_>>();
+
+        let known = self.device_commands.entry(device).or_default();
+        for name in names {
+            known.insert(name.to_string(), found.contains(name));
+        }
+
+        Ok(found.contains(command))
+    }
+
+    /// Fails with [RustADBError::UnsupportedOnDevice] if `command` cannot be run on the device matching `device`.
+    pub(crate) fn require_command(&mut self, device: &DeviceSelector, command: &str) -> Result<()> {
+        match self.has_command(device, command)? {
+            true => Ok(()),
+            false => Err(RustADBError::UnsupportedOnDevice(command.to_string())),
+        }
+    }
+
+    /// Whether `feature` is supported by both ADB server and the device matching `device`.
+    pub(crate) fn supports(
+        &mut self,
+        device: &DeviceSelector,
+        feature: HostFeatures,
+    ) -> Result<bool> {
+        if let Some(features) = self.capabilities.get(device) {
+            return Ok(features.contains(&feature));
+        }
+
+        // Other failures (e.g. device still offline) are not cached, so that features are probed again next time
+        let features = match self.host_features(device) {
+            Ok(features) => features,
+            // Servers predating features negotiation do not know this service, and support none of them
+            Err(RustADBError::ADBRequestFailed(message))
+                if message.contains("unknown host service") =>
+            {
+                vec![]
+            }
+            Err(e) => return Err(e),
+        };
+        let supported = features.contains(&feature);
+        self.capabilities.insert(device.clone(), features);
+
+        Ok(supported)
+    }
+
+    /// Fails with [RustADBError::UnsupportedByServer] if `feature` is not supported by ADB server or the device matching `device`.
+    pub(crate) fn require(&mut self, device: &DeviceSelector, feature: HostFeatures) -> Result<()> {
+        match self.supports(device, feature)? {
+            true => Ok(()),
+            false => Err(RustADBError::UnsupportedByServer(feature.to_string())),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    parser, utils::shell_quote, AdbTcpConnexion, ContentRow, ContentUri, ContentValue,
+    DeviceSelector, Result, RustADBError,
+};
+
+impl AdbTcpConnexion {
+    /// Queries rows of the content provider data at `uri`, e.g. `content://settings/global`.
+    ///
+    /// Only `projection` columns are returned, all of them when empty. Rows can be filtered with a SQL `selection`
+    /// (e.g. `name='adb_enabled'`) and ordered with a SQL `sort_order` (e.g. `name ASC`).
+    pub fn content_query(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        uri: &ContentUri,
+        projection: &[&str],
+        selection: Option<&str>,
+        sort_order: Option<&str>,
+    ) -> Result<Vec<ContentRow>> {
+        let device = device.into();
+        let mut command = format!("content query --uri {}", shell_quote(&uri.to_string()));
+        if !projection.is_empty() {
+            command += &format!(" --projection {}", shell_quote(&projection.join(":")));
+        }
+        if let Some(selection) = selection {
+            command += &format!(" --where {}", shell_quote(selection));
+        }
+        if let Some(sort_order) = sort_order {
+            command += &format!(" --sort {}", shell_quote(sort_order));
+        }
+
+        let output = self.exec_command(&device, command + " 2>&1")?;
+        parser::content_rows(&String::from_utf8(output)?, projection)
+    }
+
+    /// Inserts a row made of `values` by column name into the content provider data at `uri`.
+    pub fn content_insert(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        uri: &ContentUri,
+        values: &[(&str, ContentValue)],
+    ) -> Result<()> {
+        let command = format!(
+            "content insert --uri {}{}",
+            shell_quote(&uri.to_string()),
+            bindings(values)
+        );
+        self.run_content_command(device.into(), command)
+    }
+
+    /// Sets columns of the rows of the content provider data at `uri` matching the SQL `selection` (all rows when `None`) to `values`.
+    pub fn content_update(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        uri: &ContentUri,
+        values: &[(&str, ContentValue)],
+        selection: Option<&str>,
+    ) -> Result<()> {
+        let mut command = format!(
+            "content update --uri {}{}",
+            shell_quote(&uri.to_string()),
+            bindings(values)
+        );
+        if let Some(selection) = selection {
+            command += &format!(" --where {}", shell_quote(selection));
+        }
+
+        self.run_content_command(device.into(), command)
+    }
+
+    /// Deletes the rows of the content provider data at `uri` matching the SQL `selection` (all rows when `None`).
+    pub fn content_delete(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        uri: &ContentUri,
+        selection: Option<&str>,
+    ) -> Result<()> {
+        let mut command = format!("content delete --uri {}", shell_quote(&uri.to_string()));
+        if let Some(selection) = selection {
+            command += &format!(" --where {}", shell_quote(selection));
+        }
+
+        self.run_content_command(device.into(), command)
+    }
+
+    /// Runs a `content` command which does not print anything on success.
+    fn run_content_command(&mut self, device: DeviceSelector, command: String) -> Result<()> {
+        let output = self.exec_command(&device, command + " 2>&1")?;
+
+        match output.trim_ascii().is_empty() {
+            true => Ok(()),
+            false => Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            )),
+        }
+    }
+}
+
+/// `--bind` arguments of `content` command setting columns to `values`.
+fn bindings(values: &[(&str, ContentValue)]) -> String {
+    values
+        .iter()
+        .map(|(column, value)| format!(" --bind {}", shell_quote(&format!("{column}:{value}"))))
+        .collect()
+}
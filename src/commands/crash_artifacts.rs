@@ -0,0 +1,155 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Offset, Utc};
+
+use crate::{parser, AdbTcpConnexion, CrashArtifacts, DeviceSelector, Result, RustADBError};
+
+/// DropBox tags of ANR traces.
+const ANR_TAGS: [&str; 2] = ["data_app_anr", "system_app_anr"];
+/// DropBox tags of native crashes, whose entries contain their tombstone.
+const TOMBSTONE_TAGS: [&str; 3] = [
+    "SYSTEM_TOMBSTONE",
+    "data_app_native_crash",
+    "system_app_native_crash",
+];
+
+impl AdbTcpConnexion {
+    /// Gathers ANR traces, tombstones and the crash buffer of logcat produced since `since` into the local `directory`,
+    /// in `anr` and `tombstones` sub-directories and a `crash.log` file.
+    ///
+    /// `/data/anr` and `/data/tombstones` usually require root privileges, the copies kept by DropBox
+    /// (which bugreports include) are collected instead when they cannot be read.
+    pub fn collect_crash_artifacts<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        since: DateTime<Utc>,
+        directory: P,
+    ) -> Result<CrashArtifacts> {
+        let device = device.into();
+        let directory = directory.as_ref();
+        let mut artifacts = CrashArtifacts::default();
+
+        for (remote_directory, name, tags) in [
+            ("/data/anr", "anr", ANR_TAGS.as_slice()),
+            ("/data/tombstones", "tombstones", TOMBSTONE_TAGS.as_slice()),
+        ] {
+            let local_directory = directory.join(name);
+            fs::create_dir_all(&local_directory)?;
+
+            let files = match self.recent_files(&device, remote_directory, since)? {
+                Some(paths) => self.pull_crash_files(&device, &paths, &local_directory)?,
+                None => {
+                    artifacts.from_dropbox = true;
+                    self.write_dropbox_entries(&device, tags, since, &local_directory)?
+                }
+            };
+            match name {
+                "anr" => artifacts.anr_traces = files,
+                _ => artifacts.tombstones = files,
+            }
+        }
+
+        // Logcat accepts times since epoch, which do not depend on the time zone of the device
+        let crash_log = self.exec_command(
+            &device,
+            format!("logcat -b crash -d -T {}.000 2>&1", since.timestamp()),
+        )?;
+        if !crash_log.trim_ascii().is_empty() {
+            let path = directory.join("crash.log");
+            fs::write(&path, crash_log)?;
+            artifacts.crash_log = Some(path);
+        }
+
+        Ok(artifacts)
+    }
+
+    /// Paths of the files of `directory` modified since `since`, `None` if `directory` cannot be read.
+    fn recent_files(
+        &mut self,
+        device: &DeviceSelector,
+        directory: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Vec<String>>> {
+        let output = self.exec_command(
+            device,
+            format!("if [ -r {directory} ] && [ -x {directory} ]; then stat -c '%Y %n' {directory}/* 2>/dev/null; else echo -n F; fi"),
+        )?;
+        if output == b"F" {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            String::from_utf8(output)?
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .filter(|(modified, _)| {
+                    modified
+                        .parse::<i64>()
+                        .is_ok_and(|modified| modified >= since.timestamp())
+                })
+                .map(|(_, path)| path.to_string())
+                .collect(),
+        ))
+    }
+
+    fn pull_crash_files(
+        &mut self,
+        device: &DeviceSelector,
+        paths: &[String],
+        local_directory: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            let local_path = local_directory.join(name);
+            self.recv(device, path, &mut File::create(&local_path)?)?;
+            files.push(local_path);
+        }
+
+        Ok(files)
+    }
+
+    /// Writes DropBox entries of `tags` added since `since` to `local_directory`, one file per entry.
+    fn write_dropbox_entries(
+        &mut self,
+        device: &DeviceSelector,
+        tags: &[&str],
+        since: DateTime<Utc>,
+        local_directory: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        // Entries are dated in the local time of the device
+        let offset = self.exec_command(device, "date +%z")?;
+        let offset = String::from_utf8(offset)?;
+        let offset = DateTime::parse_from_str(
+            &format!("1970-01-01 00:00:00 {}", offset.trim()),
+            "%Y-%m-%d %H:%M:%S %z",
+        )
+        .map_or(Utc.fix(), |date| *date.offset());
+
+        let mut files = vec![];
+        for tag in tags {
+            let output =
+                self.exec_command(device, format!("dumpsys dropbox --print {tag} 2>&1"))?;
+            let entries = parser::dropbox_entries(&String::from_utf8_lossy(&output))?;
+            for (index, (time, entry_tag, content)) in entries.into_iter().enumerate() {
+                let time = time
+                    .and_local_timezone(offset)
+                    .single()
+                    .ok_or(RustADBError::ConvertionError)?;
+                if entry_tag != *tag || time < since {
+                    continue;
+                }
+
+                let local_path =
+                    local_directory.join(format!("{tag}@{}_{index}.txt", time.timestamp()));
+                fs::write(&local_path, content)?;
+                files.push(local_path);
+            }
+        }
+
+        Ok(files)
+    }
+}
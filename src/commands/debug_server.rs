@@ -0,0 +1,87 @@
+use std::{path::Path, thread, time::Duration};
+
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, DebugServer, DebugTarget, DeviceSelector, Endpoint,
+    Result, RustADBError,
+};
+
+/// Directory of the device where debug servers are pushed.
+const DEBUG_SERVER_DIR: &str = "/data/local/tmp";
+/// Number of checks that the debug server listens, made every 100ms.
+const LISTEN_CHECKS: u32 = 50;
+
+impl AdbTcpConnexion {
+    /// Pushes the debug server binary `binary` to the device, starts it on TCP `port` of the device debugging `target`,
+    /// and forwards a free TCP port of the host to it.
+    ///
+    /// Returns the endpoint on the host, to connect a debugger to (e.g. `target remote :<port>` in `gdb`).
+    /// Debug server serves a single debugger connection and exits.
+    /// Attaching to processes of other users requires adbd to run as root.
+    pub fn start_debug_server<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        server: DebugServer,
+        binary: P,
+        target: DebugTarget,
+        port: u16,
+    ) -> Result<Endpoint> {
+        let device = device.into();
+        let binary = binary.as_ref();
+        let name = binary
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("debug-server");
+        let remote_path = format!("{DEBUG_SERVER_DIR}/{name}");
+        self.push_file(&device, binary, &remote_path)?;
+
+        let remote_path = shell_quote(&remote_path);
+        let command = match (server, &target) {
+            (DebugServer::Gdbserver, DebugTarget::Pid(pid)) => {
+                format!("{remote_path} --once :{port} --attach {pid}")
+            }
+            (DebugServer::Gdbserver, DebugTarget::Command(command)) => {
+                format!("{remote_path} --once :{port} {command}")
+            }
+            (DebugServer::LldbServer, DebugTarget::Pid(pid)) => {
+                format!("{remote_path} gdbserver :{port} --attach {pid}")
+            }
+            (DebugServer::LldbServer, DebugTarget::Command(command)) => {
+                format!("{remote_path} gdbserver :{port} -- {command}")
+            }
+        };
+        let log_path = format!("{remote_path}.log");
+        // Started in the background, so that it outlives the connection starting it
+        self.exec_command(
+            &device,
+            format!("chmod 755 {remote_path} && ({command} </dev/null >{log_path} 2>&1 &)"),
+        )?;
+
+        let mut checks = 0;
+        while !self.is_listening(&device, port)? {
+            checks += 1;
+            if checks == LISTEN_CHECKS {
+                let log = self.exec_command(&device, format!("cat {log_path}"))?;
+                return Err(RustADBError::ADBRequestFailed(format!(
+                    "{server} does not listen on port {port}: {}",
+                    String::from_utf8_lossy(&log).trim()
+                )));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        let local_port = self.forward_free_port(&device, Endpoint::Tcp(port))?;
+        Ok(Endpoint::Tcp(local_port))
+    }
+
+    /// Whether a process of the device listens on TCP `port`.
+    fn is_listening(&mut self, device: &DeviceSelector, port: u16) -> Result<bool> {
+        let output = self.exec_command(device, "cat /proc/net/tcp /proc/net/tcp6")?;
+        let local_address = format!(":{port:04X}");
+
+        // Lines list sockets with their local address as second field and their state as fourth one, 0A being LISTEN
+        Ok(String::from_utf8_lossy(&output).lines().any(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            matches!(fields.as_slice(), [_, local, _, "0A", ..] if local.ends_with(&local_address))
+        }))
+    }
+}
@@ -1,10 +1,20 @@
-use std::io::Read;
+use std::{
+    collections::HashMap,
+    io::Read,
+    str::FromStr,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
 
-use crate::{models::AdbCommand, AdbTcpConnexion, Device, DeviceLong, Result, RustADBError};
+use crate::{
+    adb_stream::AdbStream, models::AdbCommand, parser, AdbTcpConnexion, Device, DeviceEvent,
+    DeviceLong, DeviceState, Result,
+};
 
 impl AdbTcpConnexion {
     /// Gets a list of connected devices.
     pub fn devices(&mut self) -> Result<Vec<Device>> {
+        self.new_connection()?;
         let devices = self.proxy_connexion(AdbCommand::Devices, true)?;
 
         let mut vec_devices: Vec<Device> = vec![];
@@ -21,6 +31,7 @@ impl AdbTcpConnexion {
 
     /// Gets an extended list of connected devices including the device paths in the state.
     pub fn devices_long(&mut self) -> Result<Vec<DeviceLong>> {
+        self.new_connection()?;
         let devices_long = self.proxy_connexion(AdbCommand::DevicesLong, true)?;
 
         let mut vec_devices: Vec<DeviceLong> = vec![];
@@ -36,24 +47,192 @@ impl AdbTcpConnexion {
     }
 
     /// Tracks new devices showing up.
+    ///
+    /// Tracking stops when `callback` returns an error, and its connection is then closed.
+    /// With a [tracking liveness](AdbTcpConnexion::set_tracking_liveness), a lost connection is re-established.
     // TODO: Change with Generator when feature stabilizes
     pub fn track_devices(&mut self, callback: impl Fn(Device) -> Result<()>) -> Result<()> {
+        self.start_tracking()?;
+
+        let mut last_update = None;
+        let mut resumed = false;
+        let result = loop {
+            match self.read_tracked_devices(&callback, &mut last_update, resumed) {
+                Err(e) if e.is_connection_lost() && self.tracking_liveness.is_some() => {
+                    let _ = self.close();
+                    if let Err(e) = self.start_tracking() {
+                        break Err(e);
+                    }
+                    resumed = true;
+                }
+                result => break result,
+            }
+        };
+        let _ = self.close();
+        result
+    }
+
+    fn start_tracking(&mut self) -> Result<()> {
+        self.new_connection()?;
         self.send_adb_request(AdbCommand::TrackDevices)?;
+        // ADB server only sends updates on changes, so a silent connection is re-established to check it is still alive
+        self.tcp_stream.set_read_timeout(self.tracking_liveness)?;
 
+        Ok(())
+    }
+
+    fn read_tracked_devices(
+        &mut self,
+        callback: &impl Fn(Device) -> Result<()>,
+        last_update: &mut Option<Vec<u8>>,
+        mut resumed: bool,
+    ) -> Result<()> {
         loop {
             let length = self.get_body_length()?;
+            let mut body = vec![0; length];
+            self.tcp_stream.read_exact(&mut body)?;
 
-            if length > 0 {
-                let mut body = vec![
-                    0;
-                    length
-                        .try_into()
-                        .map_err(|_| RustADBError::ConvertionError)?
-                ];
-                self.tcp_stream.read_exact(&mut body)?;
-
-                callback(Device::try_from(body)?)?;
+            // The list sent again on a re-established connection is only reported if it changed meanwhile
+            let repeated = std::mem::take(&mut resumed) && last_update.as_ref() == Some(&body);
+            if !repeated {
+                // Each update lists all devices, one per line
+                for line in body.split_inclusive(|x| x.eq(&b'\n')) {
+                    callback(Device::try_from(line.to_vec())?)?;
+                }
             }
+            *last_update = Some(body);
         }
     }
+
+    /// Tracks changes in connected devices, and calls `callback` with each [DeviceEvent].
+    ///
+    /// A new state is reported once it has been stable for `debounce`, so that devices flapping
+    /// between states (e.g. while booting) do not flood `callback`. Devices already connected are reported as added.
+    /// Tracking stops when `callback` returns an error.
+    /// With a [tracking liveness](AdbTcpConnexion::set_tracking_liveness), a lost connection is re-established.
+    pub fn track_device_events(
+        &mut self,
+        debounce: Duration,
+        mut callback: impl FnMut(DeviceEvent) -> Result<()>,
+    ) -> Result<()> {
+        let mut receiver = self.start_tracking_reader()?;
+
+        let mut reported: HashMap<String, DeviceState> = HashMap::new();
+        let mut observed: HashMap<String, DeviceState> = HashMap::new();
+        // Devices whose observed state differs from the reported one, with the time it was first observed
+        let mut pending: HashMap<String, Instant> = HashMap::new();
+
+        let result = loop {
+            let now = Instant::now();
+            let wait = pending
+                .values()
+                .map(|since| (*since + debounce).saturating_duration_since(now))
+                .min();
+
+            let received = match wait {
+                Some(wait) => receiver.recv_timeout(wait),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(Ok(devices)) => {
+                    let previous = std::mem::replace(&mut observed, devices);
+                    let now = Instant::now();
+                    for identifier in reported.keys().chain(observed.keys()) {
+                        if reported.get(identifier) == observed.get(identifier) {
+                            pending.remove(identifier);
+                        } else if previous.get(identifier) != observed.get(identifier)
+                            || !pending.contains_key(identifier)
+                        {
+                            // Flapping restarts the debounce period
+                            pending.insert(identifier.clone(), now);
+                        }
+                    }
+                }
+                // Devices listed again on the new connection are compared to the observed ones as any update
+                Ok(Err(e)) if e.is_connection_lost() && self.tracking_liveness.is_some() => {
+                    let _ = self.close();
+                    match self.start_tracking_reader() {
+                        Ok(new_receiver) => receiver = new_receiver,
+                        Err(e) => break Err(e),
+                    }
+                }
+                Ok(Err(e)) => break Err(e),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break Ok(()),
+            }
+
+            let now = Instant::now();
+            let stable = pending
+                .iter()
+                .filter(|(_, since)| now.duration_since(**since) >= debounce)
+                .map(|(identifier, _)| identifier.clone())
+                .collect::<Vec<_>>();
+            let mut callback_result = Ok(());
+            for identifier in stable {
+                pending.remove(&identifier);
+                let event = match (reported.remove(&identifier), observed.get(&identifier)) {
+                    (None, Some(state)) => DeviceEvent::DeviceAdded {
+                        identifier: identifier.clone(),
+                        state: state.clone(),
+                    },
+                    (Some(_), None) => DeviceEvent::DeviceRemoved {
+                        identifier: identifier.clone(),
+                    },
+                    (Some(from), Some(to)) => DeviceEvent::StateChanged {
+                        identifier: identifier.clone(),
+                        from,
+                        to: to.clone(),
+                    },
+                    (None, None) => continue,
+                };
+                if let Some(state) = observed.get(&identifier) {
+                    reported.insert(identifier, state.clone());
+                }
+                callback_result = callback(event);
+                if callback_result.is_err() {
+                    break;
+                }
+            }
+            if callback_result.is_err() {
+                break callback_result;
+            }
+        };
+
+        // Unblocks the reading thread
+        let _ = self.close();
+        result
+    }
+
+    /// Starts tracking devices, with their lists read on a separate thread.
+    fn start_tracking_reader(&mut self) -> Result<Receiver<Result<HashMap<String, DeviceState>>>> {
+        self.start_tracking()?;
+
+        let mut stream = self.tcp_stream.try_clone()?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || loop {
+            let devices = read_device_states(&mut stream);
+            let failed = devices.is_err();
+            if sender.send(devices).is_err() || failed {
+                break;
+            }
+        });
+
+        Ok(receiver)
+    }
+}
+
+/// Reads the next list of devices sent by `host:track-devices`, as states by identifier.
+fn read_device_states(stream: &mut AdbStream) -> Result<HashMap<String, DeviceState>> {
+    let mut length = [0; 4];
+    stream.read_exact(&mut length)?;
+    let length = parser::hex_length(&length)?;
+
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body)?;
+
+    String::from_utf8(body)?
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(identifier, state)| Ok((identifier.to_string(), DeviceState::from_str(state)?)))
+        .collect()
 }
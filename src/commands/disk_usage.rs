@@ -0,0 +1,97 @@
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, DeviceSelector, DiskUsage, FilesystemUsage, Result,
+    RustADBError,
+};
+
+impl AdbTcpConnexion {
+    /// Returns the space usage of every filesystem mounted on the device.
+    pub fn filesystems_usage(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+    ) -> Result<Vec<FilesystemUsage>> {
+        let device = device.into();
+        // Filesystems that cannot be read without root privileges are reported on standard error
+        let output = self.exec_command(&device, "df -k 2>/dev/null")?;
+
+        String::from_utf8_lossy(&output)
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Returns the space used by `path` on the device, and the usage of the filesystem it is stored on.
+    pub fn disk_usage(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: &str,
+    ) -> Result<DiskUsage> {
+        let device = device.into();
+        let path_arg = shell_quote(path);
+
+        let output = self.exec_command(&device, format!("df -k {path_arg}"))?;
+        let filesystem = parse_df_output(&output)?;
+
+        // Size of unreadable subdirectories is not counted, they are reported on standard error
+        let output = self.exec_command(&device, format!("du -sk {path_arg} 2>/dev/null"))?;
+        let output = String::from_utf8_lossy(&output);
+        let size = output
+            .lines()
+            .last()
+            .and_then(|line| line.split_whitespace().next())
+            .and_then(|size| size.parse::<u64>().ok())
+            .ok_or_else(|| {
+                RustADBError::UnknownResponseType(format!("Invalid du output {output}"))
+            })?;
+
+        Ok(DiskUsage {
+            path: path.to_string(),
+            size: size * 1024,
+            filesystem,
+        })
+    }
+
+    /// Fails with [RustADBError::InsufficientSpace] if the filesystem `remote_path` would be written to has less than `needed` bytes available.
+    ///
+    /// `remote_path` does not need to exist yet. Check is skipped when the output of `df` is not understood (e.g. on very old devices).
+    pub(crate) fn check_free_space(
+        &mut self,
+        device: &DeviceSelector,
+        remote_path: &str,
+        needed: u64,
+    ) -> Result<()> {
+        // Directories are created during the transfer, the closest existing one is on the same filesystem
+        let output = self.exec_command(
+            device,
+            format!(
+                "p={}; while [ ! -e \"$p\" ]; do p=$(dirname \"$p\"); done; df -k \"$p\"",
+                shell_quote(remote_path)
+            ),
+        )?;
+
+        let available = match parse_df_output(&output) {
+            Ok(filesystem) => filesystem.free,
+            Err(
+                RustADBError::ADBRequestFailed(_)
+                | RustADBError::UnknownResponseType(_)
+                | RustADBError::ParseIntError(_),
+            ) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        match needed > available {
+            true => Err(RustADBError::InsufficientSpace { needed, available }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Parses the output of `df -k <path>`, made of a header line and the line of the filesystem containing the path.
+fn parse_df_output(output: &[u8]) -> Result<FilesystemUsage> {
+    let output = String::from_utf8_lossy(output);
+    match output.lines().filter(|line| !line.trim().is_empty()).nth(1) {
+        Some(line) => line.parse(),
+        None => Err(RustADBError::ADBRequestFailed(output.trim().to_string())),
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use crate::{
+    AdbTcpConnexion, DeviceSelector, Result, RustADBError, ScreenRotation, SettingsNamespace,
+};
+
+impl AdbTcpConnexion {
+    /// Gets the manual brightness of the screen, from 0 to 255.
+    pub fn brightness(&mut self, device: impl Into<DeviceSelector>) -> Result<u8> {
+        let device = device.into();
+        self.settings_get(&device, SettingsNamespace::System, "screen_brightness")?
+            .ok_or(RustADBError::ConvertionError)
+    }
+
+    /// Sets the brightness of the screen, from 0 to 255, disabling adaptive brightness so that it is kept.
+    pub fn set_brightness(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        brightness: u8,
+    ) -> Result<()> {
+        let device = device.into();
+        self.settings_put(
+            &device,
+            SettingsNamespace::System,
+            "screen_brightness_mode",
+            0,
+        )?;
+        self.settings_put(
+            &device,
+            SettingsNamespace::System,
+            "screen_brightness",
+            brightness,
+        )
+    }
+
+    /// Gets the time without user activity after which the screen turns off.
+    pub fn screen_timeout(&mut self, device: impl Into<DeviceSelector>) -> Result<Duration> {
+        let device = device.into();
+        let timeout: u64 = self
+            .settings_get(&device, SettingsNamespace::System, "screen_off_timeout")?
+            .ok_or(RustADBError::ConvertionError)?;
+
+        Ok(Duration::from_millis(timeout))
+    }
+
+    /// Sets the time without user activity after which the screen turns off.
+    pub fn set_screen_timeout(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let device = device.into();
+        self.settings_put(
+            &device,
+            SettingsNamespace::System,
+            "screen_off_timeout",
+            timeout.as_millis(),
+        )
+    }
+
+    /// Locks the rotation of the screen to its current one, or lets the accelerometer rotate it.
+    pub fn set_rotation_locked(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        locked: bool,
+    ) -> Result<()> {
+        let device = device.into();
+        self.settings_put(
+            &device,
+            SettingsNamespace::System,
+            "accelerometer_rotation",
+            u8::from(!locked),
+        )
+    }
+
+    /// Gets the rotation the screen is locked to, `None` when the accelerometer rotates it.
+    pub fn rotation(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+    ) -> Result<Option<ScreenRotation>> {
+        let device = device.into();
+        let automatic: Option<u8> =
+            self.settings_get(&device, SettingsNamespace::System, "accelerometer_rotation")?;
+        if automatic.unwrap_or(0) != 0 {
+            return Ok(None);
+        }
+
+        let rotation: Option<u8> =
+            self.settings_get(&device, SettingsNamespace::System, "user_rotation")?;
+        ScreenRotation::try_from(rotation.unwrap_or(0)).map(Some)
+    }
+
+    /// Locks the screen to `rotation`.
+    pub fn set_rotation(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        rotation: ScreenRotation,
+    ) -> Result<()> {
+        let device = device.into();
+        self.set_rotation_locked(&device, true)?;
+        self.settings_put(
+            &device,
+            SettingsNamespace::System,
+            "user_rotation",
+            u8::from(rotation),
+        )
+    }
+
+    /// Gets the density of the display in dpi, the overridden one if any.
+    pub fn display_density(&mut self, device: impl Into<DeviceSelector>) -> Result<u32> {
+        let device = device.into();
+        // Physical density: 420
+        // Override density: 360
+        let output = self.run_wm_command(&device, "density")?;
+        last_value(&output, "density:")?.parse().map_err(Into::into)
+    }
+
+    /// Overrides the density of the display in dpi, or restores the physical one with `None`.
+    pub fn set_display_density(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        density: Option<u32>,
+    ) -> Result<()> {
+        let device = device.into();
+        let density = density.map_or("reset".to_string(), |density| density.to_string());
+        self.run_wm_setter(&device, &format!("density {density}"))
+    }
+
+    /// Gets the size of the display in pixels as (width, height), the overridden one if any.
+    pub fn display_size(&mut self, device: impl Into<DeviceSelector>) -> Result<(u32, u32)> {
+        let device = device.into();
+        // Physical size: 1080x2400
+        // Override size: 720x1600
+        let output = self.run_wm_command(&device, "size")?;
+        let (width, height) = last_value(&output, "size:")?
+            .split_once('x')
+            .ok_or(RustADBError::ConvertionError)?;
+
+        Ok((width.parse()?, height.parse()?))
+    }
+
+    /// Overrides the size of the display in pixels as (width, height), or restores the physical one with `None`.
+    pub fn set_display_size(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        size: Option<(u32, u32)>,
+    ) -> Result<()> {
+        let device = device.into();
+        let size = size.map_or("reset".to_string(), |(width, height)| {
+            format!("{width}x{height}")
+        });
+        self.run_wm_setter(&device, &format!("size {size}"))
+    }
+
+    fn run_wm_command(&mut self, device: &DeviceSelector, args: &str) -> Result<String> {
+        let output = self.exec_command(device, format!("wm {args} 2>&1"))?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    fn run_wm_setter(&mut self, device: &DeviceSelector, args: &str) -> Result<()> {
+        // Command does not print anything on success
+        let output = self.run_wm_command(device, args)?;
+        if !output.trim().is_empty() {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Value following `label` on the last line containing it, i.e. the overridden value when there is one.
+fn last_value<'a>(output: &'a str, label: &str) -> Result<&'a str> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(label))
+        .map(|(_, value)| value.trim())
+        .next_back()
+        .ok_or_else(|| RustADBError::ShellCommandFailed(output.trim().to_string()))
+}
@@ -0,0 +1,134 @@
+use std::{
+    io::{self, Read, Write},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use super::recv::copy_limited;
+use crate::{
+    adb_rate_limiter::RateLimiter, models::AdbCommand, AdbShellSession, AdbTcpConnexion,
+    DeviceSelector, Result, RustADBError, ShellOutput,
+};
+
+impl AdbTcpConnexion {
+    /// Runs `command` on the device using the `exec:` service and returns its raw output.
+    ///
+    /// Unlike `shell_command`, no terminal is involved so the output is returned untouched.
+    pub(crate) fn exec_command(
+        &mut self,
+        device: &DeviceSelector,
+        command: impl ToString,
+    ) -> Result<Vec<u8>> {
+        self.exec_command_with_input(device, command, &mut io::empty())
+    }
+
+    /// Runs `command` on the device using the `exec:` service, feeding `input` to its standard input.
+    ///
+    /// Returns the raw output of the command once it exits.
+    pub(crate) fn exec_command_with_input(
+        &mut self,
+        device: &DeviceSelector,
+        command: impl ToString,
+        input: &mut dyn Read,
+    ) -> Result<Vec<u8>> {
+        self.open_exec(device, command)?;
+
+        io::copy(input, &mut self.tcp_stream)?;
+
+        let mut output = Vec::new();
+        self.tcp_stream.read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    /// Runs `command` on the device using the `exec:` service and returns its raw output, closing its connection
+    /// to end it if it does not exit within `timeout`.
+    ///
+    /// Fails with [RustADBError::RemoteCommandTimeout] once the connection has been closed.
+    /// Output is read on a background thread, user-provided transports have to support [AdbTransportStream::try_clone](crate::AdbTransportStream::try_clone).
+    pub fn exec_command_with_timeout(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: impl ToString,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let command = command.to_string();
+        self.open_exec(&device.into(), &command)?;
+
+        // Output is read on a background thread, as not every stream supports read timeouts (e.g. user-provided transports)
+        let mut reader = self.tcp_stream.try_clone()?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let result = reader.read_to_end(&mut output).map(|_| output);
+            let _ = sender.send(result);
+        });
+
+        match receiver.recv_timeout(timeout) {
+            Ok(output) => Ok(output?),
+            Err(RecvTimeoutError::Timeout) => {
+                // Background reader stops once the connection is closed
+                self.tcp_stream.close()?;
+                Err(RustADBError::RemoteCommandTimeout(command))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(RustADBError::ConnectionClosedByPeer),
+        }
+    }
+
+    /// Opens a new connection running `command` on the device using the `exec:` service.
+    ///
+    /// Command's standard input and output can then be accessed through `tcp_stream`.
+    pub(crate) fn open_exec(
+        &mut self,
+        device: &DeviceSelector,
+        command: impl ToString,
+    ) -> Result<()> {
+        self.connect_device(device)?;
+        self.send_adb_request(AdbCommand::Exec(command.to_string()))
+    }
+
+    /// Runs `command` on the device with shell v2 protocol, sending `input` to its standard input until its end, and writing its standard output to `output`.
+    ///
+    /// Both are throttled by `rate_limiter`, if any. Unlike `exec:`, the end of input and the exit status are known: fails with
+    /// [RustADBError::ShellCommandFailed] and the standard error of `command` unless it exits successfully.
+    /// Returns the number of bytes written to `output`.
+    pub(crate) fn run_shell_v2(
+        &mut self,
+        device: &DeviceSelector,
+        command: String,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+        mut rate_limiter: Option<RateLimiter>,
+    ) -> Result<u64> {
+        self.connect_device(device)?;
+        self.send_adb_request(AdbCommand::ShellV2Command(command))?;
+
+        // Output is read in the background, the device cannot block while input is sent
+        let mut session = AdbShellSession::new(self.tcp_stream.try_clone()?, true)?;
+        let mut writer = session.writer();
+        copy_limited(input, &mut writer, &mut rate_limiter)?;
+        writer.close()?;
+
+        let mut written = 0;
+        let mut errors = Vec::new();
+        loop {
+            match session.wait()? {
+                Some(ShellOutput::Stdout(data)) => {
+                    output.write_all(&data)?;
+                    written += data.len() as u64;
+                    if let Some(rate_limiter) = rate_limiter.as_mut() {
+                        rate_limiter.throttle(data.len());
+                    }
+                }
+                Some(ShellOutput::Stderr(data)) => errors.extend_from_slice(&data),
+                Some(ShellOutput::Exit(Some(0))) => return Ok(written),
+                Some(ShellOutput::Exit(_)) | None => {
+                    return Err(RustADBError::ShellCommandFailed(
+                        String::from_utf8_lossy(&errors).trim().to_string(),
+                    ))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::Read;
+
+use crate::{
+    models::AdbCommand, AdbServerHandle, AdbTcpConnexion, DeviceSelector, Endpoint, ForwardManager,
+    ForwardRule, Result,
+};
+
+impl AdbTcpConnexion {
+    /// Forwards connections to `local` on the host to `remote` on the device, and returns the local endpoint.
+    ///
+    /// For [Endpoint::Tcp] port `0`, ADB server chooses a free port, which is set in the returned endpoint.
+    /// An existing forwarding of `local` is replaced, unless `norebind` is set in which case the request fails.
+    pub fn forward(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        local: Endpoint,
+        remote: Endpoint,
+        norebind: bool,
+    ) -> Result<Endpoint> {
+        let device = device.into();
+        self.send_forward(&device, local.clone(), remote, norebind)?;
+        if local != Endpoint::Tcp(0) {
+            return Ok(local);
+        }
+
+        Ok(Endpoint::Tcp(self.read_forwarded_port()?))
+    }
+
+    /// Forwards connections to a free TCP port of the host, chosen by ADB server, to `remote` on the device.
+    ///
+    /// Returns the chosen port.
+    pub fn forward_free_port(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        remote: Endpoint,
+    ) -> Result<u16> {
+        let device = device.into();
+        self.send_forward(&device, Endpoint::Tcp(0), remote, false)?;
+
+        self.read_forwarded_port()
+    }
+
+    /// Asks ADB server to forward `local` to `remote`, and checks that forwarding is set up.
+    fn send_forward(
+        &mut self,
+        device: &DeviceSelector,
+        local: Endpoint,
+        remote: Endpoint,
+        norebind: bool,
+    ) -> Result<()> {
+        let host_prefix = self.host_prefix(device)?;
+        self.new_connection()?;
+
+        self.send_adb_request(AdbCommand::Forward {
+            host_prefix,
+            local,
+            remote,
+            norebind,
+        })?;
+
+        // First status acknowledges the request, the second one tells whether forwarding is set up
+        self.read_request_status()
+    }
+
+    /// Reads the port chosen for a forwarding of TCP port `0`, which follows the status.
+    pub(crate) fn read_forwarded_port(&mut self) -> Result<u16> {
+        let length = self.get_body_length()?;
+        let mut port = vec![0; length];
+        self.tcp_stream.read_exact(&mut port)?;
+
+        Ok(String::from_utf8(port)?.trim().parse()?)
+    }
+
+    /// Removes the forwarding of `local` to the device.
+    pub fn kill_forward(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        local: Endpoint,
+    ) -> Result<()> {
+        let device = device.into();
+        let host_prefix = self.host_prefix(&device)?;
+        self.new_connection()?;
+
+        self.send_adb_request(AdbCommand::KillForward { host_prefix, local })?;
+        self.read_request_status()
+    }
+
+    /// Removes all forwardings, to any device.
+    pub fn kill_forward_all(&mut self) -> Result<()> {
+        self.new_connection()?;
+
+        self.send_adb_request(AdbCommand::KillForwardAll)?;
+        self.read_request_status()
+    }
+
+    /// Returns a [ForwardManager] setting forwards up again when their device reconnects.
+    pub fn forward_manager(&self) -> Result<ForwardManager> {
+        ForwardManager::new(AdbServerHandle::from(self))
+    }
+
+    /// Lists forwardings set up by ADB server, to any device.
+    pub fn list_forward(&mut self) -> Result<Vec<ForwardRule>> {
+        self.new_connection()?;
+        let rules = self.proxy_connexion(AdbCommand::ListForward, true)?;
+
+        String::from_utf8(rules)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
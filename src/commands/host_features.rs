@@ -1,19 +1,19 @@
 use crate::{
     models::{AdbCommand, HostFeatures},
-    AdbTcpConnexion, Result,
+    AdbTcpConnexion, DeviceSelector, Result,
 };
 
 impl AdbTcpConnexion {
     /// Lists available ADB server features.
-    pub fn host_features<S: ToString>(&mut self, serial: &Option<S>) -> Result<Vec<HostFeatures>> {
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
-        }
+    pub fn host_features(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+    ) -> Result<Vec<HostFeatures>> {
+        let device = device.into();
+        let host_prefix = self.host_prefix(&device)?;
+        self.new_connection()?;
 
-        let features = self.proxy_connexion(AdbCommand::HostFeatures, true)?;
+        let features = self.proxy_connexion(AdbCommand::HostFeatures(host_prefix), true)?;
 
         Ok(features
             .split(|x| x.eq(&b','))
@@ -0,0 +1,257 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use super::package::APK_INSTALL_DIR;
+use crate::{
+    models::{AdbCommand, HostFeatures},
+    utils::open_local_file,
+    AdbTcpConnexion, DeviceSelector, Result, RustADBError, TransferReport,
+};
+
+const BLOCK_SIZE: usize = 4096;
+const INCR_MAGIC: &[u8; 4] = b"INCR";
+// Magic + request type + file id + block index
+const REQUEST_SIZE: usize = 12;
+// Chunks sent to the device are kept below this size
+const MAX_CHUNK_SIZE: usize = 128 * 1024;
+
+const REQUEST_SERVING_COMPLETE: i16 = 0;
+const REQUEST_BLOCK_MISSING: i16 = 1;
+const REQUEST_PREFETCH: i16 = 2;
+const REQUEST_DESTROY: i16 = 3;
+
+const BLOCK_TYPE_DATA: u8 = 0;
+const BLOCK_TYPE_HASH: u8 = 1;
+const COMPRESSION_NONE: u8 = 0;
+
+/// Content of an APK Signature Scheme v4 file (`.idsig`).
+struct IncrementalSignature {
+    /// Signature headers, without the verity tree.
+    headers: Vec<u8>,
+    /// Verity tree, sent to the device as hash blocks.
+    tree: Vec<u8>,
+}
+
+impl IncrementalSignature {
+    fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read(path)?;
+
+        // version, hashing info (size-prefixed) and signing info (size-prefixed)
+        let mut offset = 4;
+        for _ in 0..2 {
+            let size = read_le_u32(&content, offset)? as usize;
+            offset += 4 + size;
+        }
+        let tree_size = read_le_u32(&content, offset)? as usize;
+
+        let headers = content
+            .get(..offset)
+            .ok_or(RustADBError::InvalidSignatureFile)?
+            .to_vec();
+        let tree = content
+            .get(offset + 4..offset + 4 + tree_size)
+            .ok_or(RustADBError::InvalidSignatureFile)?
+            .to_vec();
+
+        Ok(Self { headers, tree })
+    }
+}
+
+impl AdbTcpConnexion {
+    /// Installs the APK file located at `apk_path` using incremental installation.
+    ///
+    /// The APK signature file (`<apk_path>.idsig`, as generated by `apksigner`) must be present next to the APK.
+    /// The device starts installing as soon as the blocks it needs have been received, and requests the remaining
+    /// ones while the installation goes on. Installs for the user set with [AdbTcpConnexion::set_user], if any, or for all users.
+    pub fn install_incremental<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        apk_path: P,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        self.require(&device, HostFeatures::AbbExec)?;
+
+        let apk_path = apk_path.as_ref();
+        let mut signature_path = PathBuf::from(apk_path).into_os_string();
+        signature_path.push(".idsig");
+        let signature = IncrementalSignature::from_path(signature_path)?;
+
+        let mut apk = open_local_file(apk_path)?;
+        let apk_size = apk.metadata()?.len();
+        self.check_free_space(&device, APK_INSTALL_DIR, apk_size)?;
+        let file_name = apk_path
+            .file_name()
+            .ok_or_else(|| RustADBError::InstallFailed("invalid APK path".into()))?
+            .to_string_lossy();
+
+        self.connect_device(&device)?;
+
+        let mut args = vec!["package".to_string(), "install-incremental".to_string()];
+        args.extend(self.user_option().split_whitespace().map(String::from));
+        args.push("-r".into());
+        // Files are described as <name>:<size>:<file id>:<base64 signature>:<protocol version>
+        args.push(format!(
+            "{file_name}:{apk_size}:0:{}:1",
+            STANDARD.encode(&signature.headers)
+        ));
+        self.send_adb_request(AdbCommand::AbbExec(args))?;
+
+        let output = self.serve_incremental_blocks(&mut apk, apk_size, &signature)?;
+
+        let output = String::from_utf8(output)?;
+        if output.contains("Success") {
+            Ok(TransferReport::file(apk_size, start))
+        } else {
+            Err(RustADBError::InstallFailed(output.trim().to_string()))
+        }
+    }
+
+    /// Answers block requests sent by the device until it does not need them anymore.
+    ///
+    /// Returns everything the device wrote that was not a block request.
+    fn serve_incremental_blocks(
+        &mut self,
+        apk: &mut File,
+        apk_size: u64,
+        signature: &IncrementalSignature,
+    ) -> Result<Vec<u8>> {
+        let total_blocks = apk_size.div_ceil(BLOCK_SIZE as u64) as usize;
+        let mut sent_blocks = vec![false; total_blocks];
+        let mut done_sent = false;
+
+        // The whole verity tree is sent upfront, device needs it to verify any data block
+        let tree_blocks = signature
+            .tree
+            .chunks(BLOCK_SIZE)
+            .enumerate()
+            .map(|(i, block)| encode_block(BLOCK_TYPE_HASH, i as i32, block))
+            .collect::<Vec<_>>();
+        self.send_incremental_chunks(tree_blocks)?;
+
+        let mut pending = Vec::new();
+        let mut output = Vec::new();
+        let mut buffer = [0_u8; 4096];
+        loop {
+            let size = self.tcp_stream.read(&mut buffer)?;
+            if size == 0 {
+                break;
+            }
+            pending.extend_from_slice(&buffer[..size]);
+
+            let mut serving_complete = false;
+            while let Some(position) = pending.windows(4).position(|w| w == INCR_MAGIC) {
+                if pending.len() < position + REQUEST_SIZE {
+                    output.extend(pending.drain(..position));
+                    break;
+                }
+                output.extend(pending.drain(..position));
+                let request: Vec<u8> = pending.drain(..REQUEST_SIZE).collect();
+
+                let request_type = BigEndian::read_i16(&request[4..6]);
+                let block_index = BigEndian::read_i32(&request[8..12]);
+                let blocks_to_send = match request_type {
+                    REQUEST_BLOCK_MISSING => vec![block_index as usize],
+                    REQUEST_PREFETCH => (0..total_blocks).collect(),
+                    REQUEST_SERVING_COMPLETE => {
+                        serving_complete = true;
+                        vec![]
+                    }
+                    REQUEST_DESTROY => return Ok(output),
+                    _ => vec![],
+                };
+
+                let mut blocks = vec![];
+                for index in blocks_to_send {
+                    if index >= total_blocks || sent_blocks[index] {
+                        continue;
+                    }
+                    apk.seek(SeekFrom::Start((index * BLOCK_SIZE) as u64))?;
+                    let mut data = Vec::with_capacity(BLOCK_SIZE);
+                    Read::by_ref(apk)
+                        .take(BLOCK_SIZE as u64)
+                        .read_to_end(&mut data)?;
+                    blocks.push(encode_block(BLOCK_TYPE_DATA, index as i32, &data));
+                    sent_blocks[index] = true;
+                }
+                self.send_incremental_chunks(blocks)?;
+            }
+
+            if !done_sent && sent_blocks.iter().all(|sent| *sent) {
+                // A block header with file id -1 signals that everything has been sent
+                let mut done = [0_u8; 10];
+                BigEndian::write_i16(&mut done[0..2], -1);
+                self.send_incremental_chunks(vec![done.to_vec()])?;
+                done_sent = true;
+            }
+
+            if serving_complete {
+                break;
+            }
+
+            // Keeps partial requests, and a few bytes in case they are the start of a truncated magic
+            if !pending.starts_with(INCR_MAGIC) {
+                let keep = pending.len().min(INCR_MAGIC.len() - 1);
+                output.extend(pending.drain(..pending.len() - keep));
+            }
+        }
+
+        output.extend(pending);
+        self.tcp_stream.read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    /// Sends encoded blocks to the device, grouped in size-prefixed chunks.
+    fn send_incremental_chunks(&mut self, blocks: Vec<Vec<u8>>) -> Result<()> {
+        let mut chunk = Vec::with_capacity(MAX_CHUNK_SIZE);
+        for block in blocks {
+            if !chunk.is_empty() && chunk.len() + block.len() > MAX_CHUNK_SIZE {
+                self.write_incremental_chunk(&chunk)?;
+                chunk.clear();
+            }
+            chunk.extend(block);
+        }
+
+        if !chunk.is_empty() {
+            self.write_incremental_chunk(&chunk)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_incremental_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        let mut chunk_header = [0_u8; 4];
+        BigEndian::write_i32(&mut chunk_header, chunk.len() as i32);
+        self.tcp_stream.write_all(&chunk_header)?;
+        self.tcp_stream.write_all(chunk)?;
+
+        Ok(())
+    }
+}
+
+/// Encodes a block preceded by its header: file id, block type, compression type, block index and block size.
+fn encode_block(block_type: u8, block_index: i32, data: &[u8]) -> Vec<u8> {
+    let mut block = vec![0_u8; 10];
+    BigEndian::write_i16(&mut block[0..2], 0);
+    block[2] = block_type;
+    block[3] = COMPRESSION_NONE;
+    BigEndian::write_i32(&mut block[4..8], block_index);
+    BigEndian::write_i16(&mut block[8..10], data.len() as i16);
+    block.extend_from_slice(data);
+
+    block
+}
+
+fn read_le_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(LittleEndian::read_u32)
+        .ok_or(RustADBError::InvalidSignatureFile)
+}
@@ -0,0 +1,48 @@
+use crate::{AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+// Kernel log of the previous boot is kept by pstore on recent devices, and in /proc on older ones
+const LAST_KMSG_PATHS: [&str; 3] = [
+    "/sys/fs/pstore/console-ramoops-0",
+    "/sys/fs/pstore/console-ramoops",
+    "/proc/last_kmsg",
+];
+
+impl AdbTcpConnexion {
+    /// Returns the kernel ring buffer of the device.
+    ///
+    /// Most devices restrict it to root, [RustADBError::RootRequired] is returned when access is denied without root privileges.
+    pub fn dmesg(&mut self, device: impl Into<DeviceSelector>) -> Result<Vec<u8>> {
+        let device = device.into();
+        let output = self.exec_command(&device, "dmesg 2>&1")?;
+
+        if output.starts_with(b"dmesg:") {
+            if !self.is_root(&device)? {
+                return Err(RustADBError::RootRequired);
+            }
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(output)
+    }
+
+    /// Returns the kernel log of the previous boot, useful to diagnose boot failures and kernel panics. Requires root privileges.
+    ///
+    /// Returns `None` if the device kept no such log.
+    pub fn pull_last_kmsg(&mut self, device: impl Into<DeviceSelector>) -> Result<Option<Vec<u8>>> {
+        let device = device.into();
+        if !self.is_root(&device)? {
+            return Err(RustADBError::RootRequired);
+        }
+
+        for path in LAST_KMSG_PATHS {
+            let output = self.exec_command(&device, format!("cat {path} 2>/dev/null"))?;
+            if !output.is_empty() {
+                return Ok(Some(output));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -3,6 +3,16 @@ use crate::{models::AdbCommand, AdbTcpConnexion, Result};
 impl AdbTcpConnexion {
     /// Asks the ADB server to quit immediately.
     pub fn kill(&mut self) -> Result<()> {
-        self.proxy_connexion(AdbCommand::Kill, false).map(|_| ())
+        self.new_connection()?;
+        self.proxy_connexion(AdbCommand::Kill, false)?;
+
+        // Connections opened in advance are now closed by the server
+        if let Some(pool) = &self.pool {
+            pool.clear();
+        }
+        // Next server may be another version
+        self.server_version = None;
+
+        Ok(())
     }
 }
@@ -1,6 +1,8 @@
 use crate::{
-    models::{AdbCommand, SyncCommand},
-    AdbTcpConnexion, Result,
+    models::{AdbCommand, HostFeatures, SyncCommand},
+    parser,
+    protocol::codec::SYNC_DENT_V2_SIZE,
+    AdbTcpConnexion, DeviceSelector, DirEntry, Result, RustADBError,
 };
 use byteorder::{ByteOrder, LittleEndian};
 use std::{
@@ -9,29 +11,45 @@ use std::{
 };
 
 impl AdbTcpConnexion {
-    /// Lists files in [path] on the device.
-    pub fn list<S: ToString, A: AsRef<str>>(&mut self, serial: Option<S>, path: A) -> Result<()> {
-        self.new_connection()?;
-
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
+    /// Lists entries of directory `path` on the device, including `.` and `..`.
+    ///
+    /// Result is taken from the stat cache when enabled, see [AdbTcpConnexionBuilder::stat_cache](crate::AdbTcpConnexionBuilder::stat_cache).
+    /// Listing also caches the metadata of the entries as results of [AdbTcpConnexion::stat].
+    ///
+    /// Devices supporting `ls_v2` report 64-bit sizes and entries whose metadata could not be read,
+    /// others report sizes truncated to 32 bits.
+    pub fn list<A: AsRef<str>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: A,
+    ) -> Result<Vec<DirEntry>> {
+        let device = device.into();
+        let path = path.as_ref();
+        if let Some(entries) = self.stat_cache.as_ref().and_then(|c| c.list(&device, path)) {
+            return Ok(entries);
         }
 
+        let v2 = self.supports(&device, HostFeatures::LsV2)?;
+        self.connect_device(&device)?;
+
         // Set device in SYNC mode
         self.send_adb_request(AdbCommand::Sync)?;
 
         // Send a list command
-        self.send_sync_request(SyncCommand::List(path.as_ref()))?;
+        self.send_sync_request(match v2 {
+            true => SyncCommand::ListV2,
+            false => SyncCommand::List,
+        })?;
+
+        let entries = self.handle_list_command(path)?;
+        if let Some(cache) = self.stat_cache.as_mut() {
+            cache.insert_list(&device, path, entries.clone());
+        }
 
-        self.handle_list_command(path)
+        Ok(entries)
     }
 
-    // This command does not seem to work correctly. The devices I test it on just resturn
-    // 'DONE' directly without listing anything.
-    fn handle_list_command<S: AsRef<str>>(&mut self, path: S) -> Result<()> {
+    fn handle_list_command<S: AsRef<str>>(&mut self, path: S) -> Result<Vec<DirEntry>> {
         let mut len_buf = [0_u8; 4];
         LittleEndian::write_u32(&mut len_buf, path.as_ref().len() as u32);
 
@@ -43,29 +61,44 @@ impl AdbTcpConnexion {
             .write_all(path.as_ref().to_string().as_bytes())?;
 
         // Reads returned status code from ADB server
+        let mut entries = vec![];
         let mut response = [0_u8; 4];
         loop {
             self.tcp_stream.read_exact(&mut response)?;
             match str::from_utf8(response.as_ref())? {
                 "DENT" => {
-                    // TODO: Move this to a struct that extract this data, but as the device
-                    // I test this on does not return anything, I can't test it.
-                    let mut file_mod = [0_u8; 4];
-                    let mut file_size = [0_u8; 4];
-                    let mut mod_time = [0_u8; 4];
-                    let mut name_len = [0_u8; 4];
-                    self.tcp_stream.read_exact(&mut file_mod)?;
-                    self.tcp_stream.read_exact(&mut file_size)?;
-                    self.tcp_stream.read_exact(&mut mod_time)?;
-                    self.tcp_stream.read_exact(&mut name_len)?;
-                    let name_len = LittleEndian::read_u32(&name_len);
-                    let mut name_buf = vec![0_u8; name_len as usize];
+                    let mut dent = [0_u8; 16];
+                    self.tcp_stream.read_exact(&mut dent)?;
+                    let (metadata, name_len) = parser::sync_dent(&dent)?;
+                    let mut name_buf = vec![0_u8; name_len];
+                    self.tcp_stream.read_exact(&mut name_buf)?;
+                    entries.push(DirEntry {
+                        name: String::from_utf8(name_buf)?,
+                        metadata,
+                        error: None,
+                    });
+                }
+                "DNT2" => {
+                    let mut dent = [0_u8; SYNC_DENT_V2_SIZE];
+                    self.tcp_stream.read_exact(&mut dent)?;
+                    let (error, metadata, name_len) = parser::sync_dent_v2(&dent)?;
+                    let mut name_buf = vec![0_u8; name_len];
                     self.tcp_stream.read_exact(&mut name_buf)?;
+                    entries.push(DirEntry {
+                        name: String::from_utf8(name_buf)?,
+                        metadata,
+                        error: (error != 0).then_some(error),
+                    });
                 }
+                // Directories which cannot be read are listed as empty
                 "DONE" => {
-                    return Ok(());
+                    return Ok(entries);
+                }
+                x => {
+                    return Err(RustADBError::UnknownResponseType(format!(
+                        "sync list response {x}"
+                    )))
                 }
-                x => println!("Unknown response {}", x),
             }
         }
     }
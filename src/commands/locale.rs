@@ -0,0 +1,126 @@
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, ApiLevel, DeviceSelector, Result, RustADBError,
+    SettingsNamespace,
+};
+
+impl AdbTcpConnexion {
+    /// Gets the locale of the device as a language tag (e.g. `en-US`).
+    pub fn locale(&mut self, device: impl Into<DeviceSelector>) -> Result<String> {
+        let device = device.into();
+        // Locale chosen by the user, the factory one otherwise
+        let locale = self.get_prop(&device, "persist.sys.locale")?;
+        if !locale.is_empty() {
+            return Ok(locale);
+        }
+
+        self.get_prop(&device, "ro.product.locale")
+    }
+
+    /// Sets the locale of the device from a language tag (e.g. `fr-FR`).
+    ///
+    /// From Android 7.0, the locale is stored in `system_locales` setting, applied by the device on its next boot.
+    /// With root privileges, it is applied right away and running apps are notified, which before Android 7.0 is required.
+    pub fn set_locale(&mut self, device: impl Into<DeviceSelector>, locale: &str) -> Result<()> {
+        let device = device.into();
+        let (language, country) = parse_locale(locale)?;
+
+        let nougat = self.api_level(&device)? >= ApiLevel::NOUGAT;
+        if nougat {
+            self.settings_put(&device, SettingsNamespace::System, "system_locales", locale)?;
+        }
+        if !self.is_root(&device)? {
+            return match nougat {
+                true => Ok(()),
+                false => Err(RustADBError::RootRequired),
+            };
+        }
+
+        // Versions before Android 7.0 read language and country separately, the latter being optional
+        let mut properties = vec![("persist.sys.locale", locale)];
+        if !nougat {
+            properties.push(("persist.sys.language", language));
+            properties.push(("persist.sys.country", country));
+        }
+        for (name, value) in properties
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+        {
+            let output = self.exec_command(
+                &device,
+                format!("setprop {name} {} 2>&1", shell_quote(value)),
+            )?;
+            if !output.trim_ascii().is_empty() {
+                return Err(RustADBError::ShellCommandFailed(
+                    String::from_utf8_lossy(&output).trim().to_string(),
+                ));
+            }
+        }
+        self.exec_command(
+            &device,
+            "am broadcast -a android.intent.action.LOCALE_CHANGED",
+        )?;
+
+        Ok(())
+    }
+
+    /// Sets the locale of `package` from a language tag (e.g. `fr-FR`), or restores the one of the device with `None`.
+    ///
+    /// Requires Android 13. Applies to the user set with [AdbTcpConnexion::set_user], if any.
+    pub fn set_app_locale(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        let device = device.into();
+        if let Some(locale) = locale {
+            parse_locale(locale)?;
+        }
+        if self.api_level(&device)? < ApiLevel::TIRAMISU {
+            return Err(RustADBError::UnsupportedOnDevice("cmd locale".to_string()));
+        }
+
+        let user = self.user_option();
+        let locales = locale.unwrap_or_default();
+        let output = self.exec_command(
+            &device,
+            format!(
+                "cmd locale set-app-locales {} {user}--locales '{locales}' 2>&1",
+                shell_quote(package)
+            ),
+        )?;
+        if !output.trim_ascii().is_empty() {
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits language tag `locale` into its language and country (empty if none), failing if it is malformed.
+///
+/// Only letters, digits and dashes are allowed, so that the tag can be passed unquoted to commands.
+fn parse_locale(locale: &str) -> Result<(&str, &str)> {
+    let mut subtags = locale.split('-');
+    let language = subtags.next().unwrap_or_default();
+    let valid = (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && locale
+            .split('-')
+            .all(|subtag| !subtag.is_empty() && subtag.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid {
+        return Err(RustADBError::InvalidLocale(locale.to_string()));
+    }
+
+    // Country is the 2 letters or 3 digits region subtag, after an optional script one (e.g. `zh-Hant-TW`)
+    let country = subtags
+        .find(|subtag| {
+            (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+                || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+        })
+        .unwrap_or_default();
+
+    Ok((language, country))
+}
@@ -0,0 +1,86 @@
+use std::net::SocketAddrV4;
+
+use crate::{AdbEmulatorConsole, AdbTcpConnexion, ApiLevel, DeviceSelector, Result, RustADBError};
+
+/// Location provider replaced by a test provider on devices.
+const MOCKED_PROVIDER: &str = "gps";
+
+impl AdbTcpConnexion {
+    /// Connects to the console of the emulator matching `device`, found from its `emulator-<port>` serial.
+    ///
+    /// Console is expected on the host of ADB server. Fails with [RustADBError::UnsupportedOnDevice] for other devices.
+    pub fn emulator_console(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+    ) -> Result<AdbEmulatorConsole> {
+        let serial = self.get_serial_no(device)?;
+        let port = serial
+            .strip_prefix("emulator-")
+            .and_then(|port| port.parse().ok())
+            .ok_or_else(|| RustADBError::UnsupportedOnDevice("emulator console".to_string()))?;
+
+        AdbEmulatorConsole::connect(SocketAddrV4::new(*self.socket_addr.ip(), port))
+    }
+
+    /// Sets the location reported by the device, in degrees and meters, e.g. to test location-based applications.
+    ///
+    /// Emulators are given the location with `geo fix` on their console. On other devices, shell is allowed to mock locations,
+    /// and a test provider replacing the GPS one reports the location, from Android 12. Altitude is then ignored.
+    pub fn set_location(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        latitude: f64,
+        longitude: f64,
+        altitude: Option<f64>,
+    ) -> Result<()> {
+        let device = device.into();
+        match self.emulator_console(&device) {
+            Ok(mut console) => {
+                // Longitude comes first
+                let mut command = format!("geo fix {longitude} {latitude}");
+                if let Some(altitude) = altitude {
+                    command += &format!(" {altitude}");
+                }
+                console.command(&command)?;
+                return Ok(());
+            }
+            Err(RustADBError::UnsupportedOnDevice(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        if self.api_level(&device)? < ApiLevel::S {
+            return Err(RustADBError::UnsupportedOnDevice(
+                "cmd location providers".to_string(),
+            ));
+        }
+
+        self.run_location_command(
+            &device,
+            "appops set com.android.shell android:mock_location allow",
+        )?;
+        // Test provider may already have been added by a previous call
+        self.exec_command(
+            &device,
+            format!("cmd location providers add-test-provider {MOCKED_PROVIDER}"),
+        )?;
+        self.run_location_command(
+            &device,
+            &format!("cmd location providers set-test-provider-enabled {MOCKED_PROVIDER} true"),
+        )?;
+        self.run_location_command(
+            &device,
+            &format!("cmd location providers set-test-provider-location {MOCKED_PROVIDER} --location {latitude},{longitude}"),
+        )
+    }
+
+    /// Runs `command`, which does not print anything on success.
+    fn run_location_command(&mut self, device: &DeviceSelector, command: &str) -> Result<()> {
+        let output = self.exec_command(device, format!("{command} 2>&1"))?;
+        match output.trim_ascii().is_empty() {
+            true => Ok(()),
+            false => Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fs::File, path::Path, thread, time::Duration};
+
+use crate::{
+    parser, utils::shell_quote, AdbTcpConnexion, DeviceSelector, MemInfo, Result, RustADBError,
+    TransferReport,
+};
+
+/// Path of heap dumps on the device, removed once pulled.
+const HEAP_DUMP_PATH: &str = "/data/local/tmp/adb_client_heap.hprof";
+/// Number of checks that the heap dump is complete, made every 500ms.
+const HEAP_DUMP_CHECKS: u32 = 120;
+
+impl AdbTcpConnexion {
+    /// Gets memory usage of `process`, a pid or a process name (e.g. a package name), from `dumpsys meminfo -d`.
+    pub fn mem_info(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        process: &str,
+    ) -> Result<MemInfo> {
+        let device = device.into();
+        let output = self.exec_command(
+            &device,
+            format!("dumpsys meminfo -d {} 2>&1", shell_quote(process)),
+        )?;
+
+        parser::mem_info(&String::from_utf8_lossy(&output))
+    }
+
+    /// Dumps the Java heap of `process`, a pid or a process name (e.g. a package name), with `am dumpheap`,
+    /// and pulls the hprof file into the local file `local_path`.
+    ///
+    /// Heap dump is removed from the device afterwards. Only debuggable applications can be dumped on user builds.
+    pub fn dump_heap<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        process: &str,
+        local_path: P,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let output = self.exec_command(
+            &device,
+            format!(
+                "rm -f {HEAP_DUMP_PATH}; am dumpheap {}{} {HEAP_DUMP_PATH} 2>&1",
+                self.user_option(),
+                shell_quote(process)
+            ),
+        )?;
+        let output = String::from_utf8_lossy(&output);
+        if output.contains("Error") || output.contains("Exception") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        let result = self
+            .wait_heap_dump(&device)
+            .and_then(|_| self.recv(&device, HEAP_DUMP_PATH, &mut File::create(local_path)?));
+        self.exec_command(&device, format!("rm -f {HEAP_DUMP_PATH}"))?;
+        result
+    }
+
+    /// Waits for the heap dump to be complete, which older versions of `am dumpheap` do not wait for.
+    ///
+    /// Dump is considered complete once its size stops changing.
+    fn wait_heap_dump(&mut self, device: &DeviceSelector) -> Result<()> {
+        let mut previous_size = None;
+        for _ in 0..HEAP_DUMP_CHECKS {
+            let output =
+                self.exec_command(device, format!("wc -c < {HEAP_DUMP_PATH} 2>/dev/null"))?;
+            let size = String::from_utf8_lossy(&output).trim().parse::<u64>().ok();
+            if size.is_some_and(|size| size > 0) && size == previous_size {
+                return Ok(());
+            }
+            previous_size = size;
+            thread::sleep(Duration::from_millis(500));
+        }
+
+        Err(RustADBError::ShellCommandFailed(
+            "heap dump did not complete".to_string(),
+        ))
+    }
+}
@@ -1,11 +1,60 @@
+mod abb;
+mod app_data;
+mod app_database;
+mod authorize;
+mod batch;
+mod boot;
+mod bridge;
+mod capabilities;
+mod content;
+mod crash_artifacts;
+mod debug_server;
 mod devices;
+mod disk_usage;
+mod display;
+mod exec;
+mod forward;
 mod host_features;
+mod incremental;
+mod kernel_log;
 mod kill;
 mod list;
+mod locale;
+mod location;
+mod memory;
+mod network;
+mod package;
+mod permissions;
+mod power;
+mod properties;
 mod reboot;
+mod reconnect;
 mod recv;
+mod remote_file;
+mod reverse;
+mod root;
+mod run_as;
+mod security;
 mod send;
+mod service;
+mod settings;
 mod shell;
+mod simpleperf;
+mod sparse;
 mod stat;
+mod state_snapshot;
+mod storage;
+mod sync;
+mod tail;
+mod tar_transfer;
+#[cfg(feature = "zstd")]
+mod tar_zstd;
+mod temp_dir;
+mod test_prep;
+mod time;
 mod transport;
+mod tunnel;
+mod ui_dump;
+mod users;
 mod version;
+mod wireless;
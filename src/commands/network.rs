@@ -0,0 +1,111 @@
+use crate::{
+    AdbTcpConnexion, ApiLevel, DeviceSelector, NetworkStatus, Result, RustADBError,
+    SettingsNamespace,
+};
+
+impl AdbTcpConnexion {
+    /// Enables or disables Wi-Fi on the device.
+    pub fn set_wifi_enabled(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        enabled: bool,
+    ) -> Result<()> {
+        let device = device.into();
+        // `cmd wifi` appeared with Android 10, `svc wifi` is not reliable anymore on recent versions
+        let command = if self.api_level(&device)? >= ApiLevel::Q {
+            format!(
+                "cmd wifi set-wifi-enabled {}",
+                if enabled { "enabled" } else { "disabled" }
+            )
+        } else {
+            format!("svc wifi {}", if enabled { "enable" } else { "disable" })
+        };
+
+        self.run_network_command(&device, command)
+    }
+
+    /// Enables or disables mobile data on the device.
+    pub fn set_mobile_data_enabled(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        enabled: bool,
+    ) -> Result<()> {
+        let device = device.into();
+        self.run_network_command(
+            &device,
+            format!("svc data {}", if enabled { "enable" } else { "disable" }),
+        )
+    }
+
+    /// Enables or disables airplane mode on the device.
+    ///
+    /// Before Android 9, broadcasting the change requires root privileges.
+    pub fn set_airplane_mode(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        enabled: bool,
+    ) -> Result<()> {
+        let device = device.into();
+        if self.api_level(&device)? >= ApiLevel::PIE {
+            return self.run_network_command(
+                &device,
+                format!(
+                    "cmd connectivity airplane-mode {}",
+                    if enabled { "enable" } else { "disable" }
+                ),
+            );
+        }
+
+        self.settings_put(
+            &device,
+            SettingsNamespace::Global,
+            "airplane_mode_on",
+            u8::from(enabled),
+        )?;
+        self.run_network_command(
+            &device,
+            format!("am broadcast -a android.intent.action.AIRPLANE_MODE --ez state {enabled}"),
+        )
+    }
+
+    /// Gets the network state of the device.
+    pub fn network_status(&mut self, device: impl Into<DeviceSelector>) -> Result<NetworkStatus> {
+        let device = device.into();
+        // Wi-Fi setting is 2 when Wi-Fi has been enabled while in airplane mode
+        let wifi_on: Option<u8> =
+            self.settings_get(&device, SettingsNamespace::Global, "wifi_on")?;
+        let mobile_data: Option<u8> =
+            self.settings_get(&device, SettingsNamespace::Global, "mobile_data")?;
+        let airplane_mode: Option<u8> =
+            self.settings_get(&device, SettingsNamespace::Global, "airplane_mode_on")?;
+
+        let connectivity = self.exec_command(&device, "dumpsys connectivity")?;
+        let connected = String::from_utf8(connectivity)?
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Active default network:"))
+            .is_some_and(|network| network.trim() != "none");
+
+        Ok(NetworkStatus {
+            wifi_enabled: wifi_on.unwrap_or(0) != 0,
+            mobile_data_enabled: mobile_data.unwrap_or(0) != 0,
+            airplane_mode: airplane_mode.unwrap_or(0) != 0,
+            connected,
+        })
+    }
+
+    fn run_network_command(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: String,
+    ) -> Result<()> {
+        let device = device.into();
+        let output = String::from_utf8(self.exec_command(&device, command)?)?;
+
+        // These commands only print something when they fail (or for broadcasts, the result)
+        if output.contains("Exception") || output.contains("Error") || output.contains("Unknown") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(())
+    }
+}
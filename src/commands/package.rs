@@ -0,0 +1,213 @@
+use std::{io::Read, path::Path, time::Instant};
+
+use crate::{
+    models::HostFeatures,
+    parser,
+    utils::{open_local_file, shell_quote, ExactReader},
+    AdbTcpConnexion, ApiLevel, DeviceSelector, InstallStrategy, PackageInfo, Result, RustADBError,
+    TransferReport, UserId, VersionInfo,
+};
+
+/// Directory installed APKs are copied to.
+pub(crate) const APK_INSTALL_DIR: &str = "/data/app";
+/// Temporary location of APKs pushed before being installed.
+const PUSHED_APK_PATH: &str = "/data/local/tmp/adb_client_install.apk";
+
+impl AdbTcpConnexion {
+    /// Gets the version of `package` installed on the device, if any.
+    pub fn package_version(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+    ) -> Result<Option<VersionInfo>> {
+        let device = device.into();
+        let output =
+            self.exec_command(&device, format!("dumpsys package {}", shell_quote(package)))?;
+
+        // Unknown packages do not have any "Packages:" section
+        if !output.windows(9).any(|w| w == b"Packages:") {
+            return Ok(None);
+        }
+
+        Ok(Some(VersionInfo::try_from(output.as_slice())?))
+    }
+
+    /// Gets the state of `package` installed on the device (uid, install times, permissions, signatures), if any.
+    ///
+    /// Permissions and enabled state are the ones of the user set with [AdbTcpConnexion::set_user], or of the first user listed.
+    pub fn package_info(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+    ) -> Result<Option<PackageInfo>> {
+        let device = device.into();
+        let output =
+            self.exec_command(&device, format!("dumpsys package {}", shell_quote(package)))?;
+
+        parser::package_info(&String::from_utf8_lossy(&output), package, self.user)
+    }
+
+    /// Installs an APK of `size` bytes read from `input` on the device, replacing any existing version, and returns statistics of the transfer.
+    ///
+    /// The APK is streamed directly to the package manager, except on devices older than Android 5.0 where it is
+    /// pushed to a temporary file first (see [AdbTcpConnexion::install_strategy]). Installs for the user set with [AdbTcpConnexion::set_user], if any,
+    /// or for all users.
+    /// Fails with [RustADBError::InsufficientSpace] before sending anything if the data partition is too small,
+    /// and with an [std::io::ErrorKind::UnexpectedEof] error if `input` ends before `size` bytes.
+    pub fn install(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        mut input: impl Read,
+        size: u64,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        self.install_apk(&device, &mut input, size, false)
+    }
+
+    /// Installs the APK file located at `apk_path` on the device like [AdbTcpConnexion::install_file],
+    /// granting all runtime permissions it requests if `grant_all` is set.
+    ///
+    /// Devices older than Android 6.0 grant all permissions on install anyway.
+    pub fn install_with_permissions<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        apk_path: P,
+        grant_all: bool,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let mut input = open_local_file(apk_path)?;
+        let size = input.metadata()?.len();
+
+        self.install_apk(&device, &mut input, size, grant_all)
+    }
+
+    fn install_apk(
+        &mut self,
+        device: &DeviceSelector,
+        input: &mut dyn Read,
+        size: u64,
+        grant_all: bool,
+    ) -> Result<TransferReport> {
+        let start = Instant::now();
+        self.check_free_space(device, APK_INSTALL_DIR, size)?;
+
+        let size_arg = size.to_string();
+        let user = self.user_option();
+        // `-g` is rejected by devices without runtime permissions
+        let grant = grant_all && self.api_level(device)? >= ApiLevel::MARSHMALLOW;
+        let options = match grant {
+            true => "-r -g",
+            false => "-r",
+        };
+        let mut input = ExactReader::new(input, size);
+        let output = match self.install_strategy(device)? {
+            InstallStrategy::AbbExec => {
+                let mut args = vec!["package", "install"];
+                args.extend(user.split_whitespace());
+                args.extend(options.split_whitespace());
+                args.extend(["-S", &size_arg]);
+                self.abb_with_input(device, &args, &mut input)?
+            }
+            InstallStrategy::CmdPackage => self.exec_command_with_input(
+                device,
+                format!("cmd package install {user}{options} -S {size_arg}"),
+                &mut input,
+            )?,
+            InstallStrategy::PmStreamed => self.exec_command_with_input(
+                device,
+                format!("pm install {user}{options} -S {size_arg}"),
+                &mut input,
+            )?,
+            InstallStrategy::PmPushed => {
+                self.send(device, &mut input, PUSHED_APK_PATH)?;
+                self.exec_command(
+                    device,
+                    format!(
+                        "pm install {user}{options} {PUSHED_APK_PATH}; rm -f {PUSHED_APK_PATH}"
+                    ),
+                )?
+            }
+        };
+
+        let output = String::from_utf8(output)?;
+        if output.contains("Success") {
+            Ok(TransferReport::file(size, start))
+        } else {
+            Err(RustADBError::InstallFailed(output.trim().to_string()))
+        }
+    }
+
+    /// Way APKs are handed to the package manager of the device, chosen from its features and API level.
+    pub fn install_strategy(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+    ) -> Result<InstallStrategy> {
+        let device = device.into();
+        if self.supports(&device, HostFeatures::AbbExec)? {
+            return Ok(InstallStrategy::AbbExec);
+        }
+
+        Ok(match self.api_level(&device)? {
+            level if level >= ApiLevel::NOUGAT => InstallStrategy::CmdPackage,
+            level if level >= ApiLevel::LOLLIPOP => InstallStrategy::PmStreamed,
+            _ => InstallStrategy::PmPushed,
+        })
+    }
+
+    /// Installs the APK file located at `apk_path` on the device, replacing any existing version.
+    pub fn install_file<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        apk_path: P,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let mut input = open_local_file(apk_path)?;
+        let size = input.metadata()?.len();
+
+        self.install(&device, &mut input, size)
+    }
+
+    /// Installs `package`, already installed for another user, for user `user` (e.g. a work profile).
+    pub fn install_existing(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        user: UserId,
+    ) -> Result<()> {
+        let device = device.into();
+        let output = self.exec_command(
+            &device,
+            format!("pm install-existing --user {user} {}", shell_quote(package)),
+        )?;
+
+        // Package com.example installed for user: 10
+        let output = String::from_utf8(output)?;
+        if output.contains("installed for user") {
+            Ok(())
+        } else {
+            Err(RustADBError::InstallFailed(output.trim().to_string()))
+        }
+    }
+
+    /// Installs the APK file located at `apk_path` only if its version is newer than the one installed on the device.
+    ///
+    /// Returns `true` if the APK has been installed.
+    #[cfg(feature = "apk")]
+    pub fn install_if_newer<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        apk_path: P,
+    ) -> Result<bool> {
+        let device = device.into();
+        let manifest = crate::ApkManifest::from_path(apk_path.as_ref())?;
+
+        if let Some(installed) = self.package_version(&device, &manifest.package)? {
+            if installed.version_code >= manifest.version_code {
+                return Ok(false);
+            }
+        }
+
+        self.install_file(&device, apk_path)?;
+        Ok(true)
+    }
+}
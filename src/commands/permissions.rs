@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+/// Marker following the output of each `pm grant` command run by `grant_permissions`, before its exit status.
+const GRANT_MARKER: &str = "__GRANT:";
+
+impl AdbTcpConnexion {
+    /// Grants runtime `permissions` to `package` in a single command, for the user set with [AdbTcpConnexion::set_user] if any.
+    ///
+    /// Returns permissions which could not be granted (e.g. not requested by the package), with their error message.
+    pub fn grant_permissions(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        permissions: &[&str],
+    ) -> Result<BTreeMap<String, String>> {
+        let device = device.into();
+        if permissions.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        let user = self.user_option();
+        let package = shell_quote(package);
+        let command = permissions
+            .iter()
+            .map(|permission| {
+                format!(
+                    "pm grant {user}{package} {} 2>&1; printf '\\n{GRANT_MARKER}%d\\n' $?",
+                    shell_quote(permission)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let output = String::from_utf8(self.exec_command(&device, command)?)?;
+
+        let mut failed = BTreeMap::new();
+        let mut permissions = permissions.iter();
+        let mut message: Vec<&str> = Vec::new();
+        for line in output.lines() {
+            let Some(code) = line.strip_prefix(GRANT_MARKER) else {
+                message.push(line);
+                continue;
+            };
+            let permission = permissions
+                .next()
+                .ok_or(RustADBError::ConnectionClosedByPeer)?;
+            // Older versions print errors but still exit with status 0
+            if code != "0" || message.iter().any(|line| !line.trim().is_empty()) {
+                failed.insert(permission.to_string(), error_message(&message));
+            }
+            message.clear();
+        }
+        if permissions.next().is_some() {
+            return Err(RustADBError::ConnectionClosedByPeer);
+        }
+
+        Ok(failed)
+    }
+}
+
+/// Meaningful line of `pm` error output, skipping the header and stack trace of exceptions.
+fn error_message(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim_end())
+        .find(|line| {
+            !line.is_empty()
+                && !line.starts_with(char::is_whitespace)
+                && !line.starts_with("Exception occurred")
+        })
+        .unwrap_or_default()
+        .to_string()
+}
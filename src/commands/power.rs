@@ -0,0 +1,165 @@
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+impl AdbTcpConnexion {
+    /// Forces the device into deep Doze mode, until [AdbTcpConnexion::unforce_idle] is called.
+    ///
+    /// Doze must be enabled, and devices usually refuse it while charging: see [AdbTcpConnexion::unplug_battery].
+    pub fn force_idle(&mut self, device: impl Into<DeviceSelector>) -> Result<()> {
+        let device = device.into();
+        let output = self.run_dumpsys(&device, "deviceidle force-idle")?;
+        if !output.starts_with("Now forced in to deep idle mode") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Lets the device leave Doze mode forced by [AdbTcpConnexion::force_idle].
+    pub fn unforce_idle(&mut self, device: impl Into<DeviceSelector>) -> Result<()> {
+        let device = device.into();
+        // Command prints new Doze states on success
+        let output = self.run_dumpsys(&device, "deviceidle unforce")?;
+        if !output.contains("state:") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Lists packages exempted from Doze restrictions, by the system or by the user.
+    pub fn idle_whitelist(&mut self, device: impl Into<DeviceSelector>) -> Result<Vec<String>> {
+        let device = device.into();
+        let output = self.run_dumpsys(&device, "deviceidle whitelist")?;
+
+        // Lines are `<kind>,<package>,<uid>`, a package being listed once per kind
+        let mut packages: Vec<String> = Vec::new();
+        for line in output.lines() {
+            let mut fields = line.trim().split(',');
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(_), Some(package), Some(_)) => {
+                    if !packages.iter().any(|p| p == package) {
+                        packages.push(package.to_string());
+                    }
+                }
+                _ => {
+                    if !line.trim().is_empty() {
+                        return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok(packages)
+    }
+
+    /// Exempts `package` from Doze restrictions.
+    pub fn idle_whitelist_add(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+    ) -> Result<()> {
+        let device = device.into();
+        let output = self.run_dumpsys(
+            &device,
+            &format!("deviceidle whitelist +{}", shell_quote(package)),
+        )?;
+        if !output.starts_with("Added") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the exemption from Doze restrictions of `package`, returning whether it was exempted by the user.
+    pub fn idle_whitelist_remove(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+    ) -> Result<bool> {
+        let device = device.into();
+        // Command prints nothing if package was not exempted
+        let output = self.run_dumpsys(
+            &device,
+            &format!("deviceidle whitelist -{}", shell_quote(package)),
+        )?;
+        if !output.trim().is_empty() && !output.starts_with("Removed") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(!output.trim().is_empty())
+    }
+
+    /// Simulates the device running on battery, so that power management applies as when unplugged.
+    pub fn unplug_battery(&mut self, device: impl Into<DeviceSelector>) -> Result<()> {
+        let device = device.into();
+        self.run_dumpsys_setter(&device, "battery unplug")
+    }
+
+    /// Stops simulating battery states, reporting the actual ones again.
+    pub fn reset_battery(&mut self, device: impl Into<DeviceSelector>) -> Result<()> {
+        let device = device.into();
+        self.run_dumpsys_setter(&device, "battery reset")
+    }
+
+    /// Gets whether `package` is inactive, i.e. restricted by App Standby.
+    pub fn app_inactive(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+    ) -> Result<bool> {
+        let device = device.into();
+        let user = self.user_option();
+        let output = String::from_utf8(self.exec_command(
+            &device,
+            format!("am get-inactive {user}{} 2>&1", shell_quote(package)),
+        )?)?;
+
+        match output.trim() {
+            "Idle=true" => Ok(true),
+            "Idle=false" => Ok(false),
+            output => Err(RustADBError::ShellCommandFailed(output.to_string())),
+        }
+    }
+
+    /// Marks `package` as inactive, restricting it as App Standby does for unused apps, or as active again.
+    ///
+    /// Applies to the user set with [AdbTcpConnexion::set_user], if any.
+    pub fn set_app_inactive(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        inactive: bool,
+    ) -> Result<()> {
+        let device = device.into();
+        let user = self.user_option();
+        let output = self.exec_command(
+            &device,
+            format!(
+                "am set-inactive {user}{} {inactive} 2>&1",
+                shell_quote(package)
+            ),
+        )?;
+        if !output.trim_ascii().is_empty() {
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn run_dumpsys(&mut self, device: &DeviceSelector, args: &str) -> Result<String> {
+        let output = self.exec_command(device, format!("dumpsys {args} 2>&1"))?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    fn run_dumpsys_setter(&mut self, device: &DeviceSelector, args: &str) -> Result<()> {
+        // Command does not print anything on success
+        let output = self.run_dumpsys(device, args)?;
+        if !output.trim().is_empty() {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{utils::shell_quote, AdbTcpConnexion, ApiLevel, DeviceSelector, Result};
+
+impl AdbTcpConnexion {
+    /// Gets the value of system property `name`, empty if the property is not set.
+    pub(crate) fn get_prop(&mut self, device: &DeviceSelector, name: &str) -> Result<String> {
+        let output = self.exec_command(device, format!("getprop {}", shell_quote(name)))?;
+
+        Ok(String::from_utf8(output)?.trim().to_string())
+    }
+
+    /// Gets the API level of the device (e.g. 34 for Android 14).
+    ///
+    /// Helpers adapt their commands to it, it is read once per device for the lifetime of the connexion.
+    pub fn api_level(&mut self, device: impl Into<DeviceSelector>) -> Result<ApiLevel> {
+        let device = device.into();
+        if let Some(api_level) = self.api_levels.get(&device) {
+            return Ok(*api_level);
+        }
+
+        let api_level = self.get_prop(&device, "ro.build.version.sdk")?.parse()?;
+        self.api_levels.insert(device, api_level);
+
+        Ok(api_level)
+    }
+}
@@ -1,21 +1,17 @@
 use crate::{
     models::{AdbCommand, RebootType},
-    AdbTcpConnexion, Result,
+    AdbTcpConnexion, DeviceSelector, Result,
 };
 
 impl AdbTcpConnexion {
     /// Reboots the device
-    pub fn reboot<S: ToString>(
+    pub fn reboot(
         &mut self,
-        serial: &Option<S>,
+        device: impl Into<DeviceSelector>,
         reboot_type: RebootType,
     ) -> Result<()> {
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
-        }
+        let device = device.into();
+        self.connect_device(&device)?;
 
         self.proxy_connexion(AdbCommand::Reboot(reboot_type), false)
             .map(|_| ())
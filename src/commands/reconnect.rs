@@ -0,0 +1,41 @@
+use crate::{
+    models::{AdbCommand, ReconnectTarget},
+    AdbTcpConnexion, DeviceSelector, Result,
+};
+
+impl AdbTcpConnexion {
+    /// Resets the connection to the device from the given side, the usual remedy for devices stuck offline.
+    ///
+    /// Device shows up again in [AdbTcpConnexion::devices] once reconnected, which can take a few seconds.
+    pub fn reconnect(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        target: ReconnectTarget,
+    ) -> Result<()> {
+        let device = device.into();
+        match target {
+            ReconnectTarget::Host => {
+                let host_prefix = self.host_prefix(&device)?;
+                self.new_connection()?;
+                self.proxy_connexion(AdbCommand::Reconnect(host_prefix), true)?;
+            }
+            ReconnectTarget::Device => {
+                self.connect_device(&device)?;
+                self.proxy_connexion(AdbCommand::ReconnectDevice, false)?;
+            }
+            ReconnectTarget::Offline => {
+                self.new_connection()?;
+                self.proxy_connexion(AdbCommand::ReconnectOffline, true)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asks ADB server to disconnect from all devices connected over TCP/IP.
+    pub fn disconnect_all(&mut self) -> Result<()> {
+        self.new_connection()?;
+        self.proxy_connexion(AdbCommand::DisconnectAll, true)
+            .map(|_| ())
+    }
+}
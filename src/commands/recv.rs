@@ -1,41 +1,293 @@
 use crate::{
-    models::{AdbCommand, SyncCommand},
-    AdbTcpConnexion, Result, RustADBError,
+    adb_rate_limiter::RateLimiter,
+    models::{AdbCommand, HostFeatures, SyncCommand},
+    parser,
+    protocol::sync::MAX_PAYLOAD_SIZE,
+    utils::shell_quote,
+    AdbTcpConnexion, DeviceSelector, Result, RustADBError, TransferDirection, TransferReport,
 };
 use byteorder::{ByteOrder, LittleEndian};
-use std::io::{Read, Write};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+    thread,
+    time::Instant,
+};
+
+/// Size of blocks read by `dd` when resuming a pull, which can only skip whole blocks.
+const RESUME_BLOCK_SIZE: u64 = 64 * 1024;
 
 impl AdbTcpConnexion {
-    /// Receives [path] to [stream] from the device.
-    pub fn recv<S: ToString, A: AsRef<str>>(
+    /// Receives [path] to [stream] from the device, and returns statistics of the transfer.
+    ///
+    /// Throughput is limited by the transfer rate limit of the connexion, if any.
+    pub fn recv<A: AsRef<str>>(
         &mut self,
-        serial: Option<S>,
+        device: impl Into<DeviceSelector>,
         path: A,
         stream: &mut dyn Write,
-    ) -> Result<()> {
-        self.new_connection()?;
+    ) -> Result<TransferReport> {
+        let rate_limit = self.transfer_rate_limit;
+        self.recv_with_rate_limit(device, path, stream, rate_limit)
+    }
 
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
-        }
+    /// Receives [path] to [stream] from the device, at most at `bytes_per_second` (`None` for no limit) whatever the limit of the connexion.
+    ///
+    /// If enabled, falls back to `cat` when the sync service is denied access to [path], see [AdbTcpConnexionBuilder::shell_fallback](crate::AdbTcpConnexionBuilder::shell_fallback).
+    pub fn recv_with_rate_limit<A: AsRef<str>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: A,
+        stream: &mut dyn Write,
+        bytes_per_second: Option<u64>,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        self.connect_device(&device)?;
 
         // Set device in SYNC mode
         self.send_adb_request(AdbCommand::Sync)?;
 
         // Send a recv command
-        self.send_sync_request(SyncCommand::Recv(path.as_ref(), stream))?;
+        self.send_sync_request(SyncCommand::Recv)?;
+
+        let bytes =
+            match self.handle_recv_command(&path, stream, RateLimiter::new(bytes_per_second)) {
+                // Access is checked before sending any data, nothing has been written to stream yet
+                Err(e) if e.is_permission_denied() && self.can_shell_fallback(&device)? => self
+                    .run_shell_v2(
+                        &device,
+                        format!("cat {}", shell_quote(path.as_ref())),
+                        &mut io::empty(),
+                        stream,
+                        RateLimiter::new(bytes_per_second),
+                    )?,
+                result => result?,
+            };
+        Ok(self.transferred(
+            TransferDirection::Received,
+            TransferReport::file(bytes, start),
+        ))
+    }
+
+    /// Receives [path] from the device into the local file `local_path`, resuming a previous interrupted pull to the same file.
+    ///
+    /// Data already downloaded is kept, and the rest of the file is read with `dd` as sync protocol cannot start at an offset.
+    /// Returned statistics only account for data received by this call.
+    /// Fails with [RustADBError::ConnectionClosedByPeer] if the transfer is interrupted again, the call can then be retried.
+    pub fn recv_resumable<A: AsRef<str>, P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: A,
+        local_path: P,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        let path = path.as_ref();
+        let remote_size = self.remote_file_size(&device, path)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(local_path)?;
+        let local_size = file.metadata()?.len();
+        if local_size == remote_size {
+            return Ok(TransferReport {
+                duration: start.elapsed(),
+                files_skipped: 1,
+                ..Default::default()
+            });
+        }
+
+        // A local file larger than the remote one is not a partial download of it, downloads it again
+        let offset = match local_size > remote_size {
+            true => 0,
+            false => local_size - local_size % RESUME_BLOCK_SIZE,
+        };
+        file.set_len(offset)?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        if offset == 0 {
+            let report = self.recv(&device, path, &mut file)?;
+            return Ok(TransferReport {
+                duration: start.elapsed(),
+                ..report
+            });
+        }
+
+        self.open_exec(
+            &device,
+            format!(
+                "dd if={} bs={RESUME_BLOCK_SIZE} skip={} 2>/dev/null",
+                shell_quote(path),
+                offset / RESUME_BLOCK_SIZE
+            ),
+        )?;
+
+        let bytes = copy_limited(
+            &mut self.tcp_stream,
+            &mut file,
+            &mut RateLimiter::new(self.transfer_rate_limit),
+        )?;
+
+        match file.metadata()?.len() == remote_size {
+            true => Ok(self.transferred(
+                TransferDirection::Received,
+                TransferReport {
+                    retries: 1,
+                    ..TransferReport::file(bytes, start)
+                },
+            )),
+            false => Err(RustADBError::ConnectionClosedByPeer),
+        }
+    }
+
+    /// Receives [path] from the device into the local file `local_path`, reading distinct ranges of it over up to `streams` concurrent connections.
+    ///
+    /// Ranges are read with `dd`, which can speed up pulls of large files on high-latency links.
+    /// Files smaller than two ranges are received as with [AdbTcpConnexion::recv].
+    /// The transfer rate limit of the connexion, if any, is shared between connections.
+    /// Ranges are written to a temporary file next to `local_path`, which only replaces it once all of them are received.
+    pub fn recv_parallel<A: AsRef<str>, P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: A,
+        local_path: P,
+        streams: usize,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        let path = path.as_ref();
+        let local_path = local_path.as_ref();
+        let remote_size = self.remote_file_size(&device, path)?;
+
+        let blocks = remote_size.div_ceil(RESUME_BLOCK_SIZE);
+        let blocks_per_range = blocks.div_ceil(streams.max(1) as u64).max(1);
+        if blocks_per_range >= blocks {
+            let mut file = File::create(local_path)?;
+            let report = self.recv(&device, path, &mut file)?;
+            return Ok(TransferReport {
+                duration: start.elapsed(),
+                ..report
+            });
+        }
+
+        // A partially written file would look complete to `recv_resumable`, as it is sized upfront
+        let mut part_file_name = local_path.file_name().unwrap_or_default().to_os_string();
+        part_file_name.push(".part");
+        let part_path = local_path.with_file_name(part_file_name);
+        let bytes = match self.recv_ranges(&device, path, &part_path, remote_size, blocks_per_range)
+        {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let _ = fs::remove_file(&part_path);
+                return Err(e);
+            }
+        };
+        fs::rename(&part_path, local_path)?;
 
-        self.handle_recv_command(path, stream)
+        Ok(self.transferred(
+            TransferDirection::Received,
+            TransferReport::file(bytes, start),
+        ))
     }
 
-    fn handle_recv_command<S: AsRef<str>>(
+    /// Receives [path] of `remote_size` bytes into the new local file `local_path`, in ranges of `blocks_per_range` blocks read concurrently.
+    fn recv_ranges(
+        &mut self,
+        device: &DeviceSelector,
+        path: &str,
+        local_path: &Path,
+        remote_size: u64,
+        blocks_per_range: u64,
+    ) -> Result<u64> {
+        File::create(local_path)?.set_len(remote_size)?;
+
+        // Connections are opened first, so that failures to open them are reported before any data is read
+        let ranges = remote_size
+            .div_ceil(RESUME_BLOCK_SIZE)
+            .div_ceil(blocks_per_range);
+        let mut connections = Vec::new();
+        for range in 0..ranges {
+            self.open_exec(
+                device,
+                format!(
+                    "dd if={} bs={RESUME_BLOCK_SIZE} skip={} count={blocks_per_range} 2>/dev/null",
+                    shell_quote(path),
+                    range * blocks_per_range
+                ),
+            )?;
+            let offset = range * blocks_per_range * RESUME_BLOCK_SIZE;
+            let length = (blocks_per_range * RESUME_BLOCK_SIZE).min(remote_size - offset);
+            connections.push((self.tcp_stream.try_clone()?, offset, length));
+        }
+
+        let rate_limit = self
+            .transfer_rate_limit
+            .map(|bytes_per_second| (bytes_per_second / ranges).max(1));
+        let results = thread::scope(|scope| {
+            let handles = connections
+                .into_iter()
+                .map(|(mut stream, offset, length)| {
+                    scope.spawn(move || -> Result<u64> {
+                        let mut file = OpenOptions::new().write(true).open(local_path)?;
+                        file.seek(SeekFrom::Start(offset))?;
+                        let bytes = copy_limited(
+                            &mut stream,
+                            &mut file,
+                            &mut RateLimiter::new(rate_limit),
+                        )?;
+                        match bytes == length {
+                            true => Ok(bytes),
+                            false => Err(RustADBError::ConnectionClosedByPeer),
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut bytes = 0;
+        for result in results {
+            bytes += result?;
+        }
+        Ok(bytes)
+    }
+
+    /// Size of the file located at `path` on the device.
+    ///
+    /// Unlike `stat`, sizes above 4GiB are supported.
+    fn remote_file_size(&mut self, device: &DeviceSelector, path: &str) -> Result<u64> {
+        let output = self.exec_command(device, format!("wc -c < {}", shell_quote(path)))?;
+        let output = String::from_utf8_lossy(&output);
+
+        output
+            .trim()
+            .parse()
+            .map_err(|_| RustADBError::ADBRequestFailed(output.trim().to_string()))
+    }
+
+    /// Whether transfers denied by the sync service can be retried with shell v2 protocol.
+    pub(crate) fn can_shell_fallback(&mut self, device: &DeviceSelector) -> Result<bool> {
+        Ok(self.shell_fallback && self.supports(device, HostFeatures::ShellV2)?)
+    }
+
+    pub(crate) fn handle_recv_command<S: AsRef<str>>(
         &mut self,
         from: S,
         output: &mut dyn Write,
-    ) -> Result<()> {
+        mut rate_limiter: Option<RateLimiter>,
+    ) -> Result<u64> {
         // First send 8 byte common header
         let mut len_buf = [0_u8; 4];
         LittleEndian::write_u32(&mut len_buf, from.as_ref().len() as u32);
@@ -44,34 +296,75 @@ impl AdbTcpConnexion {
 
         // Then we receive the byte data in chunks of up to 64k
         // Chunk looks like 'DATA' <length> <data>
-        let mut buffer = [0_u8; 64 * 1024]; // Should this be Boxed?
-        let mut data_header = [0_u8; 4]; // DATA
-        let mut len_header = [0_u8; 4]; // <len>
+        let mut buffer = [0_u8; MAX_PAYLOAD_SIZE]; // Should this be Boxed?
+        let mut header = [0_u8; 8]; // 'DATA' <len>
+        let mut bytes_received = 0;
         loop {
-            self.tcp_stream.read_exact(&mut data_header)?;
-            // Check if data_header is DATA or DONE
-            if data_header.eq(b"DATA") {
-                self.tcp_stream.read_exact(&mut len_header)?;
-                let length: usize = LittleEndian::read_u32(&len_header).try_into().unwrap();
+            self.tcp_stream.read_exact(&mut header)?;
+            let (id, length) = parser::sync_header(&header)?;
+            let length = length as usize;
+            if length > MAX_PAYLOAD_SIZE && !id.eq(b"DONE") {
+                return Err(RustADBError::UnknownResponseType(format!(
+                    "sync packet {} of {length} bytes",
+                    id.escape_ascii()
+                )));
+            }
+
+            // Check if header is DATA or DONE
+            if id.eq(b"DATA") {
                 self.tcp_stream.read_exact(&mut buffer[..length])?;
-                output.write_all(&buffer)?;
-            } else if data_header.eq(b"DONE") {
+                output.write_all(&buffer[..length])?;
+                bytes_received += length as u64;
+
+                // Device sends chunks as fast as the connection allows, slowing down reads slows it down
+                if let Some(rate_limiter) = rate_limiter.as_mut() {
+                    rate_limiter.throttle(length);
+                }
+            } else if id.eq(b"DONE") {
                 // We're done here
                 break;
-            } else if data_header.eq(b"FAIL") {
+            } else if id.eq(b"FAIL") {
                 // Handle fail
-                self.tcp_stream.read_exact(&mut len_header)?;
-                let length: usize = LittleEndian::read_u32(&len_header).try_into().unwrap();
                 self.tcp_stream.read_exact(&mut buffer[..length])?;
-                Err(RustADBError::ADBRequestFailed(String::from_utf8(
+                return Err(RustADBError::ADBRequestFailed(String::from_utf8(
                     buffer[..length].to_vec(),
-                )?))?;
+                )?));
             } else {
-                panic!("Unknown response from device {:#?}", data_header);
+                return Err(RustADBError::UnknownResponseType(format!(
+                    "Unknown response from device {}",
+                    id.escape_ascii()
+                )));
             }
         }
 
         // Connection should've left SYNC by now
-        Ok(())
+        Ok(bytes_received)
     }
 }
+
+/// Copies `reader` into `writer` until its end, throttled by `rate_limiter`, and returns the number of bytes copied.
+pub(crate) fn copy_limited(
+    reader: &mut (impl Read + ?Sized),
+    writer: &mut impl Write,
+    rate_limiter: &mut Option<RateLimiter>,
+) -> Result<u64> {
+    let mut buffer = [0_u8; 64 * 1024];
+    let mut bytes = 0;
+    let chunk_size = rate_limiter
+        .as_ref()
+        .map_or(buffer.len(), |limiter| limiter.chunk_size(buffer.len()));
+    loop {
+        let size = reader.read(&mut buffer[..chunk_size])?;
+        if size == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..size])?;
+        bytes += size as u64;
+
+        if let Some(rate_limiter) = rate_limiter.as_mut() {
+            rate_limiter.throttle(size);
+        }
+    }
+
+    Ok(bytes)
+}
@@ -0,0 +1,90 @@
+use crate::{
+    adb_rate_limiter::RateLimiter,
+    models::{AdbCommand, SyncCommand},
+    utils::shell_quote,
+    AdbTcpConnexion, DeviceSelector, Result, RustADBError,
+};
+
+impl AdbTcpConnexion {
+    /// Reads the whole content of the file located at `path` on the device, e.g. a small configuration file.
+    ///
+    /// Content is received with the sync protocol, which does not start any process on the device.
+    /// When the device refuses sync service (e.g. minimal `adbd` of some recoveries), `cat` is run instead.
+    pub fn read_remote_file(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: &str,
+    ) -> Result<Vec<u8>> {
+        let device = device.into();
+        let mut content = Vec::new();
+
+        if self.open_sync_if_supported(&device)? {
+            self.send_sync_request(SyncCommand::Recv)?;
+            let rate_limiter = RateLimiter::new(self.transfer_rate_limit);
+            self.handle_recv_command(path, &mut content, rate_limiter)?;
+            return Ok(content);
+        }
+
+        // First byte tells whether the file could be read, as errors cannot be told apart from content
+        let quoted_path = shell_quote(path);
+        let output = self.exec_command(
+            &device,
+            format!(
+                "if [ -r {quoted_path} ]; then echo -n O; cat {quoted_path}; else echo -n F; fi"
+            ),
+        )?;
+        match output.split_first() {
+            Some((b'O', content)) => Ok(content.to_vec()),
+            _ => Err(RustADBError::ADBRequestFailed(format!(
+                "{path}: cannot be read"
+            ))),
+        }
+    }
+
+    /// Writes `content` to the file located at `path` on the device, replacing it if it exists.
+    ///
+    /// As with [AdbTcpConnexion::read_remote_file], sync protocol is used unless the device refuses it, `head` is run then.
+    pub fn write_remote_file(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: &str,
+        content: &[u8],
+    ) -> Result<()> {
+        let device = device.into();
+
+        if self.open_sync_if_supported(&device)? {
+            self.send_sync_request(SyncCommand::Send)?;
+            let rate_limiter = RateLimiter::new(self.transfer_rate_limit);
+            self.handle_send_command(&mut &content[..], path, rate_limiter)?;
+            return Ok(());
+        }
+
+        // Standard input cannot be closed with `exec:` service, `head` stops on its own after the content.
+        // Its errors are redirected to the output before the content is redirected to the file.
+        let output = self.exec_command_with_input(
+            &device,
+            format!(
+                "head -c {} 2>&1 >{} && echo -n O",
+                content.len(),
+                shell_quote(path)
+            ),
+            &mut &content[..],
+        )?;
+        match output.as_slice() {
+            b"O" => Ok(()),
+            _ => Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            )),
+        }
+    }
+
+    /// Opens a new connection to the device switched to sync mode, returns `false` if the device refuses sync service.
+    fn open_sync_if_supported(&mut self, device: &DeviceSelector) -> Result<bool> {
+        self.connect_device(device)?;
+        match self.send_adb_request(AdbCommand::Sync) {
+            Ok(()) => Ok(true),
+            Err(RustADBError::ADBRequestFailed(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::net::{Ipv4Addr, SocketAddrV4, TcpListener, TcpStream};
+
+use crate::{
+    models::AdbCommand, AdbReverse, AdbServerHandle, AdbTcpConnexion, DeviceSelector, Endpoint,
+    Result,
+};
+
+impl AdbTcpConnexion {
+    /// Forwards connections made by the device to `remote` on it, to `local` on the host ADB server runs on, and returns the remote endpoint.
+    ///
+    /// For [Endpoint::Tcp] port `0`, the device chooses a free port, which is set in the returned endpoint.
+    /// An existing reverse forwarding of `remote` is replaced, unless `norebind` is set in which case the request fails.
+    pub fn reverse(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        remote: Endpoint,
+        local: Endpoint,
+        norebind: bool,
+    ) -> Result<Endpoint> {
+        let device = device.into();
+        self.connect_device(&device)?;
+        self.send_adb_request(AdbCommand::Reverse {
+            remote: remote.clone(),
+            local,
+            norebind,
+        })?;
+
+        // As for forwards, a second status tells whether forwarding is set up
+        self.read_request_status()?;
+        if remote != Endpoint::Tcp(0) {
+            return Ok(remote);
+        }
+
+        Ok(Endpoint::Tcp(self.read_forwarded_port()?))
+    }
+
+    /// Removes the reverse forwarding of `remote` on the device.
+    pub fn kill_reverse(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        remote: Endpoint,
+    ) -> Result<()> {
+        let device = device.into();
+        self.connect_device(&device)?;
+        self.send_adb_request(AdbCommand::KillReverse(remote))?;
+
+        self.read_request_status()
+    }
+
+    /// Delivers each connection made by the device to `remote` on it to `handler`, run on its own thread in this process.
+    ///
+    /// Connections reach the process through a loopback port only known to ADB server, which must run on this host.
+    /// Reverse forwarding is removed when the returned [AdbReverse] is dropped.
+    pub fn reverse_to_handler(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        remote: Endpoint,
+        handler: impl Fn(TcpStream) + Send + Sync + 'static,
+    ) -> Result<AdbReverse> {
+        let device = device.into();
+        // Connexion removing the forwarding does not know the default device
+        let device = self.resolve_selector(&device)?;
+
+        let listener = TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))?;
+        let local = Endpoint::Tcp(listener.local_addr()?.port());
+        let remote = self.reverse(&device, remote, local, false)?;
+
+        AdbReverse::new(
+            AdbServerHandle::from(&*self),
+            device,
+            remote,
+            listener,
+            handler,
+        )
+    }
+}
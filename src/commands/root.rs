@@ -0,0 +1,10 @@
+use crate::{AdbTcpConnexion, DeviceSelector, Result};
+
+impl AdbTcpConnexion {
+    /// Checks whether commands run on the device have root privileges (e.g. after `adb root`).
+    pub(crate) fn is_root(&mut self, device: &DeviceSelector) -> Result<bool> {
+        let uid = self.exec_command(device, "id -u")?;
+
+        Ok(uid.trim_ascii() == b"0")
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    io::{self, Read, Write},
+    net::Shutdown,
+};
+
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+impl AdbTcpConnexion {
+    /// Runs `command` as the user of the debuggable application `package`, inside its data directory.
+    ///
+    /// Data directory is the one of the user set with [AdbTcpConnexion::set_user], if any. Returns the output of the command.
+    pub fn run_as(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        command: &str,
+    ) -> Result<Vec<u8>> {
+        let device = device.into();
+        let user = self.user_option();
+        let output = self.exec_command(
+            &device,
+            format!("run-as {user}{} {command}", shell_quote(package)),
+        )?;
+        check_run_as_output(&output)?;
+
+        Ok(output)
+    }
+
+    /// Receives `path`, relative to the data directory of the debuggable application `package`, to `output`.
+    pub fn pull_app_file(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        path: &str,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let device = device.into();
+        // Errors are printed on the same stream as file content, file size tells them apart
+        let size = self.app_file_size(&device, package, path)?;
+        let content = self.run_as(&device, package, &format!("cat {}", shell_quote(path)))?;
+        if Some(content.len() as u64) != size {
+            return Err(RustADBError::RunAsFailed(format!(
+                "could not read {path} in {package} data directory"
+            )));
+        }
+        output.write_all(&content)?;
+
+        Ok(())
+    }
+
+    /// Sends `input` to `path`, relative to the data directory of the debuggable application `package`.
+    pub fn push_app_file(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        package: &str,
+        input: &mut dyn Read,
+        path: &str,
+    ) -> Result<()> {
+        let device = device.into();
+        let command = format!("cat > {}", shell_quote(path));
+        let user = self.user_option();
+        self.open_exec(
+            &device,
+            format!(
+                "run-as {user}{} sh -c {}",
+                shell_quote(package),
+                shell_quote(&command)
+            ),
+        )?;
+
+        let written = io::copy(input, &mut self.tcp_stream)?;
+
+        // Closing our side lets `cat` reach the end of its input
+        self.tcp_stream.shutdown(Shutdown::Write)?;
+        let mut output = Vec::new();
+        self.tcp_stream.read_to_end(&mut output)?;
+        check_run_as_output(&output)?;
+
+        // Output is lost once the connection is closed, file size tells if everything went fine
+        if self.app_file_size(&device, package, path)? != Some(written) {
+            return Err(RustADBError::RunAsFailed(format!(
+                "could not write {path} in {package} data directory"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the size of `path` in the data directory of `package`, if it exists.
+    pub(crate) fn app_file_size(
+        &mut self,
+        device: &DeviceSelector,
+        package: &str,
+        path: &str,
+    ) -> Result<Option<u64>> {
+        let size = self.run_as(
+            device,
+            package,
+            &format!("stat -c %s {}", shell_quote(path)),
+        )?;
+
+        Ok(String::from_utf8(size)?.trim().parse::<u64>().ok())
+    }
+}
+
+fn check_run_as_output(output: &[u8]) -> Result<()> {
+    // run-as reports errors (unknown or non-debuggable package...) with a "run-as:" prefix
+    if output.starts_with(b"run-as:") {
+        return Err(RustADBError::RunAsFailed(
+            String::from_utf8_lossy(output).trim().to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use chrono::NaiveDate;
+
+use crate::{AdbTcpConnexion, DeviceSelector, Result, SecurityState};
+
+impl AdbTcpConnexion {
+    /// Gets the verified boot state, bootloader lock, SELinux mode, security patch level and encryption state of the device.
+    pub fn security_state(&mut self, device: impl Into<DeviceSelector>) -> Result<SecurityState> {
+        let device = device.into();
+        let non_empty = |value: String| (!value.is_empty()).then_some(value);
+
+        let bootloader_locked = match self.get_prop(&device, "ro.boot.flash.locked")?.as_str() {
+            "1" => Some(true),
+            "0" => Some(false),
+            // Devices with AVB report the state of vbmeta instead
+            _ => match self
+                .get_prop(&device, "ro.boot.vbmeta.device_state")?
+                .as_str()
+            {
+                "locked" => Some(true),
+                "unlocked" => Some(false),
+                _ => None,
+            },
+        };
+        let selinux_mode = String::from_utf8(self.exec_command(&device, "getenforce")?)?
+            .parse()
+            .ok();
+
+        Ok(SecurityState {
+            verified_boot_state: non_empty(self.get_prop(&device, "ro.boot.verifiedbootstate")?),
+            bootloader_locked,
+            selinux_mode,
+            security_patch: NaiveDate::parse_from_str(
+                &self.get_prop(&device, "ro.build.version.security_patch")?,
+                "%Y-%m-%d",
+            )
+            .ok(),
+            encrypted: self.get_prop(&device, "ro.crypto.state")? == "encrypted",
+            encryption_type: non_empty(self.get_prop(&device, "ro.crypto.type")?),
+        })
+    }
+}
@@ -1,42 +1,171 @@
 use crate::{
-    models::{AdbCommand, AdbRequestStatus, SyncCommand},
-    AdbTcpConnexion, Result, RustADBError,
+    adb_rate_limiter::RateLimiter,
+    models::{AdbCommand, HostFeatures, SyncCommand},
+    parser,
+    protocol::sync::MAX_PAYLOAD_SIZE,
+    utils::{open_local_file, shell_quote, sync_timestamp},
+    AdbTcpConnexion, DeviceSelector, Result, RustADBError, TransferDirection, TransferReport,
 };
 use byteorder::{ByteOrder, LittleEndian};
 use std::{
-    convert::TryInto,
-    io::{Read, Write},
-    str::{self, FromStr},
-    time::SystemTime,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+    time::{Instant, SystemTime},
 };
 
 impl AdbTcpConnexion {
-    /// Sends [stream] to [path] on the device.
-    pub fn send<S: ToString, A: AsRef<str>>(
+    /// Sends [stream] to [path] on the device, and returns statistics of the transfer.
+    ///
+    /// Throughput is limited by the transfer rate limit of the connexion, if any.
+    pub fn send<A: AsRef<str>>(
         &mut self,
-        serial: Option<S>,
+        device: impl Into<DeviceSelector>,
         stream: &mut dyn Read,
         path: A,
-    ) -> Result<()> {
-        self.new_connection()?;
+    ) -> Result<TransferReport> {
+        let rate_limit = self.transfer_rate_limit;
+        self.send_with_rate_limit(device, stream, path, rate_limit)
+    }
 
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
-        }
+    /// Sends [stream] to [path] on the device, at most at `bytes_per_second` (`None` for no limit) whatever the limit of the connexion.
+    pub fn send_with_rate_limit<A: AsRef<str>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        stream: &mut dyn Read,
+        path: A,
+        bytes_per_second: Option<u64>,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        self.connect_device(&device)?;
 
         // Set device in SYNC mode
         self.send_adb_request(AdbCommand::Sync)?;
 
         // Send a send command
-        self.send_sync_request(SyncCommand::Send(stream, path.as_ref()))?;
+        self.send_sync_request(SyncCommand::Send)?;
 
-        self.handle_send_command(stream, path)
+        let bytes = self.handle_send_command(stream, path, RateLimiter::new(bytes_per_second))?;
+        Ok(self.transferred(TransferDirection::Sent, TransferReport::file(bytes, start)))
     }
 
-    fn handle_send_command<S: AsRef<str>>(&mut self, input: &mut dyn Read, to: S) -> Result<()> {
+    /// Pushes the local file located at `local_path` to `remote_path` on the device.
+    ///
+    /// Fails with [RustADBError::InsufficientSpace] before sending anything if the destination filesystem is too small,
+    /// and with [RustADBError::FileTooLarge] if the file is larger than 4GiB and the device only supports 32-bit sync sizes.
+    /// If enabled, falls back to `cat` when the sync service is denied access to `remote_path`, see [AdbTcpConnexionBuilder::shell_fallback](crate::AdbTcpConnexionBuilder::shell_fallback).
+    pub fn push_file<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        local_path: P,
+        remote_path: &str,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        let mut input = open_local_file(local_path)?;
+        let size = input.metadata()?.len();
+        self.check_sync_size(&device, size)?;
+        self.check_free_space(&device, remote_path, size)?;
+
+        match self.send(&device, &mut input, remote_path) {
+            // Device reports the failure once all data has been sent, the file is read again
+            Err(e) if e.is_permission_denied() && self.can_shell_fallback(&device)? => {
+                input.seek(SeekFrom::Start(0))?;
+                let limit = self.transfer_rate_limit;
+                self.run_shell_v2(
+                    &device,
+                    format!("cat > {}", shell_quote(remote_path)),
+                    &mut input,
+                    &mut io::sink(),
+                    RateLimiter::new(limit),
+                )?;
+                Ok(self.transferred(TransferDirection::Sent, TransferReport::file(size, start)))
+            }
+            result => result,
+        }
+    }
+
+    /// Sends [stream] to [path] on the device as [AdbTcpConnexion::send], but into a temporary file of the same directory
+    /// which is then renamed to [path].
+    ///
+    /// Processes reading [path] on the device never see a partially written file, e.g. configuration files read by running apps.
+    /// Temporary file is removed if the transfer fails.
+    pub fn send_atomic<A: AsRef<str>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        stream: &mut dyn Read,
+        path: A,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let start = Instant::now();
+        let path = path.as_ref();
+        let temp_path = atomic_temp_path(path);
+
+        let renamed = self.send(&device, stream, &temp_path).and_then(|report| {
+            let output = self.exec_command(
+                &device,
+                format!(
+                    "mv -f {} {} 2>&1",
+                    shell_quote(&temp_path),
+                    shell_quote(path)
+                ),
+            )?;
+            match output.trim_ascii().is_empty() {
+                true => Ok(report),
+                false => Err(RustADBError::ShellCommandFailed(
+                    String::from_utf8_lossy(&output).trim().to_string(),
+                )),
+            }
+        });
+
+        match renamed {
+            Ok(report) => Ok(TransferReport {
+                duration: start.elapsed(),
+                ..report
+            }),
+            Err(e) => {
+                // Best effort, the original error is more relevant than a failure to clean up
+                let _ = self.exec_command(&device, format!("rm -f {}", shell_quote(&temp_path)));
+                Err(e)
+            }
+        }
+    }
+
+    /// Pushes the local file located at `local_path` to `remote_path` on the device as [AdbTcpConnexion::push_file],
+    /// replacing `remote_path` only once the whole file has been sent (see [AdbTcpConnexion::send_atomic]).
+    pub fn push_file_atomic<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        local_path: P,
+        remote_path: &str,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        let mut input = open_local_file(local_path)?;
+        let size = input.metadata()?.len();
+        self.check_sync_size(&device, size)?;
+        self.check_free_space(&device, remote_path, size)?;
+
+        self.send_atomic(&device, &mut input, remote_path)
+    }
+
+    /// Fails with [RustADBError::FileTooLarge] if `size` overflows the 32-bit sizes of devices without `stat_v2`,
+    /// whose transfers of such files cannot be checked.
+    pub(crate) fn check_sync_size(&mut self, device: &DeviceSelector, size: u64) -> Result<()> {
+        if size <= u32::MAX.into() || self.supports(device, HostFeatures::StatV2)? {
+            return Ok(());
+        }
+
+        Err(RustADBError::FileTooLarge(format!(
+            "{size} bytes overflow the 32-bit sizes of the sync protocol of the device"
+        )))
+    }
+
+    pub(crate) fn handle_send_command<S: AsRef<str>>(
+        &mut self,
+        input: &mut dyn Read,
+        to: S,
+        mut rate_limiter: Option<RateLimiter>,
+    ) -> Result<u64> {
         // Append the permission flags to the filename
         let to = to.as_ref().to_string() + ",0777";
 
@@ -51,8 +180,12 @@ impl AdbTcpConnexion {
         // Then we send the byte data in chunks of up to 64k
         // Chunk looks like 'DATA' <length> <data>
         let mut buffer = [0_u8; 64 * 1024];
+        let mut bytes_sent = 0;
+        let chunk_size = rate_limiter
+            .as_ref()
+            .map_or(buffer.len(), |limiter| limiter.chunk_size(buffer.len()));
         loop {
-            let bytes_read = input.read(&mut buffer)?;
+            let bytes_read = input.read(&mut buffer[..chunk_size])?;
             if bytes_read == 0 {
                 break;
             }
@@ -61,40 +194,43 @@ impl AdbTcpConnexion {
             self.tcp_stream.write_all(b"DATA")?;
             self.tcp_stream.write_all(&chunk_len_buf)?;
             self.tcp_stream.write_all(&buffer[..bytes_read])?;
+            bytes_sent += bytes_read as u64;
+
+            if let Some(rate_limiter) = rate_limiter.as_mut() {
+                rate_limiter.throttle(bytes_read);
+            }
         }
 
         // When we are done sending, we send 'DONE' <last modified time>
         // Re-use len_buf to send the last modified time
-        let last_modified = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => n,
-            Err(_) => panic!("SystemTime before UNIX EPOCH!"),
-        };
-        LittleEndian::write_u32(&mut len_buf, last_modified.as_secs() as u32);
+        LittleEndian::write_u32(&mut len_buf, sync_timestamp(SystemTime::now())?);
         self.tcp_stream.write_all(b"DONE")?;
         self.tcp_stream.write_all(&len_buf)?;
 
-        // We expect 'OKAY' response from this
-        let mut request_status = [0; 4];
-        self.tcp_stream.read_exact(&mut request_status)?;
-
-        match AdbRequestStatus::from_str(str::from_utf8(request_status.as_ref())?)? {
-            AdbRequestStatus::Fail => {
-                // We can keep reading to get further details
-                let length = self.get_body_length()?;
-
-                let mut body = vec![
-                    0;
-                    length
-                        .try_into()
-                        .map_err(|_| RustADBError::ConvertionError)?
-                ];
-                if length > 0 {
-                    self.tcp_stream.read_exact(&mut body)?;
-                }
+        // We expect 'OKAY' response from this, or 'FAIL' followed by the little-endian length of the reason
+        let mut header = [0; 8];
+        self.tcp_stream.read_exact(&mut header)?;
+
+        match parser::sync_header(&header)? {
+            (id, _) if id.eq(b"OKAY") => Ok(bytes_sent),
+            (id, length) if id.eq(b"FAIL") => {
+                let mut body = vec![0; length.min(MAX_PAYLOAD_SIZE as u32) as usize];
+                self.tcp_stream.read_exact(&mut body)?;
 
                 Err(RustADBError::ADBRequestFailed(String::from_utf8(body)?))
             }
-            AdbRequestStatus::Okay => Ok(()),
+            (id, _) => Err(RustADBError::UnknownResponseType(format!(
+                "Unknown response {}",
+                id.escape_ascii()
+            ))),
         }
     }
 }
+
+/// Temporary file pushed before being renamed to `path`, in the same directory so that renaming it is atomic.
+fn atomic_temp_path(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((directory, name)) => format!("{directory}/.{name}.adb_client.tmp"),
+        None => format!(".{path}.adb_client.tmp"),
+    }
+}
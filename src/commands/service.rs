@@ -0,0 +1,52 @@
+use crate::{
+    models::AdbCommand, protocol::service::ServiceStream, AdbCommandExt, AdbTcpConnexion,
+    DeviceSelector, Result,
+};
+
+impl AdbTcpConnexion {
+    /// Opens a new connection to the device and opens `service` on it (e.g. `shell,raw:<command>` or `tcp:<port>`).
+    ///
+    /// Meant for services not provided by this crate. Connection is left when the stream is dropped.
+    pub fn open_service(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        service: &str,
+    ) -> Result<ServiceStream<'_>> {
+        let device = device.into();
+        self.connect_device(&device)?;
+        self.send_adb_request(AdbCommand::Service(service.to_string()))?;
+
+        Ok(ServiceStream::new(&mut self.tcp_stream))
+    }
+
+    /// Opens a new connection to ADB server and opens host `service` for the device (e.g. `get-state`, sent as `host-serial:<serial>:get-state`).
+    ///
+    /// Meant for services not provided by this crate. Connection is left when the stream is dropped.
+    pub fn open_host_service(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        service: &str,
+    ) -> Result<ServiceStream<'_>> {
+        let device = device.into();
+        let host_prefix = self.host_prefix(&device)?;
+        self.new_connection()?;
+        self.send_adb_request(AdbCommand::HostService(format!("{host_prefix}:{service}")))?;
+
+        Ok(ServiceStream::new(&mut self.tcp_stream))
+    }
+
+    /// Runs `command`, a command not provided by this crate, on the device.
+    pub fn run_command<C: AdbCommandExt>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: &C,
+    ) -> Result<C::Output> {
+        let device = device.into();
+        let mut stream = match command.is_host_service() {
+            true => self.open_host_service(&device, &command.service())?,
+            false => self.open_service(&device, &command.service())?,
+        };
+
+        command.read_response(&mut stream)
+    }
+}
@@ -0,0 +1,131 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, ApiLevel, ContentUri, ContentValue, DeviceSelector,
+    Result, RustADBError, SettingsNamespace, SettingsStrategy,
+};
+
+impl AdbTcpConnexion {
+    /// Gets the value of setting `key` in `namespace`, parsed as `T`.
+    ///
+    /// Returns `None` if the setting is not defined.
+    pub fn settings_get<T: FromStr>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        namespace: SettingsNamespace,
+        key: &str,
+    ) -> Result<Option<T>> {
+        let device = device.into();
+        let value = match self.settings_strategy(&device)? {
+            SettingsStrategy::SettingsCommand => {
+                let output = self.exec_command(
+                    &device,
+                    format!("settings get {namespace} {}", shell_quote(key)),
+                )?;
+                match String::from_utf8(output)?.trim() {
+                    "null" => None,
+                    value => Some(value.to_string()),
+                }
+            }
+            SettingsStrategy::ContentProvider => {
+                let rows = self.content_query(
+                    &device,
+                    &settings_uri(namespace),
+                    &["value"],
+                    Some(&format!("name='{}'", key.replace('\'', "''"))),
+                    None,
+                )?;
+                rows.into_iter()
+                    .next()
+                    .and_then(|mut row| row.values.remove("value").flatten())
+            }
+        };
+
+        value
+            .map(|value| value.parse().map_err(|_| RustADBError::ConvertionError))
+            .transpose()
+    }
+
+    /// Sets setting `key` in `namespace` to `value`.
+    pub fn settings_put<V: Display>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        namespace: SettingsNamespace,
+        key: &str,
+        value: V,
+    ) -> Result<()> {
+        let device = device.into();
+        if self.settings_strategy(&device)? == SettingsStrategy::ContentProvider {
+            return self.content_insert(
+                &device,
+                &settings_uri(namespace),
+                &[
+                    ("name", ContentValue::from(key)),
+                    ("value", ContentValue::String(value.to_string())),
+                ],
+            );
+        }
+
+        let output = self.exec_command(
+            &device,
+            format!(
+                "settings put {namespace} {} {}",
+                shell_quote(key),
+                shell_quote(&value.to_string())
+            ),
+        )?;
+
+        // Command does not print anything on success
+        if !output.trim_ascii().is_empty() {
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Removes setting `key` from `namespace`, which then takes its default value.
+    pub fn settings_delete(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        namespace: SettingsNamespace,
+        key: &str,
+    ) -> Result<()> {
+        let device = device.into();
+        if self.settings_strategy(&device)? == SettingsStrategy::ContentProvider {
+            return self.content_delete(
+                &device,
+                &settings_uri(namespace),
+                Some(&format!("name='{}'", key.replace('\'', "''"))),
+            );
+        }
+
+        // Command prints the number of deleted rows
+        let output = self.exec_command(
+            &device,
+            format!("settings delete {namespace} {} 2>&1", shell_quote(key)),
+        )?;
+        let output = String::from_utf8_lossy(&output);
+        match output.trim() {
+            "" | "Deleted 0 rows" | "Deleted 1 rows" => Ok(()),
+            message => Err(RustADBError::ShellCommandFailed(message.to_string())),
+        }
+    }
+
+    /// Way settings of the device are read and written, chosen from its API level.
+    pub fn settings_strategy(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+    ) -> Result<SettingsStrategy> {
+        match self.api_level(device)? >= ApiLevel::JELLY_BEAN_MR1 {
+            true => Ok(SettingsStrategy::SettingsCommand),
+            false => Ok(SettingsStrategy::ContentProvider),
+        }
+    }
+}
+
+/// URI of the settings of `namespace` in the settings content provider.
+fn settings_uri(namespace: SettingsNamespace) -> ContentUri {
+    ContentUri::new("settings").join(namespace)
+}
@@ -1,33 +1,38 @@
-use std::io::{ErrorKind, Read, Write};
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::{Duration, Instant},
+};
+
+#[cfg(unix)]
+use signal_hook::{consts::SIGWINCH, iterator::Signals};
+#[cfg(windows)]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use terminal_size::{terminal_size_of, Height, Width};
 
 use crate::{
     adb_termios::ADBTermios,
-    models::{AdbCommand, HostFeatures},
-    AdbTcpConnexion, Result, RustADBError,
+    models::{AdbCommand, HostFeatures, ShellEnvironment},
+    utils::shell_quote,
+    AdbShellSession, AdbShellWriter, AdbTcpConnexion, DeviceSelector, Result, RustADBError,
+    ShellOutput,
 };
 
+const CTRL_D: u8 = 0x04;
+/// Marker preceding the exit status of commands run by `shell_checked` without shell v2 protocol.
+const EXIT_MARKER: &str = "__EXIT:";
+
 impl AdbTcpConnexion {
     /// Runs 'command' in a shell on the device, and return its output and error streams.
     pub fn shell_command<S: ToString>(
         &mut self,
-        serial: &Option<S>,
+        device: impl Into<DeviceSelector>,
         command: impl IntoIterator<Item = S>,
     ) -> Result<()> {
-        let supported_features = self.host_features(serial)?;
-        if !supported_features.contains(&HostFeatures::ShellV2)
-            && !supported_features.contains(&HostFeatures::Cmd)
-        {
-            return Err(RustADBError::ADBShellNotSupported);
-        }
-
-        self.new_connection()?;
-
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
-        }
+        let device = device.into();
+        self.connect_device(&device)?;
         self.send_adb_request(AdbCommand::ShellCommand(
             command
                 .into_iter()
@@ -55,90 +60,379 @@ impl AdbTcpConnexion {
         }
     }
 
-    /// Starts an interactive shell session on the device. Redirects stdin/stdout/stderr as appropriate.
-    pub fn shell<S: ToString>(&mut self, serial: &Option<S>) -> Result<()> {
-        let mut adb_termios = ADBTermios::new(std::io::stdin())?;
-        adb_termios.set_adb_termios()?;
+    /// Runs `command` in a shell on the device, feeding `input` to its standard input (e.g. a script piped into `sh`).
+    ///
+    /// Output and error streams are both written to `output`. Returns the exit code of the command when known.
+    ///
+    /// With shell v2 protocol, `input` is sent as is and its end is signaled to the command.
+    /// Otherwise it is passed as a here-document, so it must be UTF-8 text and gets a trailing newline if it has none.
+    pub fn shell_command_with_input<S: ToString>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: impl IntoIterator<Item = S>,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<Option<u8>> {
+        let device = device.into();
+        let command = command
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
 
-        self.tcp_stream.set_nodelay(true)?;
+        let mut session = self.start_shell_command(&device, command, input)?;
+        loop {
+            match session.wait()? {
+                Some(ShellOutput::Stdout(data)) | Some(ShellOutput::Stderr(data)) => {
+                    output.write_all(&data)?
+                }
+                Some(ShellOutput::Exit(code)) => return Ok(code),
+                None => return Ok(None),
+            }
+        }
+    }
 
-        // FORWARD CTRL+C !!
+    /// Starts `command` in a shell on the device, sending it `input` before returning its session.
+    fn start_shell_command(
+        &mut self,
+        device: &DeviceSelector,
+        command: String,
+        input: &mut dyn Read,
+    ) -> Result<AdbShellSession> {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+
+        let shell_v2 = self.supports(device, HostFeatures::ShellV2)?;
+        self.connect_device(device)?;
+
+        if shell_v2 {
+            self.send_adb_request(AdbCommand::ShellV2Command(command))?;
+            let session = AdbShellSession::new(self.tcp_stream.try_clone()?, true)?;
 
-        let supported_features = self.host_features(serial)?;
-        if !supported_features.contains(&HostFeatures::ShellV2)
-            && !supported_features.contains(&HostFeatures::Cmd)
-        {
-            return Err(RustADBError::ADBShellNotSupported);
+            // Output is read in the background, large inputs cannot block the command
+            let mut writer = session.writer();
+            writer.write_all(&data)?;
+            writer.close()?;
+            Ok(session)
+        } else {
+            let text = String::from_utf8(data).map_err(|_| {
+                RustADBError::UnsupportedByServer(HostFeatures::ShellV2.to_string())
+            })?;
+            self.send_adb_request(AdbCommand::ShellCommand(here_document(&command, &text)))?;
+            AdbShellSession::new(self.tcp_stream.try_clone()?, false)
         }
+    }
 
-        self.new_connection()?;
+    /// Runs `command` in a shell on the device as [AdbTcpConnexion::shell_command_with_input], killing it if it does not exit within `timeout`.
+    ///
+    /// Fails with [RustADBError::RemoteCommandTimeout] once the command has been killed.
+    pub fn shell_command_with_timeout<S: ToString>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: impl IntoIterator<Item = S>,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<Option<u8>> {
+        let device = device.into();
+        let command = command
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let deadline = Instant::now() + timeout;
 
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
+        // Shell prints its pid first, so that the command can be killed from another shell
+        let mut session =
+            self.start_shell_command(&device, format!("echo $$; {command}"), input)?;
+        let mut pid = None;
+        let mut pid_line = Some(Vec::new());
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match session.wait_timeout(remaining) {
+                Ok(Some(ShellOutput::Stdout(data))) => match pid_line.as_mut() {
+                    Some(line) => {
+                        line.extend_from_slice(&data);
+                        if let Some(end) = line.iter().position(|b| *b == b'\n') {
+                            pid = String::from_utf8_lossy(&line[..end])
+                                .trim()
+                                .parse::<u32>()
+                                .ok();
+                            output.write_all(&line[end + 1..])?;
+                            pid_line = None;
+                        }
+                    }
+                    None => output.write_all(&data)?,
+                },
+                Ok(Some(ShellOutput::Stderr(data))) => output.write_all(&data)?,
+                Ok(Some(ShellOutput::Exit(code))) => return Ok(code),
+                Ok(None) => return Ok(None),
+                Err(RustADBError::Timeout(_)) => {
+                    // Closing the connection is not enough for commands ignoring hangups
+                    drop(session);
+                    if let Some(pid) = pid {
+                        let kill = format!("pkill -9 -P {pid}; kill -9 {pid}");
+                        let _ = self.exec_command(&device, kill);
+                    }
+                    return Err(RustADBError::RemoteCommandTimeout(command));
+                }
+                Err(e) => return Err(e),
             }
         }
-        self.send_adb_request(AdbCommand::Shell)?;
+    }
+
+    /// Runs `command` in a shell on the device with the working directory and variables of `environment`.
+    ///
+    /// Behaves as [AdbTcpConnexion::shell_command_with_input], failing with [RustADBError::RemoteDirectoryNotFound]
+    /// rather than running the command elsewhere when the working directory does not exist.
+    pub fn shell_command_in<S: ToString>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        environment: &ShellEnvironment,
+        command: impl IntoIterator<Item = S>,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<Option<u8>> {
+        let device = device.into();
+        let command = command
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let command = environment.wrap(&command)?;
+
+        if let Some(directory) = &environment.working_directory {
+            let check = format!("[ -d {} ] && echo ok", shell_quote(directory));
+            if String::from_utf8(self.exec_command(&device, check)?)?.trim() != "ok" {
+                return Err(RustADBError::RemoteDirectoryNotFound(directory.to_string()));
+            }
+        }
+
+        self.shell_command_with_input(&device, [command], input, output)
+    }
+
+    /// Runs `command` in a shell on the device and returns its standard output, failing with [RustADBError::CommandFailed] unless it exits successfully.
+    ///
+    /// The exit status is reported by shell v2 protocol when supported, and echoed after the output of `command` otherwise.
+    pub fn shell_checked(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        command: impl ToString,
+    ) -> Result<Vec<u8>> {
+        let device = device.into();
+        let command = command.to_string();
+
+        let (code, stdout, stderr) = if self.supports(&device, HostFeatures::ShellV2)? {
+            self.connect_device(&device)?;
+            self.send_adb_request(AdbCommand::ShellV2Command(command))?;
+            let mut session = AdbShellSession::new(self.tcp_stream.try_clone()?, true)?;
+            session.writer().close()?;
+
+            let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+            let code = loop {
+                match session.wait()? {
+                    Some(ShellOutput::Stdout(data)) => stdout.extend_from_slice(&data),
+                    Some(ShellOutput::Stderr(data)) => stderr.extend_from_slice(&data),
+                    Some(ShellOutput::Exit(Some(code))) => break code,
+                    Some(ShellOutput::Exit(None)) | None => {
+                        return Err(RustADBError::ConnectionClosedByPeer)
+                    }
+                }
+            };
+            (code, stdout, stderr)
+        } else {
+            let mut output = self.exec_command(
+                &device,
+                format!("({command}); printf '\\n{EXIT_MARKER}%d' $?"),
+            )?;
+            let marker = format!("\n{EXIT_MARKER}");
+            let position = output
+                .windows(marker.len())
+                .rposition(|window| window == marker.as_bytes())
+                .ok_or(RustADBError::ConnectionClosedByPeer)?;
+            let code = std::str::from_utf8(&output[position + marker.len()..])?
+                .trim()
+                .parse::<u8>()?;
+            output.truncate(position);
+            (code, output, Vec::new())
+        };
+
+        if code != 0 {
+            return Err(RustADBError::CommandFailed {
+                code,
+                stdout: String::from_utf8_lossy(&stdout).to_string(),
+                stderr: String::from_utf8_lossy(&stderr).to_string(),
+            });
+        }
+
+        Ok(stdout)
+    }
+
+    /// Opens an interactive shell session on the device.
+    ///
+    /// A PTY is requested when the device supports shell v2 protocol, legacy shell protocol is used otherwise.
+    pub fn shell_session(&mut self, device: impl Into<DeviceSelector>) -> Result<AdbShellSession> {
+        let device = device.into();
+        let shell_v2 = self.supports(&device, HostFeatures::ShellV2)?;
+
+        self.connect_device(&device)?;
+        self.tcp_stream.set_nodelay(true)?;
+
+        if shell_v2 {
+            self.send_adb_request(AdbCommand::ShellV2Interactive)?;
+        } else {
+            self.send_adb_request(AdbCommand::Shell)?;
+        }
 
-        // let read_stream = Arc::new(self.tcp_stream);
-        let mut read_stream = self.tcp_stream.try_clone()?;
+        AdbShellSession::new(self.tcp_stream.try_clone()?, shell_v2)
+    }
 
-        // Writing thread
-        let mut write_stream = read_stream.try_clone()?;
-        let writer_t = std::thread::spawn(move || -> Result<()> {
+    /// Starts an interactive shell session on the device. Redirects stdin/stdout/stderr as appropriate.
+    ///
+    /// When the device supports it, a PTY sized as the local terminal is requested, and local terminal resizes are propagated to it.
+    pub fn shell(&mut self, device: impl Into<DeviceSelector>) -> Result<()> {
+        let device = device.into();
+        // Terminal is put in raw mode: Ctrl-C and other control characters are not handled locally
+        // but sent to the device, where the PTY turns them into signals for the remote process.
+        let mut adb_termios = ADBTermios::new(std::io::stdin())?;
+        adb_termios.set_adb_termios()?;
+
+        let mut session = self.shell_session(&device)?;
+        let has_pty = session.has_pty();
+
+        let writer = session.writer();
+        send_window_size(&writer)?;
+
+        let resize_watcher = ResizeWatcher::spawn(session.writer())?;
+
+        // Writing thread, not joined as it stays blocked on stdin until next input
+        let mut writer = session.writer();
+        std::thread::spawn(move || -> Result<()> {
             let mut buf = [0; 1024];
             loop {
                 let size = std::io::stdin().read(&mut buf)?;
+                if size == 0 {
+                    return writer.close();
+                }
 
-                write_stream.write_all(&buf[0..size])?;
+                // Without PTY, Ctrl-D is not interpreted by the device: closes the standard input instead
+                match buf[0..size].iter().position(|b| *b == CTRL_D) {
+                    Some(position) if !has_pty => {
+                        writer.write_all(&buf[0..position])?;
+                        return writer.close();
+                    }
+                    _ => writer.write_all(&buf[0..size])?,
+                }
             }
         });
 
-        // Reading thread
-        let reader_t = std::thread::spawn(move || -> Result<()> {
-            let buffer_size = 512;
-            loop {
-                let mut buffer = vec![0; buffer_size];
-                match read_stream.read(&mut buffer) {
-                    Ok(size) if size == 0 => {
-                        // TODO: check if return here is good.. return Ok(()) ?
+        let result = loop {
+            match session.wait() {
+                Ok(Some(ShellOutput::Stdout(data))) => {
+                    let mut stdout = std::io::stdout();
+                    stdout.write_all(&data)?;
+                    stdout.flush()?;
+                }
+                Ok(Some(ShellOutput::Stderr(data))) => {
+                    let mut stderr = std::io::stderr();
+                    stderr.write_all(&data)?;
+                    stderr.flush()?;
+                }
+                Ok(Some(ShellOutput::Exit(_))) | Ok(None) => break Ok(()),
+                Err(RustADBError::IOError(e)) if e.kind() == ErrorKind::BrokenPipe => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
 
-                        // return Err(RustADBError::IOError(std::io::Error::from(
-                        //     ErrorKind::BrokenPipe,
-                        // )));
+        resize_watcher.stop();
+        result
+    }
+}
 
-                        return Ok(());
-                    }
-                    Ok(_) => {
-                        print!("{}", String::from_utf8(buffer.to_vec())?);
-                        std::io::stdout().flush()?;
-                    }
-                    Err(e) => {
-                        return Err(RustADBError::IOError(e));
-                    }
+/// Builds a shell command line running `command` with `text` as standard input.
+fn here_document(command: &str, text: &str) -> String {
+    // Delimiter must not appear alone on a line of the text
+    let mut delimiter = "ADB_CLIENT_EOF".to_string();
+    while text.lines().any(|line| line == delimiter) {
+        delimiter.push('_');
+    }
+
+    let newline = if text.is_empty() || text.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+
+    format!("{command} <<'{delimiter}'\n{text}{newline}{delimiter}")
+}
+
+/// Sends current local terminal size to the device, if stdout is a terminal.
+fn send_window_size(writer: &AdbShellWriter) -> Result<()> {
+    if let Some((Width(columns), Height(rows))) = terminal_size_of(std::io::stdout()) {
+        writer.resize(rows, columns)?;
+    }
+
+    Ok(())
+}
+
+/// Propagates local terminal resizes to the device, until stopped.
+#[cfg(unix)]
+struct ResizeWatcher {
+    handle: signal_hook::iterator::Handle,
+}
+
+#[cfg(unix)]
+impl ResizeWatcher {
+    fn spawn(writer: AdbShellWriter) -> Result<Self> {
+        let mut signals = Signals::new([SIGWINCH])?;
+        let handle = signals.handle();
+        std::thread::spawn(move || {
+            for _ in signals.forever() {
+                if send_window_size(&writer).is_err() {
+                    break;
                 }
             }
         });
 
-        if let Err(e) = reader_t.join().unwrap() {
-            match e {
-                RustADBError::IOError(e) if e.kind() == ErrorKind::BrokenPipe => {}
-                _ => {
-                    return Err(e);
-                }
-            }
-        }
+        Ok(Self { handle })
+    }
+
+    fn stop(self) {
+        self.handle.close();
+    }
+}
+
+/// Propagates local terminal resizes to the device, until stopped.
+///
+/// Windows has no resize signal, console size is polled instead.
+#[cfg(windows)]
+struct ResizeWatcher {
+    stopped: Arc<AtomicBool>,
+}
 
-        if let Err(e) = writer_t.join().unwrap() {
-            match e {
-                RustADBError::IOError(e) if e.kind() == ErrorKind::BrokenPipe => {}
-                _ => {
-                    return Err(e);
+#[cfg(windows)]
+impl ResizeWatcher {
+    fn spawn(writer: AdbShellWriter) -> Result<Self> {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        std::thread::spawn(move || {
+            let mut size = terminal_size_of(std::io::stdout());
+            while !thread_stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(250));
+                let new_size = terminal_size_of(std::io::stdout());
+                if new_size != size {
+                    size = new_size;
+                    if send_window_size(&writer).is_err() {
+                        break;
+                    }
                 }
             }
-        }
+        });
+
+        Ok(Self { stopped })
+    }
 
-        Ok(())
+    fn stop(self) {
+        self.stopped.store(true, Ordering::Relaxed);
     }
 }
@@ -0,0 +1,68 @@
+use std::{fs::File, path::Path};
+
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, DeviceSelector, ProfileTarget, Result, RustADBError,
+    SimpleperfOptions,
+};
+
+/// Path of the recording on the device, removed once pulled.
+const PERF_DATA_PATH: &str = "/data/local/tmp/adb_client_perf.data";
+
+impl AdbTcpConnexion {
+    /// Profiles `target` with `simpleperf record` as set by `options`, and pulls the recording into the local file `local_path`.
+    ///
+    /// Returns the output of `simpleperf report` if requested by `options`.
+    /// Fails with [RustADBError::ShellCommandFailed] and the output of simpleperf if the recording fails.
+    pub fn simpleperf_record<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        target: ProfileTarget,
+        options: &SimpleperfOptions,
+        local_path: P,
+    ) -> Result<Option<String>> {
+        let device = device.into();
+        let mut command = format!(
+            "simpleperf record {target} --duration {} -o {PERF_DATA_PATH}",
+            options.duration.as_secs_f64()
+        );
+        if !options.events.is_empty() {
+            command += &format!(" -e {}", shell_quote(&options.events.join(",")));
+        }
+        if let Some(frequency) = options.frequency {
+            command += &format!(" -f {frequency}");
+        }
+        if options.call_graph {
+            command += " -g";
+        }
+
+        // Recording blocks for its whole duration
+        let output = self.exec_command(&device, format!("{command} 2>&1 && echo done"))?;
+        let output = String::from_utf8_lossy(&output);
+        if output.lines().last() != Some("done") {
+            let _ = self.exec_command(&device, format!("rm -f {PERF_DATA_PATH}"));
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        let result = self.pull_recording(&device, options.report, local_path.as_ref());
+        self.exec_command(&device, format!("rm -f {PERF_DATA_PATH}"))?;
+        result
+    }
+
+    fn pull_recording(
+        &mut self,
+        device: &DeviceSelector,
+        report: bool,
+        local_path: &Path,
+    ) -> Result<Option<String>> {
+        self.recv(device, PERF_DATA_PATH, &mut File::create(local_path)?)?;
+        if !report {
+            return Ok(None);
+        }
+
+        let output = self.exec_command(
+            device,
+            format!("simpleperf report -i {PERF_DATA_PATH} 2>&1"),
+        )?;
+        Ok(Some(String::from_utf8(output)?))
+    }
+}
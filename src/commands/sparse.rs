@@ -0,0 +1,109 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    time::Instant,
+};
+
+use crate::{
+    models::HostFeatures,
+    utils::{open_local_file, shell_quote},
+    AdbTcpConnexion, DeviceSelector, Result, TransferDirection, TransferReport,
+};
+
+/// Granularity of zero blocks detection, and block size of `dd` writing data on the device.
+const BLOCK_SIZE: u64 = 4096;
+
+/// Zero regions shorter than this are sent along with surrounding data, saving a command per data extent.
+const MIN_HOLE_SIZE: u64 = 1024 * 1024;
+
+impl AdbTcpConnexion {
+    /// Pushes `local_path` to `remote_path` on the device, skipping its zero regions, e.g. for emulator images or empty partitions.
+    ///
+    /// Remote file is created with the size of the local one by `truncate`, then each data extent is written in place by `dd`,
+    /// leaving zero regions as holes. Falls back to [AdbTcpConnexion::push_file] when the device lacks shell v2 protocol,
+    /// needed to signal the end of each extent.
+    ///
+    /// Returned report counts the bytes actually sent.
+    pub fn push_sparse<P: AsRef<Path>>(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        local_path: P,
+        remote_path: &str,
+    ) -> Result<TransferReport> {
+        let device = device.into();
+        if !self.supports(&device, HostFeatures::ShellV2)? {
+            return self.push_file(device, local_path, remote_path);
+        }
+
+        let start = Instant::now();
+        let mut input = open_local_file(local_path)?;
+        let size = input.metadata()?.len();
+        let extents = data_extents(&mut input)?;
+        let data_size = extents.iter().map(|(_, length)| length).sum();
+        self.check_free_space(&device, remote_path, data_size)?;
+
+        let remote = shell_quote(remote_path);
+        self.run_shell_v2(
+            &device,
+            format!(": > {remote} && truncate -s {size} {remote}"),
+            &mut io::empty(),
+            &mut io::sink(),
+            None,
+        )?;
+
+        for (offset, length) in extents {
+            input.seek(SeekFrom::Start(offset))?;
+            self.run_shell_v2(
+                &device,
+                format!(
+                    "dd of={remote} bs={BLOCK_SIZE} seek={} conv=notrunc 2>/dev/null",
+                    offset / BLOCK_SIZE
+                ),
+                &mut (&mut input).take(length),
+                &mut io::sink(),
+                None,
+            )?;
+        }
+
+        Ok(self.transferred(
+            TransferDirection::Sent,
+            TransferReport::file(data_size, start),
+        ))
+    }
+}
+
+/// Offsets and lengths of the regions of `input` holding non-zero data, aligned on [BLOCK_SIZE] except at the end of file.
+///
+/// Regions separated by less than [MIN_HOLE_SIZE] of zeros are merged.
+fn data_extents(input: &mut File) -> Result<Vec<(u64, u64)>> {
+    input.seek(SeekFrom::Start(0))?;
+    let mut reader = io::BufReader::new(input);
+    let mut block = vec![0; BLOCK_SIZE as usize];
+    let mut extents: Vec<(u64, u64)> = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        let mut length = 0;
+        while length < block.len() {
+            match reader.read(&mut block[length..])? {
+                0 => break,
+                read => length += read,
+            }
+        }
+        if length == 0 {
+            return Ok(extents);
+        }
+
+        if block[..length].iter().any(|byte| *byte != 0) {
+            let length = length as u64;
+            match extents.last_mut() {
+                Some((start, extent)) if offset - (*start + *extent) < MIN_HOLE_SIZE => {
+                    *extent = offset + length - *start
+                }
+                _ => extents.push((offset, length)),
+            }
+        }
+        offset += length as u64;
+    }
+}
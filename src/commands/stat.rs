@@ -1,53 +1,17 @@
-use std::{
-    fmt::Display,
-    io::{Read, Write},
-    time::{Duration, UNIX_EPOCH},
-};
+use std::io::{Read, Write};
 
 use byteorder::{ByteOrder, LittleEndian};
-use chrono::{DateTime, Utc};
 
 use crate::{
-    models::{AdbCommand, SyncCommand},
-    AdbTcpConnexion, Result, RustADBError,
+    adb_stat_cache::StatCache,
+    models::{AdbCommand, AdbStatResponse, HostFeatures, SyncCommand},
+    parser,
+    protocol::codec::SYNC_STAT_V2_SIZE,
+    AdbTcpConnexion, DeviceSelector, Result, RustADBError,
 };
 
-#[derive(Debug)]
-pub struct AdbStatResponse {
-    pub file_perm: u32,
-    pub file_size: u32,
-    pub mod_time: u32,
-}
-
-impl From<[u8; 12]> for AdbStatResponse {
-    fn from(value: [u8; 12]) -> Self {
-        Self {
-            file_perm: LittleEndian::read_u32(&value[0..4]),
-            file_size: LittleEndian::read_u32(&value[4..8]),
-            mod_time: LittleEndian::read_u32(&value[8..]),
-        }
-    }
-}
-
-impl Display for AdbStatResponse {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let d = UNIX_EPOCH + Duration::from_secs(self.mod_time.into());
-        // Create DateTime from SystemTime
-        let datetime = DateTime::<Utc>::from(d);
-
-        writeln!(f, "File permissions: {}", self.file_perm)?;
-        writeln!(f, "File size: {} bytes", self.file_size)?;
-        write!(
-            f,
-            "Modification time: {}",
-            datetime.format("%Y-%m-%d %H:%M:%S.%f %Z")
-        )?;
-        Ok(())
-    }
-}
-
 impl AdbTcpConnexion {
-    fn handle_stat_command<S: AsRef<str>>(&mut self, path: S) -> Result<AdbStatResponse> {
+    fn handle_stat_command<S: AsRef<str>>(&mut self, path: S, v2: bool) -> Result<AdbStatResponse> {
         let mut len_buf = [0_u8; 4];
         LittleEndian::write_u32(&mut len_buf, path.as_ref().len() as u32);
 
@@ -60,11 +24,25 @@ impl AdbTcpConnexion {
         let mut response = [0_u8; 4];
         self.tcp_stream.read_exact(&mut response)?;
         match std::str::from_utf8(response.as_ref())? {
-            "STAT" => {
+            "STAT" if !v2 => {
                 let mut data = [0_u8; 12];
                 self.tcp_stream.read_exact(&mut data)?;
 
-                Ok(data.into())
+                parser::sync_stat(&data)
+            }
+            "LST2" if v2 => {
+                let mut data = [0_u8; SYNC_STAT_V2_SIZE];
+                self.tcp_stream.read_exact(&mut data)?;
+
+                // Missing files are reported as zeroed metadata, as with version 1
+                Ok(match parser::sync_stat_v2(&data)? {
+                    (0, stat) => stat,
+                    _ => AdbStatResponse {
+                        file_perm: 0,
+                        file_size: 0,
+                        mod_time: 0,
+                    },
+                })
             }
             x => Err(RustADBError::UnknownResponseType(format!(
                 "Unknown response {}",
@@ -74,26 +52,65 @@ impl AdbTcpConnexion {
     }
 
     /// Stat file given as [path] on the device.
-    pub fn stat<S: ToString, A: AsRef<str>>(
+    ///
+    /// Size and modification time are 64-bit on devices supporting `stat_v2`, and truncated to 32 bits otherwise.
+    /// Result is taken from the stat cache when enabled, see [AdbTcpConnexionBuilder::stat_cache](crate::AdbTcpConnexionBuilder::stat_cache).
+    pub fn stat<A: AsRef<str>>(
         &mut self,
-        serial: Option<S>,
+        device: impl Into<DeviceSelector>,
         path: A,
     ) -> Result<AdbStatResponse> {
-        self.new_connection()?;
-
-        match serial {
-            None => self.send_adb_request(AdbCommand::TransportAny)?,
-            Some(serial) => {
-                self.send_adb_request(AdbCommand::TransportSerial(serial.to_string()))?
-            }
+        let device = device.into();
+        let path = path.as_ref();
+        if let Some(stat) = self.stat_cache.as_ref().and_then(|c| c.stat(&device, path)) {
+            return Ok(stat);
         }
 
+        let v2 = self.supports(&device, HostFeatures::StatV2)?;
+        self.connect_device(&device)?;
+
         // Set device in SYNC mode
         self.send_adb_request(AdbCommand::Sync)?;
 
         // Send a "Stat" command
-        self.send_sync_request(SyncCommand::Stat(path.as_ref()))?;
+        self.send_sync_request(match v2 {
+            true => SyncCommand::LstatV2,
+            false => SyncCommand::Stat,
+        })?;
+
+        let stat = self.handle_stat_command(path, v2)?;
+        if let Some(cache) = self.stat_cache.as_mut() {
+            cache.insert_stat(&device, path, stat.clone());
+        }
+
+        Ok(stat)
+    }
+
+    /// Enables or disables the stat cache, see [AdbTcpConnexionBuilder::stat_cache](crate::AdbTcpConnexionBuilder::stat_cache).
+    ///
+    /// Disabling it forgets cached results.
+    pub fn set_stat_cache(&mut self, enabled: bool) {
+        match enabled {
+            true => {
+                self.stat_cache.get_or_insert_with(StatCache::default);
+            }
+            false => self.stat_cache = None,
+        }
+    }
 
-        self.handle_stat_command(path)
+    /// Forgets cached results for `path` and everything below it, as well as the listing of its parent directory,
+    /// e.g. after pushing, removing or renaming it.
+    pub fn invalidate_stat_cache(&mut self, device: impl Into<DeviceSelector>, path: &str) {
+        let device = device.into();
+        if let Some(cache) = self.stat_cache.as_mut() {
+            cache.invalidate(&device, path);
+        }
+    }
+
+    /// Forgets all cached results, keeping the stat cache enabled if it is.
+    pub fn clear_stat_cache(&mut self) {
+        if let Some(cache) = self.stat_cache.as_mut() {
+            *cache = StatCache::default();
+        }
     }
 }
@@ -0,0 +1,14 @@
+use crate::{AdbStateSnapshot, AdbTcpConnexion, DeviceSelector, Result, StateKey};
+
+impl AdbTcpConnexion {
+    /// Captures the values of the settings and properties of `keys`, which are restored when the returned snapshot is dropped.
+    ///
+    /// Overrides made with [AdbStateSnapshot::set] are thus undone, preventing device state from drifting across test runs.
+    pub fn snapshot_state(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        keys: impl IntoIterator<Item = StateKey>,
+    ) -> Result<AdbStateSnapshot<'_>> {
+        AdbStateSnapshot::new(self, device.into(), keys)
+    }
+}
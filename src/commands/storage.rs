@@ -0,0 +1,27 @@
+use crate::{AdbTcpConnexion, DeviceSelector, Result};
+
+/// Prints the external storage of the foreground user, which is not `/sdcard` for secondary users, falling back to `$EXTERNAL_STORAGE`.
+const EXTERNAL_STORAGE_SCRIPT: &str = r#"u=$(am get-current-user 2>/dev/null); [ -n "$u" ] && [ -d "/storage/emulated/$u" ] && echo "/storage/emulated/$u" || echo "${EXTERNAL_STORAGE:-/sdcard}""#;
+
+impl AdbTcpConnexion {
+    /// Resolves the path of the external storage of the foreground user of the device, e.g. `/storage/emulated/10` for a secondary user.
+    ///
+    /// Falls back to `$EXTERNAL_STORAGE` on devices without multi-user emulated storage, then to `/sdcard`.
+    pub fn external_storage_path(&mut self, device: impl Into<DeviceSelector>) -> Result<String> {
+        let device = device.into();
+        let output = String::from_utf8(self.exec_command(&device, EXTERNAL_STORAGE_SCRIPT)?)?;
+
+        Ok(match output.trim() {
+            "" => "/sdcard".to_string(),
+            path => path.to_string(),
+        })
+    }
+
+    /// External storage of the user set with [AdbTcpConnexion::set_user], or of the foreground user if none is.
+    pub(crate) fn user_storage_path(&mut self, device: &DeviceSelector) -> Result<String> {
+        match self.user {
+            Some(user) => Ok(format!("/storage/emulated/{user}")),
+            None => self.external_storage_path(device),
+        }
+    }
+}
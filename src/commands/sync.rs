@@ -0,0 +1,16 @@
+use crate::{
+    models::AdbCommand, protocol::sync::SyncSession, AdbTcpConnexion, DeviceSelector, Result,
+};
+
+impl AdbTcpConnexion {
+    /// Opens a new connection to the device switched to sync mode, to exchange sync packets with [SyncSession].
+    ///
+    /// Meant for sync requests not provided by this crate. Connection is left when the session is dropped.
+    pub fn sync_session(&mut self, device: impl Into<DeviceSelector>) -> Result<SyncSession<'_>> {
+        let device = device.into();
+        self.connect_device(&device)?;
+        self.send_adb_request(AdbCommand::Sync)?;
+
+        Ok(SyncSession::new(&mut self.tcp_stream))
+    }
+}
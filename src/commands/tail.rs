@@ -0,0 +1,16 @@
+use crate::{AdbFileTail, AdbTcpConnexion, DeviceSelector, Result};
+
+impl AdbTcpConnexion {
+    /// Reads the lines of the file located at `path` on the device.
+    ///
+    /// When `follow` is set, lines appended to the file are then returned as they are written, even if the file is rotated
+    /// or truncated meanwhile. `tail -F` is used when available on the device, file size is polled otherwise.
+    pub fn tail_file(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        path: &str,
+        follow: bool,
+    ) -> Result<AdbFileTail<'_>> {
+        AdbFileTail::new(self, device.into(), path, follow)
+    }
+}
@@ -0,0 +1,132 @@
+use std::{io::Write, path::Path};
+
+use ruzstd::{
+    decoding::StreamingDecoder,
+    encoding::{compress_to_vec, CompressionLevel},
+};
+
+use super::tar_transfer::{append_dir, unpack_tar, CountingReader, CountingWriter};
+use crate::{
+    models::{AdbCommand, HostFeatures},
+    utils::shell_quote,
+    AdbShellSession, AdbTcpConnexion, DeviceSelector, Result, RustADBError, ShellOutput,
+    TransferFilter,
+};
+
+/// Size of data compressed at once on the host, each chunk being sent as a separate zstd frame.
+const FRAME_SIZE: usize = 1024 * 1024;
+
+impl AdbTcpConnexion {
+    /// Whether a `zstd` binary is available on the device.
+    pub(crate) fn has_device_zstd(&mut self, device: &DeviceSelector) -> Result<bool> {
+        self.has_command(device, "zstd")
+    }
+
+    /// Pulls the tar archive written by `command` compressed by `zstd` on the device, and extracts it into `local_dir`.
+    ///
+    /// Returns the number of compressed bytes received and of files extracted.
+    pub(crate) fn pull_tar_zstd(
+        &mut self,
+        device: &DeviceSelector,
+        command: &str,
+        local_dir: &Path,
+        filter: &TransferFilter,
+    ) -> Result<(u64, u64)> {
+        self.open_exec(device, format!("{command} | zstd -c 2>/dev/null"))?;
+
+        let mut reader = CountingReader {
+            inner: &mut self.tcp_stream,
+            read: 0,
+        };
+        let files = unpack_tar(StreamingDecoder::new(&mut reader)?, local_dir, filter)?;
+
+        Ok((reader.read, files))
+    }
+
+    /// Whether a compressed archive can be pushed: its end is signaled with shell v2 protocol before `zstd` decompresses it on the device.
+    pub(crate) fn can_push_tar_zstd(&mut self, device: &DeviceSelector) -> Result<bool> {
+        Ok(self.supports(device, HostFeatures::ShellV2)? && self.has_device_zstd(device)?)
+    }
+
+    /// Pushes the content of `local_dir` into `remote_dir` as a tar archive compressed on the host.
+    ///
+    /// Returns the number of compressed bytes sent.
+    pub(crate) fn push_tar_zstd(
+        &mut self,
+        device: &DeviceSelector,
+        local_dir: &Path,
+        remote_dir: &str,
+        filter: &TransferFilter,
+    ) -> Result<u64> {
+        let remote_dir = shell_quote(remote_dir);
+        self.connect_device(device)?;
+        self.send_adb_request(AdbCommand::ShellV2Command(format!(
+            "mkdir -p {remote_dir} && zstd -dc | tar -xf - -C {remote_dir}"
+        )))?;
+
+        // Output is read in the background, the device cannot block while the archive is sent
+        let mut session = AdbShellSession::new(self.tcp_stream.try_clone()?, true)?;
+        let mut writer = FrameWriter {
+            inner: CountingWriter {
+                inner: session.writer(),
+                written: 0,
+            },
+            buffer: Vec::with_capacity(FRAME_SIZE),
+        };
+        append_dir(&mut writer, local_dir, filter)?;
+        writer.flush_frame()?;
+        writer.inner.inner.close()?;
+
+        let mut errors = Vec::new();
+        loop {
+            match session.wait()? {
+                Some(ShellOutput::Stdout(data)) | Some(ShellOutput::Stderr(data)) => {
+                    errors.extend_from_slice(&data)
+                }
+                Some(ShellOutput::Exit(Some(0))) => return Ok(writer.inner.written),
+                Some(ShellOutput::Exit(_)) | None => {
+                    return Err(RustADBError::ShellCommandFailed(
+                        String::from_utf8_lossy(&errors).trim().to_string(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Compresses written data into zstd frames of [FRAME_SIZE] bytes of input.
+///
+/// Frames are compressed in memory so that errors of the inner writer are reported instead of panicking in the encoder.
+struct FrameWriter<W: Write> {
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Compresses and writes pending data, if any.
+    fn flush_frame(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let frame = compress_to_vec(self.buffer.as_slice(), CompressionLevel::Fastest);
+        self.buffer.clear();
+        self.inner.write_all(&frame)
+    }
+}
+
+impl<W: Write> Write for FrameWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let size = buf.len().min(FRAME_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..size]);
+        if self.buffer.len() == FRAME_SIZE {
+            self.flush_frame()?;
+        }
+
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,8 @@
+use crate::{AdbTcpConnexion, AdbTempDir, DeviceSelector, Result};
+
+impl AdbTcpConnexion {
+    /// Creates a uniquely named directory under `/data/local/tmp` on the device, removed with its content when the returned value is dropped.
+    pub fn temp_dir(&mut self, device: impl Into<DeviceSelector>) -> Result<AdbTempDir<'_>> {
+        AdbTempDir::new(self, device.into())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, SettingsNamespace, TestPrepOptions,
+};
+
+impl AdbTcpConnexion {
+    /// Prepares the device for automated testing by applying the steps enabled in `options`.
+    pub fn prepare_for_testing(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        options: TestPrepOptions,
+    ) -> Result<()> {
+        let device = device.into();
+        if options.disable_animations {
+            for key in [
+                "window_animation_scale",
+                "transition_animation_scale",
+                "animator_duration_scale",
+            ] {
+                self.settings_put(&device, SettingsNamespace::Global, key, 0)?;
+            }
+        }
+
+        if options.stay_awake {
+            // AC, USB and wireless chargers
+            self.settings_put(
+                &device,
+                SettingsNamespace::Global,
+                "stay_on_while_plugged_in",
+                7,
+            )?;
+        }
+
+        if options.dismiss_keyguard {
+            // Wakes the screen up first, keyguard cannot be dismissed while it is off
+            self.exec_command(&device, "input keyevent KEYCODE_WAKEUP")?;
+            self.exec_command(&device, "wm dismiss-keyguard")?;
+        }
+
+        if options.disable_soft_keyboard {
+            let input_methods = self.exec_command(&device, "ime list -s")?;
+            for input_method in String::from_utf8(input_methods)?.lines() {
+                let input_method = input_method.trim();
+                if !input_method.is_empty() {
+                    self.exec_command(
+                        &device,
+                        format!("ime disable {}", shell_quote(input_method)),
+                    )?;
+                }
+            }
+        }
+
+        if options.confirm_immersive_mode {
+            self.settings_put(
+                &device,
+                SettingsNamespace::Secure,
+                "immersive_mode_confirmations",
+                "confirmed",
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+
+use crate::{utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError};
+
+impl AdbTcpConnexion {
+    /// Gets the current date and time of the device.
+    pub fn get_time(&mut self, device: impl Into<DeviceSelector>) -> Result<DateTime<Utc>> {
+        let device = device.into();
+        let output = self.exec_command(&device, "date +%s")?;
+        let timestamp = String::from_utf8(output)?.trim().parse::<i64>()?;
+
+        DateTime::from_timestamp(timestamp, 0).ok_or(RustADBError::ConvertionError)
+    }
+
+    /// Sets the date and time of the device. Requires root privileges.
+    pub fn set_time(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        datetime: DateTime<Utc>,
+    ) -> Result<()> {
+        let device = device.into();
+        if !self.is_root(&device)? {
+            return Err(RustADBError::RootRequired);
+        }
+
+        let output = self.exec_command(&device, format!("date -u @{}", datetime.timestamp()))?;
+
+        // Clock keeps running, allows a small drift
+        let device_time = self.get_time(&device)?;
+        if (device_time - datetime).num_seconds().abs() > 5 {
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Sets the time zone of the device (e.g. `Europe/Paris`).
+    pub fn set_timezone(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        timezone: &str,
+    ) -> Result<()> {
+        let device = device.into();
+        let output = self.exec_command(
+            &device,
+            format!("setprop persist.sys.timezone {}", shell_quote(timezone)),
+        )?;
+
+        let current = self.exec_command(&device, "getprop persist.sys.timezone")?;
+        if current.trim_ascii() != timezone.as_bytes() {
+            return Err(RustADBError::ShellCommandFailed(
+                String::from_utf8_lossy(&output).trim().to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
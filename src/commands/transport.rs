@@ -1,9 +1,120 @@
-use crate::{models::AdbCommand, AdbTcpConnexion, Result};
+use crate::{
+    models::AdbCommand, AdbTcpConnexion, DeviceSelector, DeviceState, Result, RustADBError,
+};
+
+const ANDROID_SERIAL: &str = "ANDROID_SERIAL";
 
 impl AdbTcpConnexion {
     /// Asks ADB server to switch the connection to either the device or emulator connect to/running on the host. Will fail if there is more than one such device/emulator available.
     pub fn transport_any(&mut self) -> Result<()> {
+        self.new_connection()?;
         self.proxy_connexion(AdbCommand::TransportAny, false)
             .map(|_| ())
     }
+
+    /// Gets the serial of the device matching `device`, e.g. to tell emulators (`emulator-<port>`) apart.
+    pub fn get_serial_no(&mut self, device: impl Into<DeviceSelector>) -> Result<String> {
+        let device = device.into();
+        let host_prefix = self.host_prefix(&device)?;
+        self.new_connection()?;
+
+        let serial = self.proxy_connexion(AdbCommand::GetSerialNo(host_prefix), true)?;
+        Ok(String::from_utf8(serial)?)
+    }
+
+    /// Gets the connection state of the device matching `device`.
+    ///
+    /// Unauthorized and offline devices are reported as such, rather than failing.
+    pub fn get_state(&mut self, device: impl Into<DeviceSelector>) -> Result<DeviceState> {
+        let device = device.into();
+        let host_prefix = self.host_prefix(&device)?;
+        self.new_connection()?;
+
+        match self.proxy_connexion(AdbCommand::GetState(host_prefix), true) {
+            Ok(state) => String::from_utf8(state)?.trim().parse(),
+            Err(RustADBError::DeviceUnauthorized) => Ok(DeviceState::Unauthorized),
+            Err(RustADBError::DeviceOffline) => Ok(DeviceState::Offline),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Opens a new connection to ADB server, and switches it to the device matching `device`.
+    ///
+    /// As with adb tools, [DeviceSelector::Any] stands for the default device when one is configured,
+    /// or for the device whose serial is given by `ANDROID_SERIAL` environment variable.
+    pub(crate) fn connect_device(&mut self, device: &DeviceSelector) -> Result<()> {
+        let command = match self.resolve_selector(device)? {
+            DeviceSelector::Serial(serial) => AdbCommand::TransportSerial(serial),
+            DeviceSelector::Usb => AdbCommand::TransportUSB,
+            DeviceSelector::Local => AdbCommand::TransportLocal,
+            DeviceSelector::TransportId(transport_id) => AdbCommand::TransportId(transport_id),
+            _ => AdbCommand::TransportAny,
+        };
+
+        self.new_connection()?;
+        self.send_adb_request(command)
+    }
+
+    /// Prefix of host services targeting the device matching `device` (e.g. `host-serial:<serial>` in `host-serial:<serial>:features`).
+    ///
+    /// Host services cannot be sent once the connection is switched to a device, they select it themselves instead.
+    pub(crate) fn host_prefix(&mut self, device: &DeviceSelector) -> Result<String> {
+        Ok(match self.resolve_selector(device)? {
+            DeviceSelector::Serial(serial) => format!("host-serial:{serial}"),
+            DeviceSelector::Usb => "host-usb".to_string(),
+            DeviceSelector::Local => "host-local".to_string(),
+            DeviceSelector::TransportId(transport_id) => {
+                format!("host-transport-id:{transport_id}")
+            }
+            _ => "host".to_string(),
+        })
+    }
+
+    /// Applies the default device to [DeviceSelector::Any], and resolves product and model, which ADB server does not understand, to a serial.
+    pub(crate) fn resolve_selector(&mut self, device: &DeviceSelector) -> Result<DeviceSelector> {
+        let device = match device {
+            DeviceSelector::Any => self.default_device(),
+            device => device.clone(),
+        };
+
+        match device {
+            DeviceSelector::Product(_) | DeviceSelector::Model(_) => {
+                Ok(DeviceSelector::Serial(self.resolve_device(&device)?))
+            }
+            device => Ok(device),
+        }
+    }
+
+    /// Device targeted when no specific device is requested.
+    fn default_device(&self) -> DeviceSelector {
+        match &self.default_device {
+            Some(device) => device.clone(),
+            None => match std::env::var(ANDROID_SERIAL) {
+                Ok(serial) if !serial.is_empty() => DeviceSelector::Serial(serial),
+                _ => DeviceSelector::Any,
+            },
+        }
+    }
+
+    /// Finds the serial of the only device whose product or model matches `device`.
+    fn resolve_device(&mut self, device: &DeviceSelector) -> Result<String> {
+        let mut matching = self
+            .devices_long()?
+            .into_iter()
+            .filter(|d| match device {
+                DeviceSelector::Product(product) => d.product == *product,
+                DeviceSelector::Model(model) => d.model == *model,
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+
+        match matching.len() {
+            0 => Err(RustADBError::DeviceNotFound(device.to_string())),
+            1 => Ok(matching.remove(0).identifier),
+            _ => Err(RustADBError::MultipleDevices {
+                selector: device.to_string(),
+                devices: matching,
+            }),
+        }
+    }
 }
@@ -0,0 +1,69 @@
+use std::net::Ipv4Addr;
+
+use crate::{
+    adb_tunnel::NestedForward, models::Endpoint, utils::shell_quote, AdbServerHandle,
+    AdbTcpConnexion, AdbTunnel, DeviceSelector, Result,
+};
+
+impl AdbTcpConnexion {
+    /// Connects to an ADB server listening on TCP `port` of the device (e.g. one a lab host device runs for devices tethered to it),
+    /// by forwarding a free local port to it.
+    ///
+    /// Returned connexion can be nested again to reach devices further away. The forwarding is removed once it is dropped.
+    pub fn nested_connexion(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        port: u16,
+    ) -> Result<AdbTcpConnexion> {
+        // Removing the forwarding later must not depend on the default device
+        let device = self.resolve_selector(&device.into())?;
+        let local_port = self.forward_free_port(&device, Endpoint::Tcp(port))?;
+        let forward = NestedForward {
+            server: AdbServerHandle::from(&*self),
+            device,
+            local: Endpoint::Tcp(local_port),
+        };
+
+        let mut connexion = AdbTcpConnexion::new(Ipv4Addr::LOCALHOST, local_port)?;
+        connexion.nested_forward = Some(forward);
+        Ok(connexion)
+    }
+
+    /// Tunnels a free local TCP port to `host:port` as reached from the device, by running `nc` on it for each connection.
+    ///
+    /// Allows reaching addresses only accessible from the device's network (e.g. the ADB server of a host it is tethered to),
+    /// which [AdbTcpConnexion::forward] cannot. Fails with [crate::RustADBError::UnsupportedOnDevice] if `nc` is not available.
+    pub fn tunnel(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        host: &str,
+        port: u16,
+    ) -> Result<AdbTunnel> {
+        let device = device.into();
+        self.require_command(&device, "nc")?;
+        // Connexions opened for each connection do not know the default device
+        let device = self.resolve_selector(&device)?;
+
+        AdbTunnel::new(
+            AdbServerHandle::from(&*self),
+            device,
+            format!("exec:nc {} {port}", shell_quote(host)),
+        )
+    }
+
+    /// Listens on a free local TCP port, and connects each accepted connection to `remote` on the device over a new device socket.
+    ///
+    /// Unlike [AdbTcpConnexion::forward], no forwarding is registered on ADB server: it ends when the returned tunnel is dropped,
+    /// and is not listed by [AdbTcpConnexion::list_forward].
+    pub fn forward_to_listener(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        remote: Endpoint,
+    ) -> Result<AdbTunnel> {
+        let device = device.into();
+        // Connexions opened for each connection do not know the default device
+        let device = self.resolve_selector(&device)?;
+
+        AdbTunnel::new(AdbServerHandle::from(&*self), device, remote.to_string())
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{parser, AdbTcpConnexion, DeviceSelector, Result, RustADBError, UiNode};
+
+/// Path of UI dumps on the device when they cannot be written to the output, removed once pulled.
+const UI_DUMP_PATH: &str = "/data/local/tmp/adb_client_ui.xml";
+
+impl AdbTcpConnexion {
+    /// Dumps the UI hierarchy currently displayed on the device with `uiautomator dump`.
+    ///
+    /// Returned root node is the `hierarchy` element, whose children are the root views of the windows.
+    /// The dump is read from the output of the command, or pulled from a temporary file on devices
+    /// where it cannot be written to `/dev/tty`.
+    pub fn ui_dump(&mut self, device: impl Into<DeviceSelector>) -> Result<UiNode> {
+        let device = device.into();
+        let output = String::from_utf8(self.exec_command(&device, "uiautomator dump /dev/tty")?)?;
+
+        // XML is followed by "UI hierchary dumped to: /dev/tty"
+        if let Some(end) = output.rfind("</hierarchy>") {
+            let start = output.find('<').unwrap_or(end);
+            return parser::ui_hierarchy(&output[start..end + "</hierarchy>".len()]);
+        }
+
+        let output = String::from_utf8(
+            self.exec_command(&device, format!("uiautomator dump {UI_DUMP_PATH} 2>&1"))?,
+        )?;
+        if !output.contains("dumped to") {
+            return Err(RustADBError::ShellCommandFailed(output.trim().to_string()));
+        }
+
+        let mut xml = vec![];
+        let result = self.recv(&device, UI_DUMP_PATH, &mut xml);
+        self.exec_command(&device, format!("rm -f {UI_DUMP_PATH}"))?;
+        result?;
+
+        parser::ui_hierarchy(&String::from_utf8(xml)?)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    utils::shell_quote, AdbTcpConnexion, DeviceSelector, Result, RustADBError, UserId, UserInfo,
+};
+
+impl AdbTcpConnexion {
+    /// Gets the user currently in the foreground of the device.
+    ///
+    /// Devices without multi-user support, whose `am` cannot tell, only have [UserId::SYSTEM].
+    pub fn current_user(&mut self, device: impl Into<DeviceSelector>) -> Result<UserId> {
+        let device = device.into();
+        let output = String::from_utf8(self.exec_command(&device, "am get-current-user 2>&1")?)?;
+
+        Ok(output.parse().unwrap_or(UserId::SYSTEM))
+    }
+
+    /// `--user` option targeting the user configured by [AdbTcpConnexion::set_user], followed by a space, or nothing if none is.
+    pub(crate) fn user_option(&self) -> String {
+        match self.user {
+            Some(user) => format!("--user {user} "),
+            None => String::new(),
+        }
+    }
+
+    /// Lists users and profiles of the device.
+    pub fn list_users(&mut self, device: impl Into<DeviceSelector>) -> Result<Vec<UserInfo>> {
+        let device = device.into();
+        let output = String::from_utf8(self.exec_command(&device, "pm list users")?)?;
+
+        output
+            .lines()
+            .filter(|line| line.trim_start().starts_with("UserInfo{"))
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Creates a new user named `name` and returns its id.
+    pub fn create_user(&mut self, device: impl Into<DeviceSelector>, name: &str) -> Result<UserId> {
+        let device = device.into();
+        self.create_user_with(&device, "", name)
+    }
+
+    /// Creates a work profile named `name` for user `parent` and returns its id.
+    ///
+    /// The profile still has to be provisioned by a device policy controller to be fully usable.
+    pub fn create_work_profile(
+        &mut self,
+        device: impl Into<DeviceSelector>,
+        parent: UserId,
+        name: &str,
+    ) -> Result<UserId> {
+        let device = device.into();
+        self.create_user_with(&device, &format!("--profileOf {parent} --managed "), name)
+    }
+
+    fn create_user_with(
+        &mut self,
+        device: &DeviceSelector,
+        options: &str,
+        name: &str,
+    ) -> Result<UserId> {
+        let output = self.exec_command(
+            device,
+            format!("pm create-user {options}{}", shell_quote(name)),
+        )?;
+        let output = String::from_utf8(output)?;
+
+        // Success: created user id 10
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Success: created user id "))
+            .ok_or_else(|| RustADBError::ShellCommandFailed(output.trim().to_string()))?
+            .parse()
+    }
+
+    /// Removes user `user` and all its data from the device.
+    pub fn remove_user(&mut self, device: impl Into<DeviceSelector>, user: UserId) -> Result<()> {
+        let device = device.into();
+        let output =
+            String::from_utf8(self.exec_command(&device, format!("pm remove-user {user}"))?)?;
+
+        if output.contains("Success") {
+            Ok(())
+        } else {
+            Err(RustADBError::ShellCommandFailed(output.trim().to_string()))
+        }
+    }
+
+    /// Switches the foreground user of the device to `user`.
+    pub fn switch_user(&mut self, device: impl Into<DeviceSelector>, user: UserId) -> Result<()> {
+        let device = device.into();
+        let output =
+            String::from_utf8(self.exec_command(&device, format!("am switch-user {user}"))?)?;
+
+        // Nothing is printed on success
+        match output.trim() {
+            "" => Ok(()),
+            error => Err(RustADBError::ShellCommandFailed(error.to_string())),
+        }
+    }
+}
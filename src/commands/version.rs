@@ -2,9 +2,21 @@ use crate::{models::AdbCommand, AdbTcpConnexion, AdbVersion, Result};
 
 impl AdbTcpConnexion {
     /// Gets server's internal version number.
+    ///
+    /// Not subject to the minimum server version of the connexion, so that an outdated server can be reported.
     pub fn version(&mut self) -> Result<AdbVersion> {
-        let version = self.proxy_connexion(AdbCommand::Version, true)?;
+        self.open_connection()?;
+        let version = AdbVersion::try_from(self.proxy_connexion(AdbCommand::Version, true)?)?;
+        self.server_version = Some(version);
 
-        AdbVersion::try_from(version)
+        Ok(version)
+    }
+
+    /// Gets server's internal version number, read once for the lifetime of the connexion.
+    pub fn server_version(&mut self) -> Result<AdbVersion> {
+        match self.server_version {
+            Some(version) => Ok(version),
+            None => self.version(),
+        }
     }
 }
@@ -0,0 +1,87 @@
+use std::{
+    net::SocketAddrV4,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    models::{AdbCommand, MdnsService, WifiPairing},
+    AdbTcpConnexion, Result, RustADBError,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl AdbTcpConnexion {
+    /// Lists ADB services advertised over mDNS by devices of the local network, as discovered by ADB server.
+    pub fn mdns_services(&mut self) -> Result<Vec<MdnsService>> {
+        self.new_connection()?;
+        let services = self.proxy_connexion(AdbCommand::MdnsServices, true)?;
+
+        String::from_utf8(services)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Pairs ADB server with the device waiting to be paired on `address`, using the pairing `code` it displays.
+    ///
+    /// Returns the message of ADB server, which includes the GUID of the device.
+    pub fn pair(&mut self, address: SocketAddrV4, code: &str) -> Result<String> {
+        self.new_connection()?;
+        let response = self.proxy_connexion(
+            AdbCommand::Pair {
+                code: code.to_string(),
+                address: address.to_string(),
+            },
+            true,
+        )?;
+
+        let message = String::from_utf8(response)?.trim().to_string();
+        match message.starts_with("Successfully paired") {
+            true => Ok(message),
+            false => Err(RustADBError::WirelessDebuggingFailed(message)),
+        }
+    }
+
+    /// Waits for a device to scan the QR code of `pairing` for at most `timeout`, and pairs with it.
+    ///
+    /// Returns the pairing service of the device. ADB server connects to paired devices it discovers on its own,
+    /// [AdbTcpConnexion::connect] can be used with their `_adb-tls-connect._tcp` service otherwise.
+    pub fn pair_with_qr(
+        &mut self,
+        pairing: &WifiPairing,
+        timeout: Duration,
+    ) -> Result<MdnsService> {
+        let started = Instant::now();
+        loop {
+            if let Some(service) = self
+                .mdns_services()?
+                .into_iter()
+                .find(|service| service.is_pairing() && service.instance == pairing.service_name)
+            {
+                self.pair(service.address, &pairing.password)?;
+                return Ok(service);
+            }
+
+            if started.elapsed() >= timeout {
+                return Err(RustADBError::Timeout(format!(
+                    "no device scanned the QR code of {} after {timeout:?}",
+                    pairing.service_name
+                )));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Asks ADB server to connect to the device accepting connections on `address` (over TCP/IP or wireless debugging).
+    pub fn connect(&mut self, address: SocketAddrV4) -> Result<()> {
+        self.new_connection()?;
+        let response = self.proxy_connexion(AdbCommand::Connect(address.to_string()), true)?;
+
+        let message = String::from_utf8(response)?.trim().to_string();
+        match message.starts_with("connected to") || message.starts_with("already connected to") {
+            true => Ok(()),
+            false => Err(RustADBError::WirelessDebuggingFailed(message)),
+        }
+    }
+}
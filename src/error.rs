@@ -1,5 +1,9 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+use crate::{adb_stream::PeerClosed, protocol::codec::DecodeError, AdbVersion, DeviceLong};
+
 /// Custom Result type thrown by this crate.
 pub type Result<T> = std::result::Result<T, RustADBError>;
 
@@ -8,7 +12,7 @@ pub type Result<T> = std::result::Result<T, RustADBError>;
 pub enum RustADBError {
     /// Indicates that an error occured with I/O.
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
+    IOError(std::io::Error),
     /// Indicates that an error occured when sending ADB request.
     #[error("ADB request failed - {0}")]
     ADBRequestFailed(String),
@@ -42,4 +46,217 @@ pub enum RustADBError {
     /// Remote ADB server does not support shell feature.
     #[error("Remote ADB server does not support shell feature")]
     ADBShellNotSupported,
+    /// Indicates that an APK signature file (`.idsig`) could not be parsed.
+    #[error("Invalid APK signature file")]
+    InvalidSignatureFile,
+    /// Indicates that an OBB file does not follow the `[main|patch].<version code>.<package>.obb` naming convention.
+    #[error("Invalid OBB file name {0}")]
+    InvalidObbName(String),
+    /// Indicates that a command could not be run as an application user.
+    #[error("run-as failed - {0}")]
+    RunAsFailed(String),
+    /// Indicates that root privileges are required on the device.
+    #[error("Root privileges are required on the device")]
+    RootRequired,
+    /// Indicates that a command run on the device did not have the expected effect.
+    #[error("Shell command failed - {0}")]
+    ShellCommandFailed(String),
+    /// Indicates that the ADB server could not be started.
+    #[error("Could not start ADB server - {0}")]
+    ADBServerStartFailed(String),
+    /// Indicates that pairing with, or connecting to, a device over Wi-Fi failed.
+    #[error("Wireless debugging failed - {0}")]
+    WirelessDebuggingFailed(String),
+    /// Indicates that the SSH tunnel to a remote ADB server could not be opened.
+    #[error("Could not open SSH tunnel - {0}")]
+    SshTunnelFailed(String),
+    /// Indicates that no device matches the given selector.
+    #[error("No device matching {0}")]
+    DeviceNotFound(String),
+    /// Indicates that several devices match the given selector.
+    #[error("More than one device matching {0}")]
+    AmbiguousDeviceSelector(String),
+    /// Indicates that no specific device has been selected while several are connected, e.g. for users to pick one of `devices`.
+    #[error("More than one device matching {selector}: {}", identifiers(devices))]
+    MultipleDevices {
+        /// Selector matching several devices.
+        selector: String,
+        /// Devices matching the selector.
+        devices: Vec<DeviceLong>,
+    },
+    /// Indicates that a recorded session file is invalid, or has no more recorded connection to replay.
+    #[error("Recorded session error: {0}")]
+    RecordedSessionError(String),
+    /// Indicates that ADB server or the device does not support a feature required by the command.
+    #[error("Feature {0} is not supported by ADB server or device")]
+    UnsupportedByServer(String),
+    /// Indicates that the package manager refused to install an APK.
+    #[error("Installation failed - {0}")]
+    InstallFailed(String),
+    /// Indicates that an APK file could not be parsed.
+    #[error("APK parsing error - {0}")]
+    ApkParsingError(String),
+    /// Indicates an error while reading an APK archive.
+    #[cfg(feature = "apk")]
+    #[error(transparent)]
+    ZipError(#[from] zip::result::ZipError),
+    /// Indicates that a fastboot command failed, with the reason given by the device.
+    #[cfg(feature = "fastboot")]
+    #[error("Fastboot command failed: {0}")]
+    FastbootFailed(String),
+    /// Indicates an error while generating or using an RSA key.
+    #[error(transparent)]
+    RsaError(#[from] rsa::Error),
+    /// Indicates that an ADB key file could not be parsed.
+    #[error("Invalid ADB key - {0}")]
+    InvalidAdbKey(String),
+    /// Indicates that a request cannot be sent in the current state of the connection (e.g. a device service before selecting a device).
+    #[error("Invalid connexion state - {0}")]
+    InvalidConnexionState(String),
+    /// Indicates that the ADB server or the device closed the connection while a response was expected.
+    #[error("Connection closed by peer")]
+    ConnectionClosedByPeer,
+    /// Indicates that the device does not have enough free space for a transfer or an installation.
+    #[error("Insufficient space on device: {needed} bytes needed, {available} bytes available")]
+    InsufficientSpace {
+        /// Space needed, in bytes.
+        needed: u64,
+        /// Space available on the destination filesystem, in bytes.
+        available: u64,
+    },
+    /// Indicates that zstd compressed data received from the device could not be decoded.
+    #[cfg(feature = "zstd")]
+    #[error(transparent)]
+    ZstdDecodingError(#[from] ruzstd::decoding::errors::FrameDecoderError),
+    /// Indicates that the targeted device is offline.
+    #[error("Device offline")]
+    DeviceOffline,
+    /// Indicates that the targeted device has not authorized debugging from this host.
+    #[error("Device unauthorized, debugging must be allowed on the device")]
+    DeviceUnauthorized,
+    /// Indicates that a local file to transfer does not exist.
+    #[error("Local file not found: {0}")]
+    LocalFileNotFound(PathBuf),
+    /// Indicates that a timestamp cannot be represented in the protocol (e.g. before Unix epoch).
+    #[error("Invalid timestamp - {0}")]
+    InvalidTimestamp(String),
+    /// Indicates that a file is too large for the sync protocol supported by the device.
+    #[error("File too large - {0}")]
+    FileTooLarge(String),
+    /// Indicates that a binary required by the command is not available on the device.
+    #[error("Command {0} is not available on the device")]
+    UnsupportedOnDevice(String),
+    /// Indicates that a glob pattern of a transfer filter is malformed.
+    #[error("Invalid glob pattern: {0}")]
+    InvalidGlobPattern(String),
+    /// Indicates that the ADB server is older than the minimum version configured on the connexion.
+    #[error("ADB server version {actual} is older than required version {required}")]
+    IncompatibleServerVersion {
+        /// Minimum version configured on the connexion.
+        required: AdbVersion,
+        /// Version of the ADB server.
+        actual: AdbVersion,
+    },
+    /// Indicates that the device did not reach the awaited state in time.
+    #[error("Timed out waiting for {0}")]
+    Timeout(String),
+    /// Indicates that a shell command exited with a non-zero status.
+    #[error("Command exited with status {code}: {}", stderr.trim())]
+    CommandFailed {
+        /// Exit status of the command.
+        code: u8,
+        /// Standard output of the command.
+        stdout: String,
+        /// Standard error of the command, merged into `stdout` when the device does not support shell v2 protocol.
+        stderr: String,
+    },
+    /// Indicates that a command did not exit in time on the device, and has been killed.
+    #[error("Command {0} timed out and has been killed")]
+    RemoteCommandTimeout(String),
+    /// Indicates that a locale is not a valid language tag (e.g. `fr-FR`).
+    #[error("Invalid locale: {0}")]
+    InvalidLocale(String),
+    /// Indicates that the working directory of a shell command does not exist on the device.
+    #[error("Directory {0} does not exist on the device")]
+    RemoteDirectoryNotFound(String),
+    /// Indicates that the name of an environment variable of a shell command is not valid.
+    #[error("Invalid environment variable name: {0}")]
+    InvalidEnvironmentVariable(String),
+}
+
+impl RustADBError {
+    /// Turns a `FAIL` message of ADB server into the matching error, falling back to [RustADBError::ADBRequestFailed].
+    pub(crate) fn from_fail_message(message: String) -> Self {
+        match message.as_str() {
+            "no devices/emulators found" => RustADBError::DeviceNotFound("any device".to_string()),
+            "no devices found" => RustADBError::DeviceNotFound("usb device".to_string()),
+            "no emulators found" => RustADBError::DeviceNotFound("local device".to_string()),
+            "more than one device/emulator" => {
+                RustADBError::AmbiguousDeviceSelector("any device".to_string())
+            }
+            "more than one device" => {
+                RustADBError::AmbiguousDeviceSelector("usb device".to_string())
+            }
+            "more than one emulator" => {
+                RustADBError::AmbiguousDeviceSelector("local device".to_string())
+            }
+            // Both messages may be followed by details (e.g. how to fix ADB keys)
+            m if m.starts_with("device offline") => RustADBError::DeviceOffline,
+            m if m.starts_with("device unauthorized") => RustADBError::DeviceUnauthorized,
+            m => match m
+                .strip_prefix("device '")
+                .and_then(|rest| rest.strip_suffix("' not found"))
+            {
+                Some(serial) => RustADBError::DeviceNotFound(format!("serial {serial}")),
+                None => RustADBError::ADBRequestFailed(message),
+            },
+        }
+    }
+
+    /// Whether the sync service of the device has been denied access to a file, e.g. by SELinux.
+    pub(crate) fn is_permission_denied(&self) -> bool {
+        matches!(self, RustADBError::ADBRequestFailed(message) if message.contains("Permission denied"))
+    }
+
+    /// Whether the connection to ADB server has been lost, or stayed silent past its read timeout.
+    pub(crate) fn is_connection_lost(&self) -> bool {
+        match self {
+            RustADBError::ConnectionClosedByPeer => true,
+            RustADBError::IOError(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl From<std::io::Error> for RustADBError {
+    fn from(value: std::io::Error) -> Self {
+        // Reads on ADB connections flag end of stream, telling it apart from other unexpected EOFs (e.g. on local files)
+        match value.get_ref() {
+            Some(inner) if inner.is::<PeerClosed>() => RustADBError::ConnectionClosedByPeer,
+            _ => RustADBError::IOError(value),
+        }
+    }
+}
+
+impl From<DecodeError> for RustADBError {
+    fn from(value: DecodeError) -> Self {
+        RustADBError::UnknownResponseType(value.to_string())
+    }
+}
+
+/// Identifiers of `devices`, separated by commas.
+fn identifiers(devices: &[DeviceLong]) -> String {
+    devices
+        .iter()
+        .map(|device| device.identifier.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
@@ -0,0 +1,193 @@
+use std::{
+    fs::File,
+    io::Read,
+    net::{Ipv4Addr, SocketAddrV4},
+    path::Path,
+};
+
+use super::{
+    fastboot_transport::FastbootTransport, tcp_transport::TcpTransport, usb_transport::UsbTransport,
+};
+use crate::{Result, RustADBError};
+
+// Downloaded data is sent to the device in chunks of this size
+const DOWNLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Final response of the device to a fastboot command.
+enum FastbootResponse {
+    /// Command succeeded, with an optional message (e.g. the value of a variable).
+    Okay(String),
+    /// Device is ready to receive this number of bytes.
+    Data(u32),
+}
+
+/// Represents a connexion to a device in bootloader mode, speaking the fastboot protocol.
+#[derive(Debug)]
+pub struct FastbootConnexion {
+    transport: Box<dyn FastbootTransport>,
+}
+
+impl FastbootConnexion {
+    /// Connects to a device running fastboot over TCP, usually on port 5554.
+    pub fn connect_tcp(address: Ipv4Addr, port: u16) -> Result<Self> {
+        Ok(Self {
+            transport: Box::new(TcpTransport::connect(SocketAddrV4::new(address, port))?),
+        })
+    }
+
+    /// Opens the device in bootloader mode connected over USB with the given serial, or the only one if `serial` is `None`.
+    pub fn open_usb(serial: Option<&str>) -> Result<Self> {
+        let mut devices = UsbTransport::list_devices()?
+            .into_iter()
+            .filter(|device| serial.is_none() || device.serial_number() == serial);
+
+        let device = match (devices.next(), devices.next()) {
+            (Some(device), None) => device,
+            (None, _) => {
+                return Err(RustADBError::DeviceNotFound(
+                    serial.unwrap_or("fastboot device").to_string(),
+                ))
+            }
+            (Some(_), Some(_)) => {
+                return Err(RustADBError::AmbiguousDeviceSelector(
+                    serial.unwrap_or("fastboot device").to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            transport: Box::new(UsbTransport::open(&device)?),
+        })
+    }
+
+    /// Lists serials of devices in bootloader mode connected over USB.
+    pub fn usb_devices() -> Result<Vec<String>> {
+        Ok(UsbTransport::list_devices()?
+            .iter()
+            .map(|device| device.serial_number().unwrap_or_default().to_string())
+            .collect())
+    }
+
+    /// Gets the value of bootloader variable `name` (e.g. `product`, `max-download-size`...).
+    pub fn getvar(&mut self, name: &str) -> Result<String> {
+        self.okay_command(&format!("getvar:{name}"))
+            .map(|(value, _)| value)
+    }
+
+    /// Flashes `size` bytes read from `input` to `partition`.
+    ///
+    /// Sparse images larger than the download size supported by the device are not supported.
+    pub fn flash(&mut self, partition: &str, input: &mut dyn Read, size: u32) -> Result<()> {
+        self.download(input, size)?;
+        self.okay_command(&format!("flash:{partition}")).map(|_| ())
+    }
+
+    /// Flashes the image located at `path` to `partition`.
+    pub fn flash_file<P: AsRef<Path>>(&mut self, partition: &str, path: P) -> Result<()> {
+        let mut image = File::open(path)?;
+        let size = image
+            .metadata()?
+            .len()
+            .try_into()
+            .map_err(|_| RustADBError::ConvertionError)?;
+
+        self.flash(partition, &mut image, size)
+    }
+
+    /// Erases `partition`.
+    pub fn erase(&mut self, partition: &str) -> Result<()> {
+        self.okay_command(&format!("erase:{partition}")).map(|_| ())
+    }
+
+    /// Runs OEM-specific `command`, and returns the lines it printed.
+    pub fn oem(&mut self, command: &str) -> Result<Vec<String>> {
+        self.okay_command(&format!("oem {command}"))
+            .map(|(_, info)| info)
+    }
+
+    /// Reboots the device to the system.
+    pub fn reboot(&mut self) -> Result<()> {
+        self.okay_command("reboot").map(|_| ())
+    }
+
+    /// Reboots the device to the bootloader.
+    pub fn reboot_bootloader(&mut self) -> Result<()> {
+        self.okay_command("reboot-bootloader").map(|_| ())
+    }
+
+    /// Sends `size` bytes read from `input` to the device, to be used by the next command (e.g. `flash`).
+    fn download(&mut self, input: &mut dyn Read, size: u32) -> Result<()> {
+        self.transport
+            .send(format!("download:{size:08x}").as_bytes())?;
+        match self.read_response(&mut vec![])? {
+            FastbootResponse::Data(accepted) if accepted == size => {}
+            _ => {
+                return Err(RustADBError::FastbootFailed(format!(
+                    "device did not accept downloading {size} bytes"
+                )))
+            }
+        }
+
+        let mut remaining = size as usize;
+        let mut chunk = vec![0_u8; DOWNLOAD_CHUNK_SIZE];
+        while remaining > 0 {
+            let chunk = &mut chunk[..remaining.min(DOWNLOAD_CHUNK_SIZE)];
+            input.read_exact(chunk)?;
+            self.transport.send(chunk)?;
+            remaining -= chunk.len();
+        }
+
+        match self.read_response(&mut vec![])? {
+            FastbootResponse::Okay(_) => Ok(()),
+            FastbootResponse::Data(_) => Err(RustADBError::FastbootFailed(
+                "unexpected DATA response".into(),
+            )),
+        }
+    }
+
+    /// Sends `command` and waits for it to succeed. Returns the final message and the lines printed meanwhile.
+    fn okay_command(&mut self, command: &str) -> Result<(String, Vec<String>)> {
+        self.transport.send(command.as_bytes())?;
+
+        let mut info = vec![];
+        match self.read_response(&mut info)? {
+            FastbootResponse::Okay(message) => Ok((message, info)),
+            FastbootResponse::Data(_) => Err(RustADBError::FastbootFailed(
+                "unexpected DATA response".into(),
+            )),
+        }
+    }
+
+    /// Reads responses until a final one, collecting informative lines into `info`.
+    fn read_response(&mut self, info: &mut Vec<String>) -> Result<FastbootResponse> {
+        loop {
+            let response = self.transport.recv()?;
+            if response.len() < 4 {
+                return Err(RustADBError::FastbootFailed(format!(
+                    "invalid response {}",
+                    response.escape_ascii()
+                )));
+            }
+
+            let (status, message) = response.split_at(4);
+            let message = String::from_utf8_lossy(message).to_string();
+            match status {
+                b"OKAY" => return Ok(FastbootResponse::Okay(message)),
+                b"FAIL" => return Err(RustADBError::FastbootFailed(message)),
+                b"DATA" => {
+                    return Ok(FastbootResponse::Data(
+                        u32::from_str_radix(&message, 16)
+                            .map_err(|_| RustADBError::ConvertionError)?,
+                    ))
+                }
+                b"INFO" | b"TEXT" => info.push(message),
+                _ => {
+                    return Err(RustADBError::FastbootFailed(format!(
+                        "invalid response {}",
+                        response.escape_ascii()
+                    )))
+                }
+            }
+        }
+    }
+}
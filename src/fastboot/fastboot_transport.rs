@@ -0,0 +1,12 @@
+use std::fmt::Debug;
+
+use crate::Result;
+
+/// Carries fastboot packets between host and device.
+pub(crate) trait FastbootTransport: Debug + Send {
+    /// Sends a packet (a command, or a chunk of downloaded data) to the device.
+    fn send(&mut self, data: &[u8]) -> Result<()>;
+
+    /// Receives the next response packet from the device.
+    fn recv(&mut self) -> Result<Vec<u8>>;
+}
@@ -0,0 +1,6 @@
+mod fastboot_connexion;
+mod fastboot_transport;
+mod tcp_transport;
+mod usb_transport;
+
+pub use fastboot_connexion::FastbootConnexion;
@@ -0,0 +1,62 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddrV4, TcpStream},
+};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use super::fastboot_transport::FastbootTransport;
+use crate::{Result, RustADBError};
+
+const HANDSHAKE: &[u8; 4] = b"FB01";
+
+/// Fastboot over TCP: after a handshake, every packet is prefixed by its length as a 64-bit big-endian integer.
+#[derive(Debug)]
+pub(crate) struct TcpTransport {
+    tcp_stream: TcpStream,
+}
+
+impl TcpTransport {
+    pub(crate) fn connect(socket_addr: SocketAddrV4) -> Result<Self> {
+        let mut tcp_stream = TcpStream::connect(socket_addr)?;
+        tcp_stream.write_all(HANDSHAKE)?;
+
+        // Device answers with "FB" followed by the protocol version it speaks
+        let mut handshake = [0_u8; 4];
+        tcp_stream.read_exact(&mut handshake)?;
+        if &handshake[..2] != b"FB" {
+            return Err(RustADBError::FastbootFailed(format!(
+                "invalid handshake {}",
+                handshake.escape_ascii()
+            )));
+        }
+
+        Ok(Self { tcp_stream })
+    }
+}
+
+impl FastbootTransport for TcpTransport {
+    fn send(&mut self, data: &[u8]) -> Result<()> {
+        let mut length = [0_u8; 8];
+        BigEndian::write_u64(&mut length, data.len() as u64);
+        self.tcp_stream.write_all(&length)?;
+        self.tcp_stream.write_all(data)?;
+
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        let mut length = [0_u8; 8];
+        self.tcp_stream.read_exact(&mut length)?;
+
+        let mut data = vec![
+            0;
+            BigEndian::read_u64(&length)
+                .try_into()
+                .map_err(|_| RustADBError::ConvertionError)?
+        ];
+        self.tcp_stream.read_exact(&mut data)?;
+
+        Ok(data)
+    }
+}
@@ -0,0 +1,107 @@
+use futures_lite::future::block_on;
+use nusb::{
+    transfer::{Direction, EndpointType, RequestBuffer},
+    DeviceInfo, Interface,
+};
+
+use super::fastboot_transport::FastbootTransport;
+use crate::{Result, RustADBError};
+
+// Fastboot interface is identified by its class, subclass and protocol
+const FASTBOOT_CLASS: u8 = 0xFF;
+const FASTBOOT_SUBCLASS: u8 = 0x42;
+const FASTBOOT_PROTOCOL: u8 = 0x03;
+// Responses are at most 256 bytes long, a larger buffer stays a multiple of any max packet size
+const RESPONSE_BUFFER_SIZE: usize = 4096;
+
+/// Fastboot over USB: packets are bulk transfers on the fastboot interface.
+pub(crate) struct UsbTransport {
+    interface: Interface,
+    endpoint_in: u8,
+    endpoint_out: u8,
+}
+
+impl UsbTransport {
+    /// Lists USB devices exposing a fastboot interface.
+    pub(crate) fn list_devices() -> Result<Vec<DeviceInfo>> {
+        Ok(nusb::list_devices()?
+            .filter(|device| fastboot_interface_number(device).is_some())
+            .collect())
+    }
+
+    pub(crate) fn open(device_info: &DeviceInfo) -> Result<Self> {
+        let interface_number = fastboot_interface_number(device_info).ok_or_else(|| {
+            RustADBError::FastbootFailed("device has no fastboot interface".into())
+        })?;
+
+        let device = device_info.open()?;
+        let interface = device.claim_interface(interface_number)?;
+
+        let mut endpoint_in = None;
+        let mut endpoint_out = None;
+        for alt_setting in interface.descriptors() {
+            for endpoint in alt_setting
+                .endpoints()
+                .filter(|endpoint| endpoint.transfer_type() == EndpointType::Bulk)
+            {
+                match endpoint.direction() {
+                    Direction::In => endpoint_in = endpoint_in.or(Some(endpoint.address())),
+                    Direction::Out => endpoint_out = endpoint_out.or(Some(endpoint.address())),
+                }
+            }
+        }
+
+        match (endpoint_in, endpoint_out) {
+            (Some(endpoint_in), Some(endpoint_out)) => Ok(Self {
+                interface,
+                endpoint_in,
+                endpoint_out,
+            }),
+            _ => Err(RustADBError::FastbootFailed(
+                "fastboot interface has no bulk endpoints".into(),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Debug for UsbTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsbTransport")
+            .field("interface", &self.interface.interface_number())
+            .field("endpoint_in", &self.endpoint_in)
+            .field("endpoint_out", &self.endpoint_out)
+            .finish()
+    }
+}
+
+impl FastbootTransport for UsbTransport {
+    fn send(&mut self, data: &[u8]) -> Result<()> {
+        block_on(self.interface.bulk_out(self.endpoint_out, data.to_vec()))
+            .into_result()
+            .map_err(std::io::Error::from)?;
+
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        let data = block_on(
+            self.interface
+                .bulk_in(self.endpoint_in, RequestBuffer::new(RESPONSE_BUFFER_SIZE)),
+        )
+        .into_result()
+        .map_err(std::io::Error::from)?;
+
+        Ok(data)
+    }
+}
+
+fn fastboot_interface_number(device_info: &DeviceInfo) -> Option<u8> {
+    device_info
+        .interfaces()
+        .find(|interface| {
+            interface.class() == FASTBOOT_CLASS
+                && interface.subclass() == FASTBOOT_SUBCLASS
+                && interface.protocol() == FASTBOOT_PROTOCOL
+        })
+        .map(|interface| interface.interface_number())
+}
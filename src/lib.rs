@@ -4,11 +4,75 @@
 #![forbid(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+// Protocol codec only relies on core and alloc
+extern crate alloc;
+
+mod adb_batch_transfer;
+mod adb_command_ext;
+mod adb_command_hook;
+mod adb_connection_pool;
+mod adb_emulator_console;
+mod adb_file_tail;
+mod adb_forward_manager;
+mod adb_keys;
+#[cfg(feature = "metrics")]
+mod adb_metrics;
+mod adb_rate_limiter;
+mod adb_reverse;
+mod adb_server_handle;
+mod adb_server_starter;
+mod adb_session_recorder;
+mod adb_shell_session;
+mod adb_ssh_tunnel;
+mod adb_stat_cache;
+mod adb_state_snapshot;
+mod adb_stream;
 mod adb_tcp_connexion;
+mod adb_temp_dir;
 mod adb_termios;
+mod adb_transport;
+mod adb_tunnel;
+#[cfg(feature = "adbd")]
+mod adbd;
 mod commands;
 mod error;
+#[cfg(feature = "fastboot")]
+mod fastboot;
 mod models;
-pub use adb_tcp_connexion::AdbTcpConnexion;
+mod parser;
+pub mod protocol;
+mod utils;
+pub use adb_batch_transfer::AdbBatchTransfer;
+pub use adb_command_ext::AdbCommandExt;
+pub use adb_command_hook::{AdbCommandHook, CommandEvent, HookAction, TransferDirection};
+pub use adb_emulator_console::AdbEmulatorConsole;
+pub use adb_file_tail::AdbFileTail;
+pub use adb_forward_manager::ForwardManager;
+pub use adb_keys::{generate_adb_keypair, list_key_fingerprints};
+#[cfg(feature = "metrics")]
+pub use adb_metrics::AdbMetrics;
+pub use adb_reverse::AdbReverse;
+pub use adb_server_handle::AdbServerHandle;
+pub use adb_shell_session::{AdbShellSession, AdbShellWriter};
+pub use adb_ssh_tunnel::{AdbSshTunnel, AdbSshTunnelBuilder};
+pub use adb_state_snapshot::AdbStateSnapshot;
+pub use adb_tcp_connexion::{AdbTcpConnexion, AdbTcpConnexionBuilder};
+pub use adb_temp_dir::AdbTempDir;
+pub use adb_transport::AdbTransportStream;
+pub use adb_tunnel::AdbTunnel;
+#[cfg(feature = "adbd")]
+pub use adbd::AdbdServer;
 pub use error::{Result, RustADBError};
-pub use models::{AdbVersion, Device, DeviceLong, DeviceState, RebootType};
+#[cfg(feature = "fastboot")]
+pub use fastboot::FastbootConnexion;
+#[cfg(feature = "apk")]
+pub use models::ApkManifest;
+pub use models::{
+    AdbKeyFingerprint, AdbStatResponse, AdbVersion, ApiLevel, AppDatabase, BatchEntry, ContentRow,
+    ContentUri, ContentValue, CrashArtifacts, DebugServer, DebugTarget, Device, DeviceEvent,
+    DeviceLong, DeviceSelector, DeviceState, DirEntry, DiskUsage, Endpoint, FilesystemUsage,
+    ForwardRule, InstallStrategy, MdnsService, MemInfo, NetworkStatus, PackageInfo, ProfileTarget,
+    RebootType, ReconnectTarget, ScreenRotation, SecurityState, SelinuxMode, SettingsNamespace,
+    SettingsStrategy, ShellEnvironment, ShellOutput, SimpleperfOptions, StateKey, TestPrepOptions,
+    TransferFilter, TransferReport, UiBounds, UiNode, UserId, UserInfo, VersionInfo, WifiPairing,
+};
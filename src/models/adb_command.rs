@@ -1,4 +1,6 @@
-use super::RebootType;
+use std::fmt::Display;
+
+use super::{ConnexionState, Endpoint, RebootType};
 
 pub enum AdbCommand {
     Version,
@@ -6,29 +8,44 @@ pub enum AdbCommand {
     Devices,
     DevicesLong,
     TrackDevices,
-    HostFeatures,
+    /// Features of the device selected by the given host prefix (e.g. `host-serial:<serial>`).
+    HostFeatures(String),
     // TODO: NOT IMPLEMENTED YET
     // Emulator(u16),
     // Transport(String),
-    // TransportUSB,
-    // TransportLocal,
+    TransportUSB,
+    TransportLocal,
     TransportAny,
     TransportSerial(String),
+    TransportId(u32),
     // Serial((String, String)),
     // USB(String),
     // Local(String),
     // Request(String),
     // GetProduct(String),
-    // GetSerialNo(String),
+    /// Serial of the device selected by the given host prefix.
+    GetSerialNo(String),
     // GetDevPath(String),
-    // GetState(String),
-    // Forward((String, String, String)),
-    // ForwardNoRebind((String, String, String)),
-    // KillForward((String, String)),
-    // KillForwardAll(String),
-    // ListForward(String),
+    /// Connection state of the device selected by the given host prefix.
+    GetState(String),
+    /// Forwarding of `local` to `remote` on the device selected by the given host prefix, failing if `local` is already forwarded when `norebind` is set.
+    Forward {
+        host_prefix: String,
+        local: Endpoint,
+        remote: Endpoint,
+        norebind: bool,
+    },
+    /// Removal of the forwarding of `local` to the device selected by the given host prefix.
+    KillForward {
+        host_prefix: String,
+        local: Endpoint,
+    },
+    KillForwardAll,
+    ListForward,
     ShellCommand(String),
     Shell,
+    ShellV2Interactive,
+    ShellV2Command(String),
     // Remount,
     // DevPath(String),
     // Tcp(u16),
@@ -41,33 +58,162 @@ pub enum AdbCommand {
     // JDWP(u32),
     // TrackJDWP,
     Sync,
-    // Reverse(String),
+    /// Reverse forwarding of `remote` on the device to `local` on the host, failing if `remote` is already forwarded when `norebind` is set.
+    Reverse {
+        remote: Endpoint,
+        local: Endpoint,
+        norebind: bool,
+    },
+    /// Removal of the reverse forwarding of `remote` on the device.
+    KillReverse(Endpoint),
+    Exec(String),
+    AbbExec(Vec<String>),
     Reboot(RebootType),
+    /// Reconnection of the device selected by the given host prefix, from ADB server side.
+    Reconnect(String),
+    ReconnectDevice,
+    ReconnectOffline,
+    DisconnectAll,
+    MdnsServices,
+    /// Pairing with the device listening on the given address, using the given code.
+    Pair {
+        code: String,
+        address: String,
+    },
+    Connect(String),
+    /// Host service not provided by this crate, including its host prefix when it targets a device.
+    HostService(String),
+    /// Device service not provided by this crate.
+    Service(String),
 }
 
-impl ToString for AdbCommand {
-    fn to_string(&self) -> String {
+impl Display for AdbCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            AdbCommand::Version => "host:version".into(),
-            AdbCommand::Kill => "host:kill".into(),
-            AdbCommand::Devices => "host:devices".into(),
-            AdbCommand::DevicesLong => "host:devices-l".into(),
-            AdbCommand::Sync => "sync:".into(),
-            AdbCommand::TrackDevices => "host:track-devices".into(),
-            AdbCommand::TransportAny => "host:transport-any".into(),
-            AdbCommand::TransportSerial(serial) => format!("host:transport:{serial}"),
+            AdbCommand::Version => write!(f, "host:version"),
+            AdbCommand::Kill => write!(f, "host:kill"),
+            AdbCommand::Devices => write!(f, "host:devices"),
+            AdbCommand::DevicesLong => write!(f, "host:devices-l"),
+            AdbCommand::Sync => write!(f, "sync:"),
+            AdbCommand::TrackDevices => write!(f, "host:track-devices"),
+            AdbCommand::TransportAny => write!(f, "host:transport-any"),
+            AdbCommand::TransportSerial(serial) => write!(f, "host:transport:{serial}"),
+            AdbCommand::TransportUSB => write!(f, "host:transport-usb"),
+            AdbCommand::TransportLocal => write!(f, "host:transport-local"),
+            AdbCommand::TransportId(transport_id) => {
+                write!(f, "host:transport-id:{transport_id}")
+            }
             AdbCommand::ShellCommand(command) => match std::env::var("TERM") {
-                Ok(term) => format!("shell,TERM={term},raw:{command}"),
-                Err(_) => format!("shell,raw:{command}"),
+                Ok(term) => write!(f, "shell,TERM={term},raw:{command}"),
+                Err(_) => write!(f, "shell,raw:{command}"),
             },
             AdbCommand::Shell => match std::env::var("TERM") {
-                Ok(term) => format!("shell,TERM={term},raw:"),
-                Err(_) => "shell,raw:".into(),
+                Ok(term) => write!(f, "shell,TERM={term},raw:"),
+                Err(_) => write!(f, "shell,raw:"),
             },
-            AdbCommand::HostFeatures => "host:features".into(),
+            AdbCommand::ShellV2Interactive => match std::env::var("TERM") {
+                Ok(term) => write!(f, "shell,v2,TERM={term},pty:"),
+                Err(_) => write!(f, "shell,v2,pty:"),
+            },
+            AdbCommand::ShellV2Command(command) => write!(f, "shell,v2,raw:{command}"),
+            AdbCommand::HostFeatures(host_prefix) => write!(f, "{host_prefix}:features"),
+            AdbCommand::GetSerialNo(host_prefix) => write!(f, "{host_prefix}:get-serialno"),
+            AdbCommand::GetState(host_prefix) => write!(f, "{host_prefix}:get-state"),
             AdbCommand::Reboot(reboot_type) => {
-                format!("reboot:{reboot_type}")
+                write!(f, "reboot:{reboot_type}")
+            }
+            AdbCommand::Exec(command) => write!(f, "exec:{command}"),
+            AdbCommand::AbbExec(args) => write!(f, "abb_exec:{}", args.join("\0")),
+            AdbCommand::Reconnect(host_prefix) => write!(f, "{host_prefix}:reconnect"),
+            AdbCommand::ReconnectDevice => write!(f, "reconnect"),
+            AdbCommand::ReconnectOffline => write!(f, "host:reconnect-offline"),
+            AdbCommand::DisconnectAll => write!(f, "host:disconnect:"),
+            AdbCommand::MdnsServices => write!(f, "host:mdns:services"),
+            AdbCommand::Pair { code, address } => write!(f, "host:pair:{code}:{address}"),
+            AdbCommand::Connect(address) => write!(f, "host:connect:{address}"),
+            AdbCommand::Forward {
+                host_prefix,
+                local,
+                remote,
+                norebind,
+            } => match norebind {
+                true => write!(f, "{host_prefix}:forward:norebind:{local};{remote}"),
+                false => write!(f, "{host_prefix}:forward:{local};{remote}"),
+            },
+            AdbCommand::KillForward { host_prefix, local } => {
+                write!(f, "{host_prefix}:killforward:{local}")
+            }
+            AdbCommand::Reverse {
+                remote,
+                local,
+                norebind,
+            } => match norebind {
+                true => write!(f, "reverse:forward:norebind:{remote};{local}"),
+                false => write!(f, "reverse:forward:{remote};{local}"),
+            },
+            AdbCommand::KillReverse(remote) => write!(f, "reverse:killforward:{remote}"),
+            AdbCommand::KillForwardAll => write!(f, "host:killforward-all"),
+            AdbCommand::ListForward => write!(f, "host:list-forward"),
+            AdbCommand::HostService(service) | AdbCommand::Service(service) => {
+                write!(f, "{service}")
             }
         }
     }
 }
+
+impl AdbCommand {
+    /// State the connection must be in for this command to be sent.
+    pub(crate) fn required_state(&self) -> ConnexionState {
+        match self {
+            AdbCommand::Version
+            | AdbCommand::Kill
+            | AdbCommand::Devices
+            | AdbCommand::DevicesLong
+            | AdbCommand::TrackDevices
+            | AdbCommand::HostFeatures(_)
+            | AdbCommand::GetSerialNo(_)
+            | AdbCommand::GetState(_)
+            | AdbCommand::Reconnect(_)
+            | AdbCommand::ReconnectOffline
+            | AdbCommand::DisconnectAll
+            | AdbCommand::MdnsServices
+            | AdbCommand::Pair { .. }
+            | AdbCommand::Connect(_)
+            | AdbCommand::Forward { .. }
+            | AdbCommand::KillForward { .. }
+            | AdbCommand::KillForwardAll
+            | AdbCommand::ListForward
+            | AdbCommand::HostService(_)
+            | AdbCommand::TransportUSB
+            | AdbCommand::TransportLocal
+            | AdbCommand::TransportAny
+            | AdbCommand::TransportSerial(_)
+            | AdbCommand::TransportId(_) => ConnexionState::Host,
+            AdbCommand::ShellCommand(_)
+            | AdbCommand::Shell
+            | AdbCommand::ShellV2Interactive
+            | AdbCommand::ShellV2Command(_)
+            | AdbCommand::Sync
+            | AdbCommand::Exec(_)
+            | AdbCommand::AbbExec(_)
+            | AdbCommand::Reboot(_)
+            | AdbCommand::ReconnectDevice
+            | AdbCommand::Reverse { .. }
+            | AdbCommand::KillReverse(_)
+            | AdbCommand::Service(_) => ConnexionState::Device,
+        }
+    }
+
+    /// State of the connection once this command has been accepted.
+    pub(crate) fn next_state(&self) -> ConnexionState {
+        match self {
+            AdbCommand::TransportUSB
+            | AdbCommand::TransportLocal
+            | AdbCommand::TransportAny
+            | AdbCommand::TransportSerial(_)
+            | AdbCommand::TransportId(_) => ConnexionState::Device,
+            AdbCommand::Sync => ConnexionState::Sync,
+            _ => ConnexionState::Stream,
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use std::{fmt::Display, path::PathBuf};
+
+/// Fingerprint of an ADB key, as displayed by devices when asking to allow USB debugging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdbKeyFingerprint {
+    /// Path of the private key file.
+    pub path: PathBuf,
+    /// MD5 digest of the public key, as colon-separated hexadecimal bytes.
+    pub fingerprint: String,
+}
+
+impl Display for AdbKeyFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.fingerprint, self.path.display())
+    }
+}
@@ -0,0 +1,35 @@
+use std::{
+    fmt::Display,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+
+/// Represents the metadata of a file on the device, as returned by sync `STAT` requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdbStatResponse {
+    /// File type and permissions, as in `st_mode`.
+    pub file_perm: u32,
+    /// File size in bytes. Truncated to 32 bits by devices supporting neither `stat_v2` nor `ls_v2`.
+    pub file_size: u64,
+    /// Last modification time, in seconds since Unix epoch.
+    pub mod_time: u64,
+}
+
+impl Display for AdbStatResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let d = UNIX_EPOCH + Duration::from_secs(self.mod_time);
+        // Create DateTime from SystemTime
+        let datetime = DateTime::<Utc>::from(d);
+
+        writeln!(f, "File permissions: {}", self.file_perm)?;
+        writeln!(f, "File size: {} bytes", self.file_size)?;
+        write!(
+            f,
+            "Modification time: {}",
+            datetime.format("%Y-%m-%d %H:%M:%S.%f %Z")
+        )?;
+        Ok(())
+    }
+}
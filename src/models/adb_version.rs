@@ -1,10 +1,12 @@
 use std::fmt::Display;
-use std::str;
 
-use crate::RustADBError;
+use crate::{parser, RustADBError};
 
 /// Represents the ADB server version.
-#[derive(Debug)]
+///
+/// Versions are ordered, e.g. to compare the version of a server with a minimum one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdbVersion {
     /// Major version number.
     pub major: u32,
@@ -35,9 +37,6 @@ impl TryFrom<Vec<u8>> for AdbVersion {
     type Error = RustADBError;
 
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        Ok(AdbVersion::new(
-            u32::from_str_radix(str::from_utf8(&value[0..2])?, 16)?,
-            u32::from_str_radix(str::from_utf8(&value[2..4])?, 16)?,
-        ))
+        parser::adb_version(&value)
     }
 }
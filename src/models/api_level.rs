@@ -0,0 +1,41 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::RustADBError;
+
+/// Represents the API level of a device, as reported by `ro.build.version.sdk` (e.g. 34 for Android 14).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApiLevel(pub u32);
+
+impl ApiLevel {
+    /// Android 4.2, first version with the `settings` command.
+    pub const JELLY_BEAN_MR1: ApiLevel = ApiLevel(17);
+    /// Android 5.0, first version whose `pm install` reads APKs from standard input.
+    pub const LOLLIPOP: ApiLevel = ApiLevel(21);
+    /// Android 6.0, first version with runtime permissions.
+    pub const MARSHMALLOW: ApiLevel = ApiLevel(23);
+    /// Android 7.0, first version with the `cmd` command.
+    pub const NOUGAT: ApiLevel = ApiLevel(24);
+    /// Android 9.
+    pub const PIE: ApiLevel = ApiLevel(28);
+    /// Android 10.
+    pub const Q: ApiLevel = ApiLevel(29);
+    /// Android 12, first version with location test providers in `cmd location`.
+    pub const S: ApiLevel = ApiLevel(31);
+    /// Android 13, first version with per-app languages in `cmd locale`.
+    pub const TIRAMISU: ApiLevel = ApiLevel(33);
+}
+
+impl Display for ApiLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for ApiLevel {
+    type Err = RustADBError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ApiLevel(s.trim().parse()?))
+    }
+}
@@ -0,0 +1,223 @@
+use std::{fs::File, io::Read, path::Path};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{Result, RustADBError};
+
+const RES_STRING_POOL_TYPE: u16 = 0x0001;
+const RES_XML_START_ELEMENT_TYPE: u16 = 0x0102;
+const RES_XML_RESOURCE_MAP_TYPE: u16 = 0x0180;
+const UTF8_FLAG: u32 = 1 << 8;
+
+// Android framework resource identifiers, used when attribute names have been stripped from the string pool.
+const ATTR_VERSION_CODE: u32 = 0x0101_021b;
+const ATTR_VERSION_NAME: u32 = 0x0101_021c;
+
+const TYPE_STRING: u8 = 0x03;
+const TYPE_FIRST_INT: u8 = 0x10;
+const TYPE_LAST_INT: u8 = 0x1f;
+
+/// Represents the informations held in the `AndroidManifest.xml` of an APK file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApkManifest {
+    /// Package name.
+    pub package: String,
+    /// Internal version number, used to compare versions.
+    pub version_code: u64,
+    /// Version name displayed to users.
+    pub version_name: Option<String>,
+}
+
+impl ApkManifest {
+    /// Reads the manifest of the APK file located at `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let mut manifest = archive.by_name("AndroidManifest.xml")?;
+
+        let mut content = Vec::new();
+        manifest.read_to_end(&mut content)?;
+
+        Self::try_from(content.as_slice())
+    }
+}
+
+impl TryFrom<&[u8]> for ApkManifest {
+    type Error = RustADBError;
+
+    /// Parses a binary (compiled) `AndroidManifest.xml`.
+    fn try_from(value: &[u8]) -> std::result::Result<Self, Self::Error> {
+        let mut strings: Vec<String> = vec![];
+        let mut resource_ids: Vec<u32> = vec![];
+
+        // Skips the XML file header
+        let mut offset = read_u16(value, 2)? as usize;
+        while offset < value.len() {
+            let chunk_type = read_u16(value, offset)?;
+            let header_size = read_u16(value, offset + 2)? as usize;
+            let chunk_size = read_u32(value, offset + 4)? as usize;
+            let chunk = value
+                .get(offset..offset + chunk_size)
+                .filter(|_| chunk_size >= 8)
+                .ok_or_else(|| RustADBError::ApkParsingError("truncated chunk".into()))?;
+
+            match chunk_type {
+                RES_STRING_POOL_TYPE => strings = parse_string_pool(chunk)?,
+                RES_XML_RESOURCE_MAP_TYPE => {
+                    resource_ids = chunk
+                        .get(header_size..)
+                        .ok_or_else(|| {
+                            RustADBError::ApkParsingError("truncated resource map".into())
+                        })?
+                        .chunks_exact(4)
+                        .map(LittleEndian::read_u32)
+                        .collect()
+                }
+                RES_XML_START_ELEMENT_TYPE => {
+                    // First element is always <manifest>
+                    return parse_manifest_element(chunk, header_size, &strings, &resource_ids);
+                }
+                _ => {}
+            }
+
+            offset += chunk_size;
+        }
+
+        Err(RustADBError::ApkParsingError(
+            "no manifest element found".into(),
+        ))
+    }
+}
+
+fn parse_manifest_element(
+    chunk: &[u8],
+    header_size: usize,
+    strings: &[String],
+    resource_ids: &[u32],
+) -> Result<ApkManifest> {
+    let attribute_start = header_size + read_u16(chunk, header_size + 8)? as usize;
+    let attribute_size = read_u16(chunk, header_size + 10)? as usize;
+    let attribute_count = read_u16(chunk, header_size + 12)? as usize;
+
+    let mut package = None;
+    let mut version_code = None;
+    let mut version_name = None;
+
+    for i in 0..attribute_count {
+        let attribute = attribute_start + i * attribute_size;
+        let name_index = read_u32(chunk, attribute + 4)? as usize;
+        let raw_value = read_u32(chunk, attribute + 8)?;
+        let data_type = *chunk
+            .get(attribute + 15)
+            .ok_or_else(|| RustADBError::ApkParsingError("truncated attribute".into()))?;
+        let data = read_u32(chunk, attribute + 16)?;
+
+        let name = strings.get(name_index).map(String::as_str).unwrap_or("");
+        let resource_id = resource_ids.get(name_index).copied();
+        let string_value = || {
+            let index = match data_type {
+                TYPE_STRING => data,
+                _ => raw_value,
+            };
+            strings.get(index as usize).cloned()
+        };
+
+        match (name, resource_id) {
+            ("package", _) => package = string_value(),
+            ("versionCode", _) | (_, Some(ATTR_VERSION_CODE)) => {
+                if (TYPE_FIRST_INT..=TYPE_LAST_INT).contains(&data_type) {
+                    version_code = Some(u64::from(data));
+                } else {
+                    version_code = string_value().and_then(|v| v.parse().ok());
+                }
+            }
+            ("versionName", _) | (_, Some(ATTR_VERSION_NAME)) => version_name = string_value(),
+            _ => {}
+        }
+    }
+
+    Ok(ApkManifest {
+        package: package
+            .ok_or_else(|| RustADBError::ApkParsingError("missing package attribute".into()))?,
+        version_code: version_code
+            .ok_or_else(|| RustADBError::ApkParsingError("missing versionCode attribute".into()))?,
+        version_name,
+    })
+}
+
+fn parse_string_pool(chunk: &[u8]) -> Result<Vec<String>> {
+    let string_count = read_u32(chunk, 8)? as usize;
+    let flags = read_u32(chunk, 16)?;
+    let strings_start = read_u32(chunk, 20)? as usize;
+    let header_size = read_u16(chunk, 2)? as usize;
+
+    // Each string has at least its offset in the chunk, checked before trusting the count
+    if string_count > chunk.len() / 4 {
+        return Err(RustADBError::ApkParsingError(
+            "truncated string pool".into(),
+        ));
+    }
+
+    let mut strings = Vec::with_capacity(string_count);
+    for i in 0..string_count {
+        let offset = strings_start + read_u32(chunk, header_size + i * 4)? as usize;
+        let string = if flags & UTF8_FLAG != 0 {
+            // UTF-16 length is encoded first, then UTF-8 length
+            let (_, offset) = read_utf8_length(chunk, offset)?;
+            let (length, offset) = read_utf8_length(chunk, offset)?;
+            String::from_utf8_lossy(
+                chunk
+                    .get(offset..offset + length)
+                    .ok_or_else(|| RustADBError::ApkParsingError("truncated string".into()))?,
+            )
+            .into_owned()
+        } else {
+            let (length, offset) = read_utf16_length(chunk, offset)?;
+            let units = (0..length)
+                .map(|j| read_u16(chunk, offset + j * 2))
+                .collect::<Result<Vec<u16>>>()?;
+            String::from_utf16_lossy(&units)
+        };
+        strings.push(string);
+    }
+
+    Ok(strings)
+}
+
+fn read_utf8_length(data: &[u8], offset: usize) -> Result<(usize, usize)> {
+    let first = *data
+        .get(offset)
+        .ok_or_else(|| RustADBError::ApkParsingError("truncated string length".into()))?
+        as usize;
+    if first & 0x80 != 0 {
+        let second = *data
+            .get(offset + 1)
+            .ok_or_else(|| RustADBError::ApkParsingError("truncated string length".into()))?
+            as usize;
+        Ok((((first & 0x7f) << 8) | second, offset + 2))
+    } else {
+        Ok((first, offset + 1))
+    }
+}
+
+fn read_utf16_length(data: &[u8], offset: usize) -> Result<(usize, usize)> {
+    let first = read_u16(data, offset)? as usize;
+    if first & 0x8000 != 0 {
+        let second = read_u16(data, offset + 2)? as usize;
+        Ok((((first & 0x7fff) << 16) | second, offset + 4))
+    } else {
+        Ok((first, offset + 2))
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(LittleEndian::read_u16)
+        .ok_or_else(|| RustADBError::ApkParsingError("unexpected end of data".into()))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(LittleEndian::read_u32)
+        .ok_or_else(|| RustADBError::ApkParsingError("unexpected end of data".into()))
+}
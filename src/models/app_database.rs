@@ -0,0 +1,40 @@
+use std::{fs, path::Path};
+
+use crate::Result;
+
+/// Represents a SQLite database pulled from an application data directory.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppDatabase {
+    /// Content of the main database file.
+    pub database: Vec<u8>,
+    /// Content of the write-ahead log (`-wal`) file, if any.
+    pub wal: Option<Vec<u8>>,
+    /// Content of the shared memory (`-shm`) file, if any.
+    pub shm: Option<Vec<u8>>,
+    /// Whether the write-ahead log has been checkpointed into the main database file before pulling it.
+    pub checkpointed: bool,
+}
+
+impl AppDatabase {
+    /// Writes the database to `path`, along with its `-wal` and `-shm` files if any, so that it can be opened with SQLite.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, &self.database)?;
+
+        for (suffix, content) in [("-wal", &self.wal), ("-shm", &self.shm)] {
+            let mut companion_path = path.as_os_str().to_owned();
+            companion_path.push(suffix);
+            match content {
+                Some(content) => fs::write(&companion_path, content)?,
+                // Stale companion files would corrupt the database when opened
+                None => match fs::remove_file(&companion_path) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)?,
+                    _ => {}
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
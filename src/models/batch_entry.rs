@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+use crate::{Result, TransferReport};
+
+/// Represents the outcome of one file of a batch transfer, as returned by [AdbTcpConnexion::push_many](crate::AdbTcpConnexion::push_many)
+/// and [AdbTcpConnexion::pull_many](crate::AdbTcpConnexion::pull_many).
+#[derive(Debug)]
+pub struct BatchEntry {
+    /// Path of the file on the host.
+    pub local: PathBuf,
+    /// Path of the file on the device.
+    pub remote: String,
+    /// Statistics of the transfer, or the reason it failed.
+    pub result: Result<TransferReport>,
+}
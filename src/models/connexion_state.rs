@@ -0,0 +1,25 @@
+use std::fmt::Display;
+
+/// State of a connection to the ADB server, deciding which requests can be sent on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConnexionState {
+    /// Freshly opened connection, accepting host services (`host:*`), including transport selection.
+    Host,
+    /// Connection switched to a device, accepting device services (`shell:`, `sync:`...).
+    Device,
+    /// Connection running the file sync protocol, accepting sync requests only.
+    Sync,
+    /// Connection carrying the response of a service, no more requests can be sent on it.
+    Stream,
+}
+
+impl Display for ConnexionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnexionState::Host => write!(f, "host mode"),
+            ConnexionState::Device => write!(f, "device mode"),
+            ConnexionState::Sync => write!(f, "sync mode"),
+            ConnexionState::Stream => write!(f, "a service stream"),
+        }
+    }
+}
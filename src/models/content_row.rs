@@ -0,0 +1,22 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use crate::{Result, RustADBError};
+
+/// Represents a row returned by a content provider query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentRow {
+    /// Values of the row by column name, `None` for null values.
+    pub values: BTreeMap<String, Option<String>>,
+}
+
+impl ContentRow {
+    /// Gets the value of `column` parsed as `T`, `None` if the column is null or not part of the row.
+    pub fn get<T: FromStr>(&self, column: &str) -> Result<Option<T>> {
+        self.values
+            .get(column)
+            .and_then(Option::as_deref)
+            .map(|value| value.parse().map_err(|_| RustADBError::ConvertionError))
+            .transpose()
+    }
+}
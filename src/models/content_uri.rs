@@ -0,0 +1,38 @@
+use std::fmt::Display;
+
+/// Represents the URI of data exposed by a content provider (e.g. `content://settings/global`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentUri {
+    /// Authority of the content provider (e.g. `settings`, `media`).
+    pub authority: String,
+    /// Path segments following the authority (e.g. `["global"]`).
+    pub segments: Vec<String>,
+}
+
+impl ContentUri {
+    /// Creates the URI of the root of the content provider registered with `authority`.
+    pub fn new<S: Into<String>>(authority: S) -> Self {
+        Self {
+            authority: authority.into(),
+            segments: vec![],
+        }
+    }
+
+    /// Appends `segment` to the path of the URI (e.g. an id to target a single row).
+    pub fn join<S: ToString>(mut self, segment: S) -> Self {
+        self.segments.push(segment.to_string());
+        self
+    }
+}
+
+impl Display for ContentUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "content://{}", self.authority)?;
+        for segment in &self.segments {
+            write!(f, "/{segment}")?;
+        }
+
+        Ok(())
+    }
+}
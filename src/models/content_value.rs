@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+/// Represents a typed value bound to a column by content provider insertions and updates.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContentValue {
+    /// Text value.
+    String(String),
+    /// 32-bit integer value.
+    Int(i32),
+    /// 64-bit integer value.
+    Long(i64),
+    /// Single precision floating point value.
+    Float(f32),
+    /// Double precision floating point value.
+    Double(f64),
+    /// Boolean value.
+    Bool(bool),
+    /// Null value.
+    Null,
+}
+
+/// Formats the value as expected by `--bind` argument of `content` command, its type followed by the value (e.g. `i:42`).
+impl Display for ContentValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentValue::String(value) => write!(f, "s:{value}"),
+            ContentValue::Int(value) => write!(f, "i:{value}"),
+            ContentValue::Long(value) => write!(f, "l:{value}"),
+            ContentValue::Float(value) => write!(f, "f:{value}"),
+            ContentValue::Double(value) => write!(f, "d:{value}"),
+            ContentValue::Bool(value) => write!(f, "b:{value}"),
+            ContentValue::Null => write!(f, "n:"),
+        }
+    }
+}
+
+impl From<&str> for ContentValue {
+    fn from(value: &str) -> Self {
+        ContentValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ContentValue {
+    fn from(value: String) -> Self {
+        ContentValue::String(value)
+    }
+}
+
+impl From<i32> for ContentValue {
+    fn from(value: i32) -> Self {
+        ContentValue::Int(value)
+    }
+}
+
+impl From<i64> for ContentValue {
+    fn from(value: i64) -> Self {
+        ContentValue::Long(value)
+    }
+}
+
+impl From<f32> for ContentValue {
+    fn from(value: f32) -> Self {
+        ContentValue::Float(value)
+    }
+}
+
+impl From<f64> for ContentValue {
+    fn from(value: f64) -> Self {
+        ContentValue::Double(value)
+    }
+}
+
+impl From<bool> for ContentValue {
+    fn from(value: bool) -> Self {
+        ContentValue::Bool(value)
+    }
+}
@@ -0,0 +1,33 @@
+use std::{fmt::Display, path::PathBuf};
+
+/// Local files gathered by `collect_crash_artifacts`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrashArtifacts {
+    /// Traces of ANRs (Application Not Responding).
+    pub anr_traces: Vec<PathBuf>,
+    /// Tombstones of native crashes.
+    pub tombstones: Vec<PathBuf>,
+    /// Crash buffer of logcat, `None` if it was empty.
+    pub crash_log: Option<PathBuf>,
+    /// Whether ANR traces or tombstones were read from DropBox copies, as their directories required root privileges.
+    pub from_dropbox: bool,
+}
+
+impl Display for CrashArtifacts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ANR traces, {} tombstones",
+            self.anr_traces.len(),
+            self.tombstones.len()
+        )?;
+        if self.crash_log.is_some() {
+            write!(f, ", crash log")?;
+        }
+        if self.from_dropbox {
+            write!(f, " (from DropBox)")?;
+        }
+        Ok(())
+    }
+}
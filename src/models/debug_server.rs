@@ -0,0 +1,20 @@
+use std::fmt::Display;
+
+/// Native debug server run on the device by [AdbTcpConnexion::start_debug_server](crate::AdbTcpConnexion::start_debug_server).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DebugServer {
+    /// `gdbserver`, to be used with `gdb`.
+    Gdbserver,
+    /// `lldb-server` in gdbserver mode, to be used with `lldb`.
+    LldbServer,
+}
+
+impl Display for DebugServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DebugServer::Gdbserver => write!(f, "gdbserver"),
+            DebugServer::LldbServer => write!(f, "lldb-server"),
+        }
+    }
+}
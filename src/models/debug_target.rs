@@ -0,0 +1,20 @@
+use std::fmt::Display;
+
+/// Process debugged by a [DebugServer](crate::DebugServer).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DebugTarget {
+    /// Running process with the given pid, to attach to.
+    Pid(u32),
+    /// Command line of a process to start, e.g. a binary path followed by its arguments.
+    Command(String),
+}
+
+impl Display for DebugTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DebugTarget::Pid(pid) => write!(f, "pid {pid}"),
+            DebugTarget::Command(command) => write!(f, "{command}"),
+        }
+    }
+}
@@ -1,11 +1,10 @@
-use std::{fmt::Display, str::FromStr};
+use std::fmt::Display;
 
-use regex::bytes::Regex;
-
-use crate::{DeviceState, RustADBError};
+use crate::{parser, DeviceState, RustADBError};
 
 /// Represents a device connected to the ADB server.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Device {
     /// Unique device identifier.
     pub identifier: String,
@@ -22,27 +21,7 @@ impl Display for Device {
 impl TryFrom<Vec<u8>> for Device {
     type Error = RustADBError;
 
-    // TODO: Prevent regex compilation every call to try_from()
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        // Optional final '\n' is used to match TrackDevices inputs
-        let parse_regex = Regex::new("^(\\w+)\t(\\w+)\n?$")?;
-
-        let groups = parse_regex.captures(&value).unwrap();
-        Ok(Device {
-            identifier: String::from_utf8(
-                groups
-                    .get(1)
-                    .ok_or(RustADBError::RegexParsingError)?
-                    .as_bytes()
-                    .to_vec(),
-            )?,
-            state: DeviceState::from_str(&String::from_utf8(
-                groups
-                    .get(2)
-                    .ok_or(RustADBError::RegexParsingError)?
-                    .as_bytes()
-                    .to_vec(),
-            )?)?,
-        })
+        parser::device(&value)
     }
 }
@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+use crate::DeviceState;
+
+/// Change in the devices connected to the ADB server, as reported by [track_device_events](crate::AdbTcpConnexion::track_device_events).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeviceEvent {
+    /// A device has been connected.
+    DeviceAdded {
+        /// Unique device identifier.
+        identifier: String,
+        /// Connection state of the device.
+        state: DeviceState,
+    },
+    /// A device has been disconnected.
+    DeviceRemoved {
+        /// Unique device identifier.
+        identifier: String,
+    },
+    /// Connection state of a device changed.
+    StateChanged {
+        /// Unique device identifier.
+        identifier: String,
+        /// Previous connection state.
+        from: DeviceState,
+        /// New connection state.
+        to: DeviceState,
+    },
+}
+
+impl DeviceEvent {
+    /// Whether a device showed up, or changed, waiting for debugging to be allowed on it (e.g. to ask the user to accept the RSA key prompt).
+    pub fn needs_authorization(&self) -> bool {
+        matches!(
+            self,
+            DeviceEvent::DeviceAdded {
+                state: DeviceState::Unauthorized,
+                ..
+            } | DeviceEvent::StateChanged {
+                to: DeviceState::Unauthorized,
+                ..
+            }
+        )
+    }
+}
+
+impl Display for DeviceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceEvent::DeviceAdded { identifier, state } => {
+                write!(f, "{identifier} added ({state})")
+            }
+            DeviceEvent::DeviceRemoved { identifier } => write!(f, "{identifier} removed"),
+            DeviceEvent::StateChanged {
+                identifier,
+                from,
+                to,
+            } => write!(f, "{identifier} {from} -> {to}"),
+        }
+    }
+}
@@ -1,12 +1,10 @@
-use std::str::FromStr;
-use std::{fmt::Display, str};
+use std::fmt::Display;
 
-use regex::bytes::Regex;
-
-use crate::{DeviceState, RustADBError};
+use crate::{parser, DeviceState, RustADBError};
 
 /// Represents a new device with more informations helded.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviceLong {
     /// Unique device identifier.
     pub identifier: String,
@@ -43,57 +41,7 @@ impl Display for DeviceLong {
 impl TryFrom<Vec<u8>> for DeviceLong {
     type Error = RustADBError;
 
-    // TODO: Prevent regex compilation every call to try_from()
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        let parse_regex = Regex::new("^(?P<identifier>\\w+)\\s+(?P<state>\\w+) usb:(?P<usb>.*) (product:(?P<product>\\w+) model:(?P<model>\\w+) device:(?P<device>\\w+) )?transport_id:(?P<transport_id>\\d+)$")?;
-
-        let groups = parse_regex
-            .captures(&value)
-            .ok_or(RustADBError::RegexParsingError)?;
-
-        Ok(DeviceLong {
-            identifier: String::from_utf8(
-                groups
-                    .name("identifier")
-                    .ok_or(RustADBError::RegexParsingError)?
-                    .as_bytes()
-                    .to_vec(),
-            )?,
-            state: DeviceState::from_str(&String::from_utf8(
-                groups
-                    .name("state")
-                    .ok_or(RustADBError::RegexParsingError)?
-                    .as_bytes()
-                    .to_vec(),
-            )?)?,
-            usb: String::from_utf8(
-                groups
-                    .name("usb")
-                    .ok_or(RustADBError::RegexParsingError)?
-                    .as_bytes()
-                    .to_vec(),
-            )?,
-            product: match groups.name("product") {
-                None => "Unk".to_string(),
-                Some(product) => String::from_utf8(product.as_bytes().to_vec())?,
-            },
-            model: match groups.name("model") {
-                None => "Unk".to_string(),
-                Some(model) => String::from_utf8(model.as_bytes().to_vec())?,
-            },
-            device: match groups.name("device") {
-                None => "Unk".to_string(),
-                Some(device) => String::from_utf8(device.as_bytes().to_vec())?,
-            },
-            transport_id: u32::from_str_radix(
-                str::from_utf8(
-                    groups
-                        .name("transport_id")
-                        .ok_or(RustADBError::RegexParsingError)?
-                        .as_bytes(),
-                )?,
-                16,
-            )?,
-        })
+        parser::device_long(&value)
     }
 }
@@ -0,0 +1,72 @@
+use std::fmt::Display;
+
+/// Selects the device targeted by a command, matching `adb` command-line options.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeviceSelector {
+    /// The only connected device or emulator. Fails if there are several of them.
+    #[default]
+    Any,
+    /// Device with the given serial (`adb -s`).
+    Serial(String),
+    /// The only device connected over USB (`adb -d`).
+    Usb,
+    /// The only device connected over TCP/IP, usually an emulator (`adb -e`).
+    Local,
+    /// Device with the given transport identifier (`adb -t`).
+    TransportId(u32),
+    /// The only device with the given product code, as listed by `devices_long`.
+    Product(String),
+    /// The only device with the given model, as listed by `devices_long`.
+    Model(String),
+}
+
+impl Display for DeviceSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceSelector::Any => write!(f, "any device"),
+            DeviceSelector::Serial(serial) => write!(f, "serial {serial}"),
+            DeviceSelector::Usb => write!(f, "usb device"),
+            DeviceSelector::Local => write!(f, "local device"),
+            DeviceSelector::TransportId(transport_id) => write!(f, "transport id {transport_id}"),
+            DeviceSelector::Product(product) => write!(f, "product {product}"),
+            DeviceSelector::Model(model) => write!(f, "model {model}"),
+        }
+    }
+}
+
+impl<S: ToString> From<Option<S>> for DeviceSelector {
+    fn from(value: Option<S>) -> Self {
+        match value {
+            None => DeviceSelector::Any,
+            Some(serial) => DeviceSelector::Serial(serial.to_string()),
+        }
+    }
+}
+
+impl<S: ToString> From<&Option<S>> for DeviceSelector {
+    fn from(value: &Option<S>) -> Self {
+        match value {
+            None => DeviceSelector::Any,
+            Some(serial) => DeviceSelector::Serial(serial.to_string()),
+        }
+    }
+}
+
+impl From<&str> for DeviceSelector {
+    fn from(value: &str) -> Self {
+        DeviceSelector::Serial(value.to_string())
+    }
+}
+
+impl From<String> for DeviceSelector {
+    fn from(value: String) -> Self {
+        DeviceSelector::Serial(value)
+    }
+}
+
+impl From<&DeviceSelector> for DeviceSelector {
+    fn from(value: &DeviceSelector) -> Self {
+        value.clone()
+    }
+}
@@ -3,7 +3,8 @@ use std::{fmt::Display, str::FromStr};
 use crate::RustADBError;
 
 /// Represents the connection state of the device.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DeviceState {
     /// The device is not connected to adb or is not responding.
     Offline,
@@ -15,6 +16,20 @@ pub enum DeviceState {
     Authorizing,
     /// The device is unauthorized.
     Unauthorized,
+    /// The device is in bootloader mode.
+    Bootloader,
+    /// The device is in recovery mode.
+    Recovery,
+    /// The device is in rescue mode.
+    Rescue,
+    /// The device is in recovery mode, waiting for `adb sideload`.
+    Sideload,
+    /// ADB server is still connecting to the device.
+    Connecting,
+    /// The remote end is a host rather than a device.
+    Host,
+    /// Any other state reported by ADB server, e.g. `no permissions` with an explanation.
+    Other(String),
 }
 
 impl Display for DeviceState {
@@ -25,6 +40,13 @@ impl Display for DeviceState {
             DeviceState::NoDevice => write!(f, "no device"),
             DeviceState::Authorizing => write!(f, "authorizing"),
             DeviceState::Unauthorized => write!(f, "unauthorized"),
+            DeviceState::Bootloader => write!(f, "bootloader"),
+            DeviceState::Recovery => write!(f, "recovery"),
+            DeviceState::Rescue => write!(f, "rescue"),
+            DeviceState::Sideload => write!(f, "sideload"),
+            DeviceState::Connecting => write!(f, "connecting"),
+            DeviceState::Host => write!(f, "host"),
+            DeviceState::Other(state) => write!(f, "{state}"),
         }
     }
 }
@@ -40,7 +62,14 @@ impl FromStr for DeviceState {
             "no device" => Ok(Self::NoDevice),
             "authorizing" => Ok(Self::Authorizing),
             "unauthorized" => Ok(Self::Unauthorized),
-            _ => Err(RustADBError::UnknownDeviceState(lowercased)),
+            "bootloader" => Ok(Self::Bootloader),
+            "recovery" => Ok(Self::Recovery),
+            "rescue" => Ok(Self::Rescue),
+            "sideload" => Ok(Self::Sideload),
+            "connecting" => Ok(Self::Connecting),
+            "host" => Ok(Self::Host),
+            // Unknown states are kept rather than failing, e.g. so that trackers keep going
+            _ => Ok(Self::Other(s.to_string())),
         }
     }
 }
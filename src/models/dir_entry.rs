@@ -0,0 +1,27 @@
+use std::fmt::Display;
+
+use super::AdbStatResponse;
+
+/// Represents an entry of a directory on the device, as returned by sync `LIST` requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirEntry {
+    /// Name of the entry, relative to the listed directory.
+    pub name: String,
+    /// Metadata of the entry.
+    pub metadata: AdbStatResponse,
+    /// Error number (`errno`) of the device if the metadata of the entry could not be read, in which case it is zeroed.
+    ///
+    /// Only reported by devices supporting `ls_v2`, others omit such entries or report them zeroed.
+    pub error: Option<u32>,
+}
+
+impl Display for DirEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:06o} {:>10} {:>10} {}",
+            self.metadata.file_perm, self.metadata.file_size, self.metadata.mod_time, self.name
+        )
+    }
+}
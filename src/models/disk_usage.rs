@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+use crate::FilesystemUsage;
+
+/// Represents the space used by a path on the device, and the usage of the filesystem it is stored on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiskUsage {
+    /// Path on the device.
+    pub path: String,
+    /// Space used by the path and its content, in bytes, as reported by `du -s`.
+    pub size: u64,
+    /// Usage of the filesystem containing the path.
+    pub filesystem: FilesystemUsage,
+}
+
+impl Display for DiskUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}: {} bytes", self.path, self.size)?;
+        write!(f, "{}", self.filesystem)
+    }
+}
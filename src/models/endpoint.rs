@@ -0,0 +1,56 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::RustADBError;
+
+/// Represents a socket endpoint of a port forwarding, on the host or on the device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endpoint {
+    /// TCP port, `0` letting ADB server choose a free port on the host.
+    Tcp(u16),
+    /// Unix domain socket in the abstract namespace.
+    LocalAbstract(String),
+    /// Unix domain socket in the reserved namespace (`/dev/socket`).
+    LocalReserved(String),
+    /// Unix domain socket in the filesystem.
+    LocalFilesystem(String),
+    /// Character device, on the device only.
+    Dev(String),
+    /// Java debugger of the process with the given pid, on the device only.
+    Jdwp(u32),
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Tcp(port) => write!(f, "tcp:{port}"),
+            Endpoint::LocalAbstract(name) => write!(f, "localabstract:{name}"),
+            Endpoint::LocalReserved(name) => write!(f, "localreserved:{name}"),
+            Endpoint::LocalFilesystem(path) => write!(f, "localfilesystem:{path}"),
+            Endpoint::Dev(path) => write!(f, "dev:{path}"),
+            Endpoint::Jdwp(pid) => write!(f, "jdwp:{pid}"),
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = RustADBError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(':').ok_or_else(|| {
+            RustADBError::UnknownResponseType(format!("Invalid forward endpoint {s}"))
+        })?;
+
+        match kind {
+            "tcp" => Ok(Endpoint::Tcp(value.parse()?)),
+            "localabstract" => Ok(Endpoint::LocalAbstract(value.to_string())),
+            "localreserved" => Ok(Endpoint::LocalReserved(value.to_string())),
+            "localfilesystem" => Ok(Endpoint::LocalFilesystem(value.to_string())),
+            "dev" => Ok(Endpoint::Dev(value.to_string())),
+            "jdwp" => Ok(Endpoint::Jdwp(value.parse()?)),
+            _ => Err(RustADBError::UnknownResponseType(format!(
+                "Invalid forward endpoint {s}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::RustADBError;
+
+/// Represents the space usage of a filesystem mounted on the device, as reported by `df`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilesystemUsage {
+    /// Device or name of the filesystem (e.g. `/dev/block/dm-5`, `tmpfs`).
+    pub filesystem: String,
+    /// Directory the filesystem is mounted on.
+    pub mount_point: String,
+    /// Total size of the filesystem, in bytes.
+    pub total: u64,
+    /// Space used on the filesystem, in bytes.
+    pub used: u64,
+    /// Space available to unprivileged users, in bytes.
+    pub free: u64,
+}
+
+impl Display for FilesystemUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} on {}: {} bytes used, {} bytes free, {} bytes total",
+            self.filesystem, self.mount_point, self.used, self.free, self.total
+        )
+    }
+}
+
+impl FromStr for FilesystemUsage {
+    type Err = RustADBError;
+
+    /// Parses a line of `df -k` output: filesystem, size, used and available 1K blocks, use percentage and mount point.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        let (filesystem, blocks, mount_point) = match fields.as_slice() {
+            [filesystem, total, used, free, _, mount_point @ ..] if !mount_point.is_empty() => {
+                (filesystem, [total, used, free], mount_point.join(" "))
+            }
+            _ => {
+                return Err(RustADBError::UnknownResponseType(format!(
+                    "Invalid df output line {s}"
+                )))
+            }
+        };
+
+        let [total, used, free] = blocks.map(|blocks| blocks.parse::<u64>().map(|v| v * 1024));
+
+        Ok(Self {
+            filesystem: filesystem.to_string(),
+            mount_point,
+            total: total?,
+            used: used?,
+            free: free?,
+        })
+    }
+}
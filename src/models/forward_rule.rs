@@ -0,0 +1,40 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::Endpoint;
+use crate::RustADBError;
+
+/// Represents a port forwarding set up by ADB server, from an endpoint on the host to an endpoint on a device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForwardRule {
+    /// Serial of the device.
+    pub serial: String,
+    /// Endpoint on the host.
+    pub local: Endpoint,
+    /// Endpoint on the device.
+    pub remote: Endpoint,
+}
+
+impl Display for ForwardRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.serial, self.local, self.remote)
+    }
+}
+
+impl FromStr for ForwardRule {
+    type Err = RustADBError;
+
+    /// Parses a line of `host:list-forward` response: serial, local and remote endpoints.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [serial, local, remote] => Ok(Self {
+                serial: serial.to_string(),
+                local: local.parse()?,
+                remote: remote.parse()?,
+            }),
+            _ => Err(RustADBError::UnknownResponseType(format!(
+                "Invalid forward rule {s}"
+            ))),
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HostFeatures {
     ShellV2,
     Cmd,
+    AbbExec,
+    LsV2,
+    StatV2,
 }
 
 impl Display for HostFeatures {
@@ -11,6 +14,9 @@ impl Display for HostFeatures {
         match self {
             HostFeatures::ShellV2 => write!(f, "ShellV2"),
             HostFeatures::Cmd => write!(f, "Cmd"),
+            HostFeatures::AbbExec => write!(f, "AbbExec"),
+            HostFeatures::LsV2 => write!(f, "LsV2"),
+            HostFeatures::StatV2 => write!(f, "StatV2"),
         }
     }
 }
@@ -22,6 +28,9 @@ impl TryFrom<&[u8]> for HostFeatures {
         match value {
             b"shell_v2" => Ok(Self::ShellV2),
             b"cmd" => Ok(Self::Cmd),
+            b"abb_exec" => Ok(Self::AbbExec),
+            b"ls_v2" => Ok(Self::LsV2),
+            b"stat_v2" => Ok(Self::StatV2),
             _ => Err(format!("Unknown value {value:?}")),
         }
     }
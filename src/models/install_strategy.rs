@@ -0,0 +1,26 @@
+use std::fmt::Display;
+
+/// Way APKs are handed to the package manager, depending on what the device supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InstallStrategy {
+    /// Streamed to the package service with the `abb_exec` service.
+    AbbExec,
+    /// Streamed to `cmd package install`, from Android 7.0.
+    CmdPackage,
+    /// Streamed to `pm install`, from Android 5.0.
+    PmStreamed,
+    /// Pushed to a temporary file installed with `pm install`, on older versions.
+    PmPushed,
+}
+
+impl Display for InstallStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallStrategy::AbbExec => write!(f, "abb_exec package install"),
+            InstallStrategy::CmdPackage => write!(f, "cmd package install"),
+            InstallStrategy::PmStreamed => write!(f, "pm install from standard input"),
+            InstallStrategy::PmPushed => write!(f, "pm install from a pushed file"),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::{fmt::Display, net::SocketAddrV4, str::FromStr};
+
+use crate::RustADBError;
+
+/// Service type advertised by devices waiting to be paired over Wi-Fi.
+const PAIRING_SERVICE_TYPE: &str = "_adb-tls-pairing._tcp";
+/// Service type advertised by paired devices accepting wireless debugging connections.
+const CONNECT_SERVICE_TYPE: &str = "_adb-tls-connect._tcp";
+
+/// Represents an ADB service advertised by a device over mDNS, as discovered by ADB server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MdnsService {
+    /// Instance name of the service (e.g. `adb-R58M123456-AbCdEf`, or the service name of a QR code being paired).
+    pub instance: String,
+    /// Type of the service (e.g. `_adb-tls-connect._tcp`).
+    pub service_type: String,
+    /// Address the service listens on.
+    pub address: SocketAddrV4,
+}
+
+impl MdnsService {
+    /// Whether the device waits to be paired on this service.
+    pub fn is_pairing(&self) -> bool {
+        self.service_type == PAIRING_SERVICE_TYPE
+    }
+
+    /// Whether the device accepts wireless debugging connections on this service.
+    pub fn is_connect(&self) -> bool {
+        self.service_type == CONNECT_SERVICE_TYPE
+    }
+}
+
+impl Display for MdnsService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}",
+            self.instance, self.service_type, self.address
+        )
+    }
+}
+
+impl FromStr for MdnsService {
+    type Err = RustADBError;
+
+    /// Parses a line of `host:mdns:services` response: `<instance>\t<service type>\t<ip>:<port>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RustADBError::UnknownResponseType(format!("Invalid mDNS service {s}"));
+        let mut fields = s.trim().split('\t');
+        let (Some(instance), Some(service_type), Some(address), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+
+        Ok(Self {
+            instance: instance.to_string(),
+            // Some ADB server versions report fully qualified types
+            service_type: service_type.trim_end_matches('.').to_string(),
+            address: address.parse().map_err(|_| invalid())?,
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Memory usage of an application process, as reported by `dumpsys meminfo -d`.
+///
+/// All sizes are in KB. Available columns and rows depend on the Android version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemInfo {
+    /// Pid of the process.
+    pub pid: u32,
+    /// Name of the process.
+    pub process: String,
+    /// Sizes by category (e.g. `Native Heap` or `TOTAL`), then by column (e.g. `Pss Total` or `Heap Alloc`).
+    pub categories: BTreeMap<String, BTreeMap<String, u64>>,
+    /// App summary sizes (e.g. `Java Heap` or `TOTAL PSS`), PSS ones when RSS is also given.
+    pub summary: BTreeMap<String, u64>,
+    /// Numbers of objects (e.g. `Views` or `Activities`).
+    pub objects: BTreeMap<String, u64>,
+}
+
+impl MemInfo {
+    /// Total PSS of the process.
+    pub fn total_pss(&self) -> Option<u64> {
+        self.summary.get("TOTAL PSS").copied().or_else(|| {
+            self.categories
+                .get("TOTAL")
+                .and_then(|columns| columns.get("Pss Total"))
+                .copied()
+        })
+    }
+
+    /// Size of `column` in `category`, e.g. `("Native Heap", "Heap Alloc")`.
+    pub fn get(&self, category: &str, column: &str) -> Option<u64> {
+        self.categories.get(category)?.get(column).copied()
+    }
+}
+
+impl Display for MemInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (pid {})", self.process, self.pid)?;
+        for (name, size) in &self.summary {
+            write!(f, "\n{name}: {size} KB")?;
+        }
+        for (name, count) in &self.objects {
+            write!(f, "\n{name}: {count}")?;
+        }
+        Ok(())
+    }
+}
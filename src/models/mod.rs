@@ -1,19 +1,113 @@
 mod adb_command;
+mod adb_key_fingerprint;
 mod adb_request_status;
+mod adb_stat_response;
 mod adb_version;
+mod api_level;
+#[cfg(feature = "apk")]
+mod apk_manifest;
+mod app_database;
+mod batch_entry;
+mod connexion_state;
+mod content_row;
+mod content_uri;
+mod content_value;
+mod crash_artifacts;
+mod debug_server;
+mod debug_target;
 mod device;
+mod device_event;
 mod device_long;
+mod device_selector;
 mod device_state;
+mod dir_entry;
+mod disk_usage;
+mod endpoint;
+mod filesystem_usage;
+mod forward_rule;
 mod host_features;
+mod install_strategy;
+mod mdns_service;
+mod mem_info;
+mod network_status;
+mod package_info;
+mod profile_target;
 mod reboot_type;
+mod reconnect_target;
+mod screen_rotation;
+mod security_state;
+mod selinux_mode;
+mod settings_namespace;
+mod settings_strategy;
+mod shell_environment;
+mod shell_output;
+mod shell_packet;
+mod simpleperf_options;
+mod state_key;
 mod sync_command;
+mod test_prep_options;
+mod transfer_filter;
+mod transfer_report;
+mod ui_bounds;
+mod ui_node;
+mod user_id;
+mod user_info;
+mod version_info;
+mod wifi_pairing;
 
 pub use adb_command::AdbCommand;
+pub use adb_key_fingerprint::AdbKeyFingerprint;
 pub use adb_request_status::AdbRequestStatus;
+pub use adb_stat_response::AdbStatResponse;
 pub use adb_version::AdbVersion;
+pub use api_level::ApiLevel;
+#[cfg(feature = "apk")]
+pub use apk_manifest::ApkManifest;
+pub use app_database::AppDatabase;
+pub use batch_entry::BatchEntry;
+pub(crate) use connexion_state::ConnexionState;
+pub use content_row::ContentRow;
+pub use content_uri::ContentUri;
+pub use content_value::ContentValue;
+pub use crash_artifacts::CrashArtifacts;
+pub use debug_server::DebugServer;
+pub use debug_target::DebugTarget;
 pub use device::Device;
+pub use device_event::DeviceEvent;
 pub use device_long::DeviceLong;
+pub use device_selector::DeviceSelector;
 pub use device_state::DeviceState;
+pub use dir_entry::DirEntry;
+pub use disk_usage::DiskUsage;
+pub use endpoint::Endpoint;
+pub use filesystem_usage::FilesystemUsage;
+pub use forward_rule::ForwardRule;
 pub use host_features::HostFeatures;
+pub use install_strategy::InstallStrategy;
+pub use mdns_service::MdnsService;
+pub use mem_info::MemInfo;
+pub use network_status::NetworkStatus;
+pub use package_info::PackageInfo;
+pub use profile_target::ProfileTarget;
 pub use reboot_type::RebootType;
+pub use reconnect_target::ReconnectTarget;
+pub use screen_rotation::ScreenRotation;
+pub use security_state::SecurityState;
+pub use selinux_mode::SelinuxMode;
+pub use settings_namespace::SettingsNamespace;
+pub use settings_strategy::SettingsStrategy;
+pub use shell_environment::ShellEnvironment;
+pub use shell_output::ShellOutput;
+pub use shell_packet::{ShellPacket, ShellPacketId};
+pub use simpleperf_options::SimpleperfOptions;
+pub use state_key::StateKey;
 pub use sync_command::SyncCommand;
+pub use test_prep_options::TestPrepOptions;
+pub use transfer_filter::TransferFilter;
+pub use transfer_report::TransferReport;
+pub use ui_bounds::UiBounds;
+pub use ui_node::UiNode;
+pub use user_id::UserId;
+pub use user_info::UserInfo;
+pub use version_info::VersionInfo;
+pub use wifi_pairing::WifiPairing;
@@ -0,0 +1,24 @@
+use std::fmt::Display;
+
+/// Represents the network state of the device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetworkStatus {
+    /// Whether Wi-Fi is enabled.
+    pub wifi_enabled: bool,
+    /// Whether mobile data is enabled.
+    pub mobile_data_enabled: bool,
+    /// Whether airplane mode is enabled.
+    pub airplane_mode: bool,
+    /// Whether the device currently has a default network to reach the outside world.
+    pub connected: bool,
+}
+
+impl Display for NetworkStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Wi-Fi enabled: {}", self.wifi_enabled)?;
+        writeln!(f, "Mobile data enabled: {}", self.mobile_data_enabled)?;
+        writeln!(f, "Airplane mode: {}", self.airplane_mode)?;
+        write!(f, "Connected: {}", self.connected)
+    }
+}
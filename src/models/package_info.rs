@@ -0,0 +1,54 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::NaiveDateTime;
+
+/// State of a package installed on the device, as reported by `dumpsys package`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackageInfo {
+    /// Name of the package.
+    pub package: String,
+    /// Linux uid of the package (its app id, shared by all users).
+    pub uid: u32,
+    /// Time of the first install, in device local time.
+    pub first_install_time: Option<NaiveDateTime>,
+    /// Time of the last update, in device local time.
+    pub last_update_time: Option<NaiveDateTime>,
+    /// Permissions requested by the manifest of the package.
+    pub requested_permissions: Vec<String>,
+    /// Whether install and runtime permissions are granted, by permission name.
+    pub permissions: BTreeMap<String, bool>,
+    /// Digests of the signing certificates, as hexadecimal hash codes.
+    pub signature_digests: Vec<String>,
+    /// Whether the package is enabled for the user.
+    pub enabled: bool,
+}
+
+impl PackageInfo {
+    /// Names of the permissions granted to the package.
+    pub fn granted_permissions(&self) -> impl Iterator<Item = &str> {
+        self.permissions
+            .iter()
+            .filter(|(_, granted)| **granted)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Whether `permission` is granted to the package.
+    pub fn is_granted(&self, permission: &str) -> bool {
+        self.permissions.get(permission).copied().unwrap_or(false)
+    }
+}
+
+impl Display for PackageInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (uid {}, {}), {}/{} permissions granted",
+            self.package,
+            self.uid,
+            if self.enabled { "enabled" } else { "disabled" },
+            self.granted_permissions().count(),
+            self.permissions.len()
+        )
+    }
+}
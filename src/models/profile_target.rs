@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+use crate::utils::shell_quote;
+
+/// Process profiled by `simpleperf_record`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProfileTarget {
+    /// Running process with the given pid.
+    Pid(u32),
+    /// Running debuggable or profileable application with the given package name.
+    Package(String),
+}
+
+impl Display for ProfileTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileTarget::Pid(pid) => write!(f, "-p {pid}"),
+            ProfileTarget::Package(package) => write!(f, "--app {}", shell_quote(package)),
+        }
+    }
+}
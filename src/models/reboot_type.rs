@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Type of reboot needed.
 pub enum RebootType {
     /// "Classic" device reboot
@@ -0,0 +1,23 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Side from which a connection to a device is reset.
+pub enum ReconnectTarget {
+    /// ADB server drops and reopens its connection to the device
+    Host,
+    /// Device drops its connection, ADB server then reconnects to it
+    Device,
+    /// ADB server reopens its connections to all offline devices, whatever the selected device
+    Offline,
+}
+
+impl Display for ReconnectTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReconnectTarget::Host => write!(f, "host"),
+            ReconnectTarget::Device => write!(f, "device"),
+            ReconnectTarget::Offline => write!(f, "offline"),
+        }
+    }
+}
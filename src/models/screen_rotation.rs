@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+use crate::RustADBError;
+
+/// Rotation of the screen from its natural orientation (portrait on phones, often landscape on tablets).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScreenRotation {
+    /// Natural orientation of the device.
+    Natural,
+    /// Rotated by 90 degrees counterclockwise.
+    Rotated90,
+    /// Upside down.
+    Rotated180,
+    /// Rotated by 90 degrees clockwise.
+    Rotated270,
+}
+
+impl From<ScreenRotation> for u8 {
+    /// Value of the rotation in `user_rotation` setting.
+    fn from(value: ScreenRotation) -> Self {
+        match value {
+            ScreenRotation::Natural => 0,
+            ScreenRotation::Rotated90 => 1,
+            ScreenRotation::Rotated180 => 2,
+            ScreenRotation::Rotated270 => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for ScreenRotation {
+    type Error = RustADBError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ScreenRotation::Natural),
+            1 => Ok(ScreenRotation::Rotated90),
+            2 => Ok(ScreenRotation::Rotated180),
+            3 => Ok(ScreenRotation::Rotated270),
+            _ => Err(RustADBError::ConvertionError),
+        }
+    }
+}
+
+impl Display for ScreenRotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} degrees", u16::from(u8::from(*self)) * 90)
+    }
+}
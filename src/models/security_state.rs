@@ -0,0 +1,53 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+
+use crate::SelinuxMode;
+
+/// Security state of the device, as audited on lab devices.
+///
+/// Fields are `None` when the device does not report them (e.g. older Android versions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecurityState {
+    /// Verified boot state (`green`, `yellow`, `orange` or `red`).
+    pub verified_boot_state: Option<String>,
+    /// Whether the bootloader is locked.
+    pub bootloader_locked: Option<bool>,
+    /// SELinux mode.
+    pub selinux_mode: Option<SelinuxMode>,
+    /// Android security patch level.
+    pub security_patch: Option<NaiveDate>,
+    /// Whether user data is encrypted.
+    pub encrypted: bool,
+    /// Encryption type of user data (`file` or `block`).
+    pub encryption_type: Option<String>,
+}
+
+impl Display for SecurityState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn or_unknown<T: Display>(value: &Option<T>) -> String {
+            value
+                .as_ref()
+                .map_or("unknown".to_string(), |value| value.to_string())
+        }
+
+        writeln!(
+            f,
+            "Verified boot state: {}",
+            or_unknown(&self.verified_boot_state)
+        )?;
+        writeln!(
+            f,
+            "Bootloader locked: {}",
+            or_unknown(&self.bootloader_locked)
+        )?;
+        writeln!(f, "SELinux mode: {}", or_unknown(&self.selinux_mode))?;
+        writeln!(f, "Security patch: {}", or_unknown(&self.security_patch))?;
+        write!(f, "Encrypted: {}", self.encrypted)?;
+        if let Some(encryption_type) = &self.encryption_type {
+            write!(f, " ({encryption_type})")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::RustADBError;
+
+/// Represents the SELinux mode of the device, as reported by `getenforce`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelinuxMode {
+    /// Policy is enforced, denials are logged and applied.
+    Enforcing,
+    /// Policy is not enforced, denials are only logged.
+    Permissive,
+    /// SELinux is disabled.
+    Disabled,
+}
+
+impl Display for SelinuxMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelinuxMode::Enforcing => write!(f, "Enforcing"),
+            SelinuxMode::Permissive => write!(f, "Permissive"),
+            SelinuxMode::Disabled => write!(f, "Disabled"),
+        }
+    }
+}
+
+impl FromStr for SelinuxMode {
+    type Err = RustADBError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "enforcing" => Ok(Self::Enforcing),
+            "permissive" => Ok(Self::Permissive),
+            "disabled" => Ok(Self::Disabled),
+            _ => Err(RustADBError::ConvertionError),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::fmt::Display;
+
+/// Namespaces of the device settings provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SettingsNamespace {
+    /// Device-wide settings, applying to all users (e.g. `airplane_mode_on`, `window_animation_scale`).
+    Global,
+    /// User preferences (e.g. `screen_brightness`).
+    System,
+    /// Secured user preferences, that applications can read but not write (e.g. `default_input_method`).
+    Secure,
+}
+
+impl Display for SettingsNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsNamespace::Global => write!(f, "global"),
+            SettingsNamespace::System => write!(f, "system"),
+            SettingsNamespace::Secure => write!(f, "secure"),
+        }
+    }
+}
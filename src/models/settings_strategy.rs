@@ -0,0 +1,20 @@
+use std::fmt::Display;
+
+/// Way device settings are read and written, depending on what the device supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SettingsStrategy {
+    /// `settings` command, from Android 4.2.
+    SettingsCommand,
+    /// `content` command querying the settings provider, on older versions.
+    ContentProvider,
+}
+
+impl Display for SettingsStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsStrategy::SettingsCommand => write!(f, "settings command"),
+            SettingsStrategy::ContentProvider => write!(f, "settings content provider"),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{utils::shell_quote, Result, RustADBError};
+
+/// Working directory and environment variables of a command run by `shell_command_in`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShellEnvironment {
+    /// Directory the command is run in, which must exist on the device.
+    pub working_directory: Option<String>,
+    /// Variables exported to the command, in order.
+    pub variables: Vec<(String, String)>,
+}
+
+impl ShellEnvironment {
+    /// Runs the command in `directory`.
+    pub fn working_directory<S: ToString>(mut self, directory: S) -> Self {
+        self.working_directory = Some(directory.to_string());
+        self
+    }
+
+    /// Exports variable `name` with `value` to the command.
+    pub fn variable<N: ToString, V: ToString>(mut self, name: N, value: V) -> Self {
+        self.variables.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Prefixes `command` with the change of directory and variable exports, stopping before `command` if any of them fails.
+    pub(crate) fn wrap(&self, command: &str) -> Result<String> {
+        let mut steps = Vec::new();
+        if let Some(directory) = &self.working_directory {
+            steps.push(format!("cd {}", shell_quote(directory)));
+        }
+        for (name, value) in &self.variables {
+            if !is_variable_name(name) {
+                return Err(RustADBError::InvalidEnvironmentVariable(name.to_string()));
+            }
+            steps.push(format!("export {name}={}", shell_quote(value)));
+        }
+        steps.push(command.to_string());
+        Ok(steps.join(" && "))
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
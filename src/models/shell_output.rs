@@ -0,0 +1,11 @@
+/// Represents output received from an interactive shell session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShellOutput {
+    /// Data written by the remote process on its standard output.
+    Stdout(Vec<u8>),
+    /// Data written by the remote process on its standard error.
+    Stderr(Vec<u8>),
+    /// Session is over, with the exit code of the remote process when known (shell v2 only).
+    Exit(Option<u8>),
+}
@@ -0,0 +1,88 @@
+use std::io::Read;
+
+use crate::{parser, protocol::codec, Result, RustADBError};
+
+/// Identifiers of shell v2 protocol packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellPacketId {
+    Stdin,
+    Stdout,
+    Stderr,
+    Exit,
+    CloseStdin,
+    WindowSizeChange,
+}
+
+impl From<ShellPacketId> for u8 {
+    fn from(value: ShellPacketId) -> Self {
+        match value {
+            ShellPacketId::Stdin => 0,
+            ShellPacketId::Stdout => 1,
+            ShellPacketId::Stderr => 2,
+            ShellPacketId::Exit => 3,
+            ShellPacketId::CloseStdin => 4,
+            ShellPacketId::WindowSizeChange => 5,
+        }
+    }
+}
+
+impl TryFrom<u8> for ShellPacketId {
+    type Error = RustADBError;
+
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Stdin),
+            1 => Ok(Self::Stdout),
+            2 => Ok(Self::Stderr),
+            3 => Ok(Self::Exit),
+            4 => Ok(Self::CloseStdin),
+            5 => Ok(Self::WindowSizeChange),
+            _ => Err(RustADBError::UnknownResponseType(format!(
+                "Unknown shell packet id {value}"
+            ))),
+        }
+    }
+}
+
+/// Represents a shell v2 protocol packet: packet id, little-endian payload length and payload.
+#[derive(Debug)]
+pub struct ShellPacket {
+    pub id: ShellPacketId,
+    pub payload: Vec<u8>,
+}
+
+impl ShellPacket {
+    pub fn new(id: ShellPacketId, payload: Vec<u8>) -> Self {
+        Self { id, payload }
+    }
+
+    /// Builds a window size change packet for a terminal of `rows` x `columns` characters.
+    pub fn window_size(rows: u16, columns: u16) -> Self {
+        // Sizes in pixels are not known, they are left to 0
+        Self::new(
+            ShellPacketId::WindowSizeChange,
+            format!("{rows}x{columns},0x0\0").into_bytes(),
+        )
+    }
+
+    /// Reads the next packet from `reader`.
+    pub fn read_from(reader: &mut dyn Read) -> Result<Self> {
+        let mut header = [0_u8; 5];
+        reader.read_exact(&mut header)?;
+
+        let (id, length) = parser::shell_packet_header(&header)?;
+        let mut payload = vec![0; length];
+        reader.read_exact(&mut payload)?;
+
+        Ok(Self::new(id, payload))
+    }
+
+    /// Encodes this packet to be sent on the wire.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = codec::encode_shell_packet_header(self.id.into(), self.payload.len() as u32);
+        let mut bytes = header.to_vec();
+        bytes.extend_from_slice(&self.payload);
+
+        bytes
+    }
+}
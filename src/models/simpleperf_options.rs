@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+/// Recording settings of `simpleperf_record`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleperfOptions {
+    /// Events to record (e.g. `cpu-cycles` or `sched:sched_switch`), simpleperf default ones if empty.
+    pub events: Vec<String>,
+    /// Duration of the recording. Defaults to 10 seconds.
+    pub duration: Duration,
+    /// Sampling frequency in samples per second, simpleperf default one if `None`.
+    pub frequency: Option<u32>,
+    /// Records call graphs of samples.
+    pub call_graph: bool,
+    /// Runs `simpleperf report` on the device and returns its output.
+    pub report: bool,
+}
+
+impl Default for SimpleperfOptions {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            duration: Duration::from_secs(10),
+            frequency: None,
+            call_graph: false,
+            report: false,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+use super::SettingsNamespace;
+
+/// Device state captured by [AdbStateSnapshot](crate::AdbStateSnapshot): a setting or a system property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StateKey {
+    /// Setting `key` of a settings namespace.
+    Setting(SettingsNamespace, String),
+    /// System property with the given name (e.g. `debug.hwui.profile`), which must be settable with `setprop`.
+    Property(String),
+}
+
+impl Display for StateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateKey::Setting(namespace, key) => write!(f, "{namespace} setting {key}"),
+            StateKey::Property(name) => write!(f, "property {name}"),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/// Device preparation steps applied by `prepare_for_testing`.
+///
+/// All steps are enabled by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestPrepOptions {
+    /// Disables window, transition and animator animations.
+    pub disable_animations: bool,
+    /// Keeps the screen on while the device is plugged in.
+    pub stay_awake: bool,
+    /// Dismisses the keyguard (lock screen) if it is not secured.
+    pub dismiss_keyguard: bool,
+    /// Disables all soft keyboards (input methods).
+    pub disable_soft_keyboard: bool,
+    /// Marks the immersive mode confirmation as already shown, so that it does not cover the screen.
+    pub confirm_immersive_mode: bool,
+}
+
+impl Default for TestPrepOptions {
+    fn default() -> Self {
+        Self {
+            disable_animations: true,
+            stay_awake: true,
+            dismiss_keyguard: true,
+            disable_soft_keyboard: true,
+            confirm_immersive_mode: true,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::{Result, RustADBError};
+
+/// Glob pattern matched against paths relative to the transferred directory.
+#[derive(Debug, Clone)]
+struct GlobPattern {
+    regex: Regex,
+    /// Pattern ends with `/`, only matching directories.
+    dir_only: bool,
+}
+
+impl GlobPattern {
+    /// Compiles `pattern`, following `.gitignore` rules: patterns without `/` match names at any depth,
+    /// others are anchored to the transferred directory, `*` and `?` do not match `/` while `**` does.
+    fn new(pattern: &str) -> Result<Self> {
+        let (pattern, dir_only) = match pattern.strip_suffix('/') {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+        let mut regex = String::from(match anchored {
+            true => "^",
+            false => "^(?:.*/)?",
+        });
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    // `**/` also matches no directory at all
+                    match chars.next_if_eq(&'/') {
+                        Some(_) => regex.push_str("(?:.*/)?"),
+                        None => regex.push_str(".*"),
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => {
+                    let mut class = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') if !class.is_empty() => break,
+                            Some('\\') => class.push_str(r"\\"),
+                            Some(c) => class.push(c),
+                            None => return Err(RustADBError::InvalidGlobPattern(pattern.into())),
+                        }
+                    }
+                    match class.strip_prefix('!') {
+                        Some(negated) => regex.push_str(&format!("[^{negated}]")),
+                        None => regex.push_str(&format!("[{class}]")),
+                    }
+                }
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+
+        Ok(Self {
+            regex: Regex::new(&regex)?,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.regex.is_match(path)
+    }
+}
+
+/// Selects the files of recursive transfers (e.g. `push_dir_tar_filtered`) with glob patterns.
+///
+/// Patterns follow `.gitignore` rules, e.g. `*.o` matches object files in any directory, `/build` only the
+/// top-level build directory and `node_modules/` directories named so. Excluding a directory excludes its content.
+#[derive(Debug, Clone, Default)]
+pub struct TransferFilter {
+    includes: Vec<GlobPattern>,
+    /// Exclusion rules in order, `false` for negated ones re-including paths.
+    rules: Vec<(GlobPattern, bool)>,
+}
+
+impl TransferFilter {
+    /// Creates a filter selecting every file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only transfers files matching `pattern`, or contained in a directory matching it, and those of other included patterns.
+    pub fn include(mut self, pattern: &str) -> Result<Self> {
+        self.includes.push(GlobPattern::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Skips files and directories matching `pattern`.
+    pub fn exclude(mut self, pattern: &str) -> Result<Self> {
+        self.rules.push((GlobPattern::new(pattern)?, true));
+        Ok(self)
+    }
+
+    /// Skips files and directories matching the patterns of the ignore file located at `path` (e.g. `.adbignore`).
+    ///
+    /// As in `.gitignore` files, blank lines and lines starting with `#` are ignored,
+    /// and patterns starting with `!` include again paths excluded by previous patterns.
+    pub fn ignore_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        for line in std::fs::read_to_string(path)?.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            self.rules.push(match line.strip_prefix('!') {
+                Some(pattern) => (GlobPattern::new(pattern)?, false),
+                None => (GlobPattern::new(line)?, true),
+            });
+        }
+
+        Ok(self)
+    }
+
+    /// Whether the file or directory at `path`, relative to the transferred directory, is transferred.
+    ///
+    /// Directories are transferred unless excluded, includes only select files.
+    pub(crate) fn allows(&self, path: &Path, is_dir: bool) -> bool {
+        let components = path
+            .components()
+            .filter_map(|component| match component {
+                std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if components.is_empty() {
+            return true;
+        }
+
+        // Paths of the directories containing the entry, and of the entry itself
+        let paths = (1..=components.len())
+            .map(|depth| components[..depth].join("/"))
+            .collect::<Vec<_>>();
+        let last = paths.len() - 1;
+
+        let excluded = paths
+            .iter()
+            .enumerate()
+            .any(|(depth, path)| self.excludes(path, is_dir || depth < last));
+        if excluded {
+            return false;
+        }
+
+        is_dir
+            || self.includes.is_empty()
+            || paths.iter().enumerate().any(|(depth, path)| {
+                self.includes
+                    .iter()
+                    .any(|include| include.matches(path, depth < last))
+            })
+    }
+
+    /// Whether the last rule matching `path` excludes it.
+    fn excludes(&self, path: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern.matches(path, is_dir))
+            .is_some_and(|(_, excluded)| *excluded)
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// Represents statistics of a completed transfer (push, pull or install), e.g. to log or trend transfer performance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransferReport {
+    /// Number of bytes sent or received.
+    pub bytes: u64,
+    /// Time taken by the transfer, measured with a monotonic clock.
+    pub duration: Duration,
+    /// Number of files transferred.
+    pub files_transferred: u64,
+    /// Number of files not transferred as they were already up to date.
+    pub files_skipped: u64,
+    /// Number of times the transfer resumed an interrupted one.
+    pub retries: u32,
+}
+
+impl TransferReport {
+    /// Report of a single file of `bytes` bytes transferred since `start`.
+    pub(crate) fn file(bytes: u64, start: Instant) -> Self {
+        Self {
+            bytes,
+            duration: start.elapsed(),
+            files_transferred: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Average throughput of the transfer, in bytes per second.
+    pub fn throughput(&self) -> f64 {
+        match self.duration.is_zero() {
+            true => 0.0,
+            false => self.bytes as f64 / self.duration.as_secs_f64(),
+        }
+    }
+}
+
+impl Display for TransferReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} bytes in {:.3}s ({:.0} bytes/s), {} file(s) transferred, {} skipped",
+            self.bytes,
+            self.duration.as_secs_f64(),
+            self.throughput(),
+            self.files_transferred,
+            self.files_skipped
+        )?;
+
+        if self.retries > 0 {
+            write!(f, ", {} retries", self.retries)?;
+        }
+
+        Ok(())
+    }
+}
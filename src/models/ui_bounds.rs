@@ -0,0 +1,72 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::RustADBError;
+
+/// Screen bounds of a UI node, in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UiBounds {
+    /// X coordinate of the left edge.
+    pub left: i32,
+    /// Y coordinate of the top edge.
+    pub top: i32,
+    /// X coordinate of the right edge, excluded.
+    pub right: i32,
+    /// Y coordinate of the bottom edge, excluded.
+    pub bottom: i32,
+}
+
+impl UiBounds {
+    /// Width of the bounds.
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    /// Height of the bounds.
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    /// Center of the bounds, e.g. to tap the node.
+    pub fn center(&self) -> (i32, i32) {
+        ((self.left + self.right) / 2, (self.top + self.bottom) / 2)
+    }
+
+    /// Whether point (`x`, `y`) is inside the bounds.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.left..self.right).contains(&x) && (self.top..self.bottom).contains(&y)
+    }
+}
+
+impl Display for UiBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{},{}][{},{}]",
+            self.left, self.top, self.right, self.bottom
+        )
+    }
+}
+
+impl FromStr for UiBounds {
+    type Err = RustADBError;
+
+    /// Parses bounds as written by uiautomator: `[<left>,<top>][<right>,<bottom>]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RustADBError::UnknownResponseType(format!("Invalid bounds {s}"));
+        let (top_left, bottom_right) = s
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .and_then(|s| s.split_once("]["))
+            .ok_or_else(invalid)?;
+        let (left, top) = top_left.split_once(',').ok_or_else(invalid)?;
+        let (right, bottom) = bottom_right.split_once(',').ok_or_else(invalid)?;
+
+        Ok(Self {
+            left: left.parse()?,
+            top: top.parse()?,
+            right: right.parse()?,
+            bottom: bottom.parse()?,
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use super::UiBounds;
+
+/// Node of the UI hierarchy of the device, as dumped by `uiautomator dump`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UiNode {
+    /// Class of the view, e.g. `android.widget.Button`.
+    pub class: String,
+    /// Package of the application displaying the view.
+    pub package: String,
+    /// Resource id of the view, e.g. `com.example:id/login`, empty if it has none.
+    pub resource_id: String,
+    /// Text of the view.
+    pub text: String,
+    /// Content description of the view.
+    pub content_desc: String,
+    /// Screen bounds of the view.
+    pub bounds: UiBounds,
+    /// All attributes of the node, including the ones above and state flags like `clickable` or `checked`.
+    pub attributes: BTreeMap<String, String>,
+    /// Child nodes.
+    pub children: Vec<UiNode>,
+}
+
+impl UiNode {
+    /// Value of attribute `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Whether boolean attribute `name` (e.g. `clickable`, `enabled` or `checked`) is set.
+    pub fn is(&self, name: &str) -> bool {
+        self.attribute(name) == Some("true")
+    }
+
+    /// Iterates over this node and all its descendants, depth first.
+    pub fn descendants(&self) -> impl Iterator<Item = &UiNode> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// First node, among this node and its descendants, matching `predicate`.
+    pub fn find(&self, predicate: impl Fn(&UiNode) -> bool) -> Option<&UiNode> {
+        self.descendants().find(|node| predicate(node))
+    }
+
+    /// First node, among this node and its descendants, with resource id `resource_id`.
+    pub fn find_by_resource_id(&self, resource_id: &str) -> Option<&UiNode> {
+        self.find(|node| node.resource_id == resource_id)
+    }
+
+    /// First node, among this node and its descendants, with text `text`.
+    pub fn find_by_text(&self, text: &str) -> Option<&UiNode> {
+        self.find(|node| node.text == text)
+    }
+}
+
+impl Display for UiNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if depth > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:indent$}{} {}",
+                "",
+                node.class,
+                node.bounds,
+                indent = depth * 2
+            )?;
+            for (name, value) in [
+                ("id", &node.resource_id),
+                ("text", &node.text),
+                ("desc", &node.content_desc),
+            ] {
+                if !value.is_empty() {
+                    write!(f, " {name}={value:?}")?;
+                }
+            }
+            stack.extend(node.children.iter().rev().map(|child| (child, depth + 1)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::RustADBError;
+
+/// Represents the id of a user of the device, or of a profile (e.g. a work profile) of a user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserId(pub u32);
+
+impl UserId {
+    /// System user, the primary user on most devices.
+    pub const SYSTEM: UserId = UserId(0);
+}
+
+impl Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for UserId {
+    type Err = RustADBError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(UserId(s.trim().parse()?))
+    }
+}
@@ -0,0 +1,63 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::UserId;
+use crate::RustADBError;
+
+/// `UserInfo` flag of managed profiles, e.g. work profiles.
+const FLAG_MANAGED_PROFILE: u32 = 0x20;
+
+/// Represents a user of the device, as listed by `pm list users`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserInfo {
+    /// Id of the user.
+    pub id: UserId,
+    /// Name of the user.
+    pub name: String,
+    /// `UserInfo` flags of the user (e.g. `0x20` for managed profiles).
+    pub flags: u32,
+    /// Whether the user is running.
+    pub running: bool,
+}
+
+impl UserInfo {
+    /// Whether the user is a managed profile, e.g. a work profile.
+    pub fn is_managed_profile(&self) -> bool {
+        self.flags & FLAG_MANAGED_PROFILE != 0
+    }
+}
+
+impl Display for UserInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} (flags {:#x})", self.id, self.name, self.flags)?;
+        if self.running {
+            write!(f, " running")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for UserInfo {
+    type Err = RustADBError;
+
+    /// Parses a line of `pm list users` output: `UserInfo{<id>:<name>:<hexadecimal flags>}`, followed by `running` for running users.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RustADBError::UnknownResponseType(format!("Invalid user {s}"));
+        let (info, state) = s
+            .trim()
+            .strip_prefix("UserInfo{")
+            .and_then(|rest| rest.rsplit_once('}'))
+            .ok_or_else(invalid)?;
+
+        // Names may contain ':'
+        let (id, rest) = info.split_once(':').ok_or_else(invalid)?;
+        let (name, flags) = rest.rsplit_once(':').ok_or_else(invalid)?;
+
+        Ok(Self {
+            id: id.parse()?,
+            name: name.to_string(),
+            flags: u32::from_str_radix(flags, 16)?,
+            running: state.trim() == "running",
+        })
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::Display;
+
+use regex::bytes::Regex;
+
+use crate::RustADBError;
+
+/// Represents the version of a package installed on the device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionInfo {
+    /// Internal version number, used to compare versions.
+    pub version_code: u64,
+    /// Version name displayed to users, if the package declares one.
+    pub version_name: Option<String>,
+}
+
+impl Display for VersionInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version_name {
+            Some(version_name) => write!(f, "{version_name} ({})", self.version_code),
+            None => write!(f, "{}", self.version_code),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for VersionInfo {
+    type Error = RustADBError;
+
+    /// Parses the output of `dumpsys package <package>`.
+    ///
+    /// Only the first package entry is considered, as it corresponds to the version currently in use
+    /// (updated system apps list their factory version afterwards).
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let code_regex = Regex::new(r"versionCode=(?P<code>\d+)")?;
+        let name_regex = Regex::new(r"versionName=(?P<name>[^\r\n]*)")?;
+
+        let version_code = code_regex
+            .captures(value)
+            .and_then(|groups| groups.name("code"))
+            .ok_or(RustADBError::RegexParsingError)?;
+        let version_name = name_regex
+            .captures(value)
+            .and_then(|groups| groups.name("name"))
+            .map(|name| String::from_utf8(name.as_bytes().to_vec()))
+            .transpose()?;
+
+        Ok(VersionInfo {
+            version_code: std::str::from_utf8(version_code.as_bytes())?.parse()?,
+            version_name,
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use std::fmt::Display;
+
+use rand::{distributions::Alphanumeric, Rng};
+
+/// Service name and password of a "Pair device with QR code" flow of wireless debugging.
+///
+/// The device scanning the QR code advertises a pairing service named after `service_name`, and expects `password`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WifiPairing {
+    /// Instance name the device gives to its pairing service.
+    pub service_name: String,
+    /// Password the device pairs with.
+    pub password: String,
+}
+
+impl WifiPairing {
+    /// Generates a random service name and password.
+    pub fn generate() -> Self {
+        Self {
+            service_name: format!("adb_client-{}", random_alphanumeric(10)),
+            password: random_alphanumeric(12),
+        }
+    }
+
+    /// Content of the QR code to display for the device to scan (e.g. `WIFI:T:ADB;S:<service name>;P:<password>;;`).
+    pub fn qr_payload(&self) -> String {
+        format!(
+            "WIFI:T:ADB;S:{};P:{};;",
+            escape(&self.service_name),
+            escape(&self.password)
+        )
+    }
+}
+
+impl Display for WifiPairing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.qr_payload())
+    }
+}
+
+fn random_alphanumeric(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+/// Escapes characters having a meaning in `WIFI:` payloads.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
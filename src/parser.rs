@@ -0,0 +1,556 @@
+use std::str::{self, FromStr};
+
+use chrono::NaiveDateTime;
+use regex::bytes::Regex;
+
+use crate::{
+    models::{AdbStatResponse, ShellPacketId},
+    protocol::codec,
+    AdbVersion, ContentRow, Device, DeviceLong, DeviceState, MemInfo, PackageInfo, Result,
+    RustADBError, UiNode, UserId,
+};
+
+/// Parses the 4 hexadecimal digits prefixing the body of ADB server responses.
+pub(crate) fn hex_length(input: &[u8]) -> Result<usize> {
+    Ok(codec::decode_length(input)?)
+}
+
+/// Parses the response of `host:version`, made of 4 hexadecimal digits.
+pub(crate) fn adb_version(input: &[u8]) -> Result<AdbVersion> {
+    let digits = take(input, 4, "version")?;
+    if !digits.iter().all(u8::is_ascii_hexdigit) {
+        return Err(malformed("version", input));
+    }
+
+    Ok(AdbVersion::new(
+        u32::from_str_radix(str::from_utf8(&digits[0..2])?, 16)?,
+        u32::from_str_radix(str::from_utf8(&digits[2..4])?, 16)?,
+    ))
+}
+
+/// Parses a line of `host:devices` response: identifier and state separated by a tab, with an optional final new line.
+pub(crate) fn device(line: &[u8]) -> Result<Device> {
+    // TODO: Prevent regex compilation every call
+    let parse_regex = Regex::new("^(\\S+)\t([^\t\n]+)\n?$")?;
+    let groups = parse_regex
+        .captures(line)
+        .ok_or(RustADBError::RegexParsingError)?;
+
+    Ok(Device {
+        identifier: group(&groups, 1)?,
+        state: DeviceState::from_str(&group(&groups, 2)?)?,
+    })
+}
+
+/// Parses a line of `host:devices-l` response: identifier, state, then `key:value` fields in any order.
+///
+/// Only `transport_id` is always present, e.g. emulators and devices connected over TCP/IP do not have any `usb` field.
+pub(crate) fn device_long(line: &[u8]) -> Result<DeviceLong> {
+    // TODO: Prevent regex compilation every call
+    let parse_regex =
+        Regex::new("^(?P<identifier>\\S+)\\s+(?P<state>.+?)(?P<fields>( [a-z_]+:\\S*)+)\n?$")?;
+    let groups = parse_regex
+        .captures(line)
+        .ok_or(RustADBError::RegexParsingError)?;
+
+    let mut usb = None;
+    let mut product = None;
+    let mut model = None;
+    let mut device = None;
+    let mut transport_id = None;
+    for field in named_group(&groups, "fields")?.split_whitespace() {
+        let Some((key, value)) = field.split_once(':') else {
+            continue;
+        };
+        let value = Some(value.to_string());
+        match key {
+            "usb" => usb = value,
+            "product" => product = value,
+            "model" => model = value,
+            "device" => device = value,
+            "transport_id" => transport_id = value,
+            _ => {}
+        }
+    }
+
+    let unknown = || "Unk".to_string();
+    Ok(DeviceLong {
+        identifier: named_group(&groups, "identifier")?,
+        state: DeviceState::from_str(&named_group(&groups, "state")?)?,
+        usb: usb.unwrap_or_else(unknown),
+        product: product.unwrap_or_else(unknown),
+        model: model.unwrap_or_else(unknown),
+        device: device.unwrap_or_else(unknown),
+        transport_id: transport_id
+            .ok_or(RustADBError::RegexParsingError)?
+            .parse()?,
+    })
+}
+
+/// Parses a sync packet header: 4 bytes id followed by a 32-bit little-endian value, usually the length of the payload.
+pub(crate) fn sync_header(input: &[u8]) -> Result<([u8; 4], u32)> {
+    Ok(codec::decode_sync_header(input)?)
+}
+
+/// Parses the 12 bytes following the id of a sync `STAT` response: mode, size and modification time.
+pub(crate) fn sync_stat(input: &[u8]) -> Result<AdbStatResponse> {
+    Ok(codec::decode_sync_stat(input)?)
+}
+
+/// Parses the 16 bytes following the id of a sync `DENT` response: the metadata of the entry, and the length of the name that follows.
+pub(crate) fn sync_dent(input: &[u8]) -> Result<(AdbStatResponse, usize)> {
+    Ok(codec::decode_sync_dent(input)?)
+}
+
+/// Parses the 68 bytes following the id of a sync `LST2` response: error code and metadata.
+pub(crate) fn sync_stat_v2(input: &[u8]) -> Result<(u32, AdbStatResponse)> {
+    Ok(codec::decode_sync_stat_v2(input)?)
+}
+
+/// Parses the 72 bytes following the id of a sync `DNT2` response: error code, metadata and length of the name that follows.
+pub(crate) fn sync_dent_v2(input: &[u8]) -> Result<(u32, AdbStatResponse, usize)> {
+    Ok(codec::decode_sync_dent_v2(input)?)
+}
+
+/// Parses a shell v2 packet header: packet id and little-endian payload length.
+pub(crate) fn shell_packet_header(input: &[u8]) -> Result<(ShellPacketId, usize)> {
+    let (id, length) = codec::decode_shell_packet_header(input)?;
+
+    Ok((ShellPacketId::try_from(id)?, length))
+}
+
+/// Parses the output of `content query`, made of `Row: <index> <column>=<value>, <column>=<value>` lines.
+///
+/// Values may contain `, ` or new lines, only columns of `projection` (all of them when empty) are looked for.
+/// Output which is not made of rows is an error message of the command.
+pub(crate) fn content_rows(output: &str, projection: &[&str]) -> Result<Vec<ContentRow>> {
+    let output = output.trim_end();
+    if output.is_empty() || output == "No result found." {
+        return Ok(vec![]);
+    }
+
+    let mut lines: Vec<String> = vec![];
+    for line in output.lines() {
+        match (line.strip_prefix("Row: "), lines.last_mut()) {
+            (Some(row), _) => lines.push(row.to_string()),
+            // Continuation of a value containing new lines
+            (None, Some(last)) => {
+                last.push('\n');
+                last.push_str(line);
+            }
+            (None, None) => return Err(RustADBError::ShellCommandFailed(output.to_string())),
+        }
+    }
+
+    let column_regex = regex::Regex::new("(?:^|, )([A-Za-z_][A-Za-z0-9_]*)=")?;
+    let mut rows = Vec::with_capacity(lines.len());
+    for line in lines {
+        // Rows start with their index
+        let (_, line) = line
+            .split_once(' ')
+            .ok_or_else(|| malformed("content row", line.as_bytes()))?;
+
+        let columns = column_regex
+            .captures_iter(line)
+            .filter_map(|captures| Some((captures.get(0)?, captures.get(1)?)))
+            .filter(|(column, name)| {
+                column.start() == 0 || projection.is_empty() || projection.contains(&name.as_str())
+            })
+            .collect::<Vec<_>>();
+        if columns
+            .first()
+            .is_none_or(|(column, _)| column.start() != 0)
+        {
+            return Err(malformed("content row", line.as_bytes()));
+        }
+
+        let mut row = ContentRow::default();
+        for (index, (column, name)) in columns.iter().enumerate() {
+            let end = columns
+                .get(index + 1)
+                .map_or(line.len(), |(next, _)| next.start());
+            let value = &line[column.end()..end];
+            row.values.insert(
+                name.as_str().to_string(),
+                (value != "NULL").then(|| value.to_string()),
+            );
+        }
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Parses the output of `dumpsys meminfo -d <process>`, keeping the first process listed.
+///
+/// Categories table has a two lines header, whose words are paired into column names.
+/// Output without any process is an error message of the command.
+pub(crate) fn mem_info(output: &str) -> Result<MemInfo> {
+    let header_regex = regex::Regex::new(r"\*\* MEMINFO in pid (\d+) \[(.*)\] \*\*")?;
+    let pair_regex = regex::Regex::new(r"([A-Za-z][A-Za-z .]*?):\s+(\d+)")?;
+
+    let mut lines = output.lines();
+    let mut mem_info = loop {
+        let line = lines
+            .next()
+            .ok_or_else(|| RustADBError::ShellCommandFailed(output.trim().to_string()))?;
+        if let Some(groups) = header_regex.captures(line) {
+            break MemInfo {
+                pid: groups[1].parse()?,
+                process: groups[2].to_string(),
+                ..Default::default()
+            };
+        }
+    };
+
+    let mut header: Vec<&str> = vec![];
+    let mut columns: Vec<String> = vec![];
+    let mut section = "";
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("** MEMINFO") {
+            break;
+        }
+        if matches!(trimmed, "App Summary" | "Objects" | "SQL" | "DATABASES") {
+            section = trimmed;
+            continue;
+        }
+
+        match section {
+            "" => {
+                let words = trimmed.split_whitespace().collect::<Vec<_>>();
+                let first_number = words.iter().position(|word| word.parse::<u64>().is_ok());
+                match first_number {
+                    _ if words.is_empty() || words.iter().all(|word| word.starts_with('-')) => {}
+                    // Header lines come before any category
+                    None if columns.is_empty() && header.is_empty() => header = words,
+                    None if columns.is_empty() && words.len() == header.len() => {
+                        columns = header
+                            .iter()
+                            .zip(words)
+                            .map(|(first, second)| format!("{first} {second}"))
+                            .collect()
+                    }
+                    Some(index) if index > 0 && !columns.is_empty() => {
+                        // Rows without some columns (e.g. heap ones) only leave trailing ones out
+                        let values = words[index..]
+                            .iter()
+                            .map(|word| word.parse::<u64>())
+                            .collect::<std::result::Result<Vec<_>, _>>()?;
+                        mem_info.categories.insert(
+                            words[..index].join(" "),
+                            columns.iter().cloned().zip(values).collect(),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            "App Summary" | "Objects" => {
+                let values = match section {
+                    "App Summary" => &mut mem_info.summary,
+                    _ => &mut mem_info.objects,
+                };
+                for groups in pair_regex.captures_iter(line) {
+                    values.insert(groups[1].trim().to_string(), groups[2].parse()?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mem_info)
+}
+
+/// Parses the output of `dumpsys package <package>`, keeping the permissions and enabled state of `user` (first user listed by default).
+///
+/// Only the first entry of `package` is considered, as updated system apps list their factory version afterwards.
+/// Sections (e.g. `runtime permissions:`) span the lines indented more than their heading.
+pub(crate) fn package_info(
+    output: &str,
+    package: &str,
+    user: Option<UserId>,
+) -> Result<Option<PackageInfo>> {
+    let heading = format!("Package [{package}]");
+    let Some(start) = output.find(&heading) else {
+        return Ok(None);
+    };
+    let block = &output[start..];
+    // Entry ends with the next package, or the next top level section
+    let end = block
+        .match_indices('\n')
+        .map(|(index, _)| index + 1)
+        .find(|index| {
+            let line = &block[*index..];
+            line.trim_start().starts_with("Package [")
+                || line.starts_with(|c: char| !c.is_whitespace())
+        })
+        .unwrap_or(block.len());
+    let block = &block[..end];
+
+    let uid_regex = regex::Regex::new(r"\b(?:userId|appId)=(\d+)")?;
+    let time_regex = regex::Regex::new(r"\b(firstInstallTime|lastUpdateTime)=([^\r\n]+)")?;
+    let signatures_regex = regex::Regex::new(r"\bsignatures:\[([^\]]*)\]")?;
+    let user_regex = regex::Regex::new(r"^User (\d+):")?;
+    let enabled_regex = regex::Regex::new(r"\benabled=(\d+)")?;
+
+    let mut info = PackageInfo {
+        package: package.to_string(),
+        uid: uid_regex
+            .captures(block)
+            .ok_or(RustADBError::RegexParsingError)?[1]
+            .parse()?,
+        // Packages of devices not listing users are enabled
+        enabled: true,
+        ..Default::default()
+    };
+    for groups in time_regex.captures_iter(block) {
+        let time = NaiveDateTime::parse_from_str(groups[2].trim(), "%Y-%m-%d %H:%M:%S").ok();
+        match &groups[1] {
+            "firstInstallTime" => info.first_install_time = time,
+            _ => info.last_update_time = time,
+        }
+    }
+    if let Some(groups) = signatures_regex.captures(block) {
+        info.signature_digests = groups[1]
+            .split(',')
+            .map(str::trim)
+            .filter(|digest| !digest.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+
+    let mut section = ("", 0);
+    let mut current_user = None;
+    let mut selected_user = user;
+    for line in block.lines().skip(1) {
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if indent <= section.1 {
+            section = ("", 0);
+        }
+
+        if let Some(groups) = user_regex.captures(trimmed) {
+            let id = UserId(groups[1].parse()?);
+            current_user = Some(id);
+            if *selected_user.get_or_insert(id) == id {
+                // Enabled state 0 is the default one, 1 enabled, and higher ones disabled
+                info.enabled = enabled_regex.captures(trimmed).map_or(Ok(true), |groups| {
+                    groups[1].parse::<u8>().map(|state| state <= 1)
+                })?;
+            }
+            continue;
+        }
+        if trimmed.ends_with(':') {
+            section = (trimmed, indent);
+            continue;
+        }
+
+        match section.0 {
+            "requested permissions:" => {
+                // Some versions append attributes, e.g. `, maxSdkVersion=18`
+                let name = trimmed.split(',').next().unwrap_or(trimmed);
+                info.requested_permissions.push(name.trim().to_string());
+            }
+            "install permissions:" | "runtime permissions:" => {
+                // Runtime permissions are listed for each user
+                if section.0 == "runtime permissions:" && current_user != selected_user {
+                    continue;
+                }
+                if let Some((name, state)) = trimmed.split_once(':') {
+                    let granted = state.contains("granted=true");
+                    info.permissions.insert(name.trim().to_string(), granted);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Some(info))
+}
+
+/// Parses the output of `dumpsys dropbox --print`: entries separated by lines of `=`, each starting with
+/// a `YYYY-MM-DD hh:mm:ss <tag> (<kind>, <size> bytes)` line.
+///
+/// Returns the local time, tag and content of each entry.
+pub(crate) fn dropbox_entries(output: &str) -> Result<Vec<(NaiveDateTime, String, String)>> {
+    let header_regex = regex::Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}) (\S+) \(.*\)$")?;
+
+    let mut entries: Vec<(NaiveDateTime, String, String)> = vec![];
+    let mut separated = false;
+    for line in output.lines() {
+        if line.len() >= 40 && line.bytes().all(|c| c == b'=') {
+            separated = true;
+            continue;
+        }
+
+        match (separated, header_regex.captures(line), entries.last_mut()) {
+            (true, Some(groups), _) => entries.push((
+                NaiveDateTime::parse_from_str(&groups[1], "%Y-%m-%d %H:%M:%S")
+                    .map_err(|_| malformed("dropbox entry", line.as_bytes()))?,
+                groups[2].to_string(),
+                String::new(),
+            )),
+            (false, _, Some((_, _, content))) => {
+                content.push_str(line);
+                content.push('\n');
+            }
+            _ => {}
+        }
+        separated = false;
+    }
+
+    Ok(entries)
+}
+
+/// Parses the XML written by `uiautomator dump`, made of `node` elements nested in a `hierarchy` root element.
+///
+/// Every element becomes a [UiNode], the root one included, named after its `class` attribute or its tag.
+/// Only what uiautomator writes is supported: no comments, CDATA or text content.
+pub(crate) fn ui_hierarchy(xml: &str) -> Result<UiNode> {
+    let invalid =
+        |what: &str| RustADBError::UnknownResponseType(format!("Invalid UI dump: {what}"));
+
+    let mut stack: Vec<UiNode> = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        // Prolog, e.g. <?xml version='1.0' encoding='UTF-8' standalone='yes' ?>
+        if rest.starts_with('?') {
+            let end = rest
+                .find("?>")
+                .ok_or_else(|| invalid("unterminated prolog"))?;
+            rest = &rest[end + 2..];
+            continue;
+        }
+
+        if let Some(closing) = rest.strip_prefix('/') {
+            let end = closing
+                .find('>')
+                .ok_or_else(|| invalid("unterminated tag"))?;
+            rest = &closing[end + 1..];
+            let node = stack
+                .pop()
+                .ok_or_else(|| invalid("unexpected closing tag"))?;
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => return Ok(node),
+            }
+            continue;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .ok_or_else(|| invalid("unterminated tag"))?;
+        let mut node = UiNode {
+            class: rest[..name_end].to_string(),
+            ..Default::default()
+        };
+        rest = &rest[name_end..];
+
+        // Attributes, until the end of the tag
+        let self_closing = loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix("/>") {
+                rest = after;
+                break true;
+            }
+            if let Some(after) = rest.strip_prefix('>') {
+                rest = after;
+                break false;
+            }
+
+            let (name, after) = rest.split_once('=').ok_or_else(|| invalid("attribute"))?;
+            let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'');
+            let quote = quote.ok_or_else(|| invalid("attribute"))?;
+            let (value, after) = after[1..]
+                .split_once(quote)
+                .ok_or_else(|| invalid("unterminated attribute"))?;
+            node.attributes
+                .insert(name.trim().to_string(), xml_unescape(value)?);
+            rest = after;
+        };
+
+        for (name, field) in [
+            ("class", &mut node.class),
+            ("package", &mut node.package),
+            ("resource-id", &mut node.resource_id),
+            ("text", &mut node.text),
+            ("content-desc", &mut node.content_desc),
+        ] {
+            if let Some(value) = node.attributes.get(name) {
+                field.clone_from(value);
+            }
+        }
+        if let Some(bounds) = node.attributes.get("bounds") {
+            node.bounds = bounds.parse()?;
+        }
+
+        match (self_closing, stack.last_mut()) {
+            (false, _) => stack.push(node),
+            (true, Some(parent)) => parent.children.push(node),
+            (true, None) => return Ok(node),
+        }
+    }
+
+    Err(invalid("unterminated hierarchy"))
+}
+
+/// Replaces XML entities and character references of `value`.
+fn xml_unescape(value: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let (entity, after) = rest[start + 1..]
+            .split_once(';')
+            .ok_or_else(|| malformed("XML entity", rest.as_bytes()))?;
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix('#') {
+                Some(code) => match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => code.parse().ok(),
+                }
+                .and_then(char::from_u32),
+                None => None,
+            },
+        };
+        unescaped.push(character.ok_or_else(|| malformed("XML entity", entity.as_bytes()))?);
+        rest = after;
+    }
+    unescaped.push_str(rest);
+
+    Ok(unescaped)
+}
+
+/// First `size` bytes of `input`, failing if it is shorter.
+fn take<'a>(input: &'a [u8], size: usize, what: &str) -> Result<&'a [u8]> {
+    input.get(..size).ok_or_else(|| {
+        RustADBError::UnknownResponseType(format!(
+            "Truncated {what}: {size} bytes expected, {} received",
+            input.len()
+        ))
+    })
+}
+
+fn malformed(what: &str, input: &[u8]) -> RustADBError {
+    RustADBError::UnknownResponseType(format!("Invalid {what} {}", input.escape_ascii()))
+}
+
+fn group(groups: &regex::bytes::Captures, index: usize) -> Result<String> {
+    let value = groups.get(index).ok_or(RustADBError::RegexParsingError)?;
+    Ok(String::from_utf8(value.as_bytes().to_vec())?)
+}
+
+fn named_group(groups: &regex::bytes::Captures, name: &str) -> Result<String> {
+    let value = groups.name(name).ok_or(RustADBError::RegexParsingError)?;
+    Ok(String::from_utf8(value.as_bytes().to_vec())?)
+}
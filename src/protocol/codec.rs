@@ -0,0 +1,286 @@
+//! Encoding and decoding of ADB protocols framing on byte slices, without any IO.
+//!
+//! Only `core` and `alloc` are used here, so that hosts without `std` (e.g. embedded gadgets) can reuse this logic
+//! and feed it bytes from their own transport. Covers requests to the ADB server, sync and shell v2 framing,
+//! and messages exchanged with adbd (e.g. over USB), with their checksums.
+
+use alloc::{format, vec::Vec};
+use core::fmt::Display;
+
+use crate::AdbStatResponse;
+
+/// Maximum payload length accepted in shell v2 packets, far above what adbd sends, to avoid huge allocations on corrupted headers.
+pub const MAX_SHELL_PAYLOAD_LENGTH: usize = 1024 * 1024;
+
+/// Maximum length accepted for names of directory entries, `PATH_MAX` on Linux.
+pub const MAX_DENT_NAME_LENGTH: usize = 4096;
+
+/// Size of the header of messages exchanged with adbd.
+pub const MESSAGE_HEADER_SIZE: usize = 24;
+
+/// Size of a sync `LST2` or `STA2` response following its id.
+pub const SYNC_STAT_V2_SIZE: usize = 68;
+
+/// Size of a sync `DNT2` response following its id: a `LST2` response followed by the length of the name.
+pub const SYNC_DENT_V2_SIZE: usize = SYNC_STAT_V2_SIZE + 4;
+
+/// Represents an error decoding bytes received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Input is shorter than `expected` bytes.
+    Truncated {
+        /// What was being decoded.
+        what: &'static str,
+        /// Number of bytes expected.
+        expected: usize,
+        /// Number of bytes received.
+        received: usize,
+    },
+    /// Input is not valid.
+    Malformed {
+        /// What was being decoded.
+        what: &'static str,
+        /// Bytes received.
+        input: Vec<u8>,
+    },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Truncated {
+                what,
+                expected,
+                received,
+            } => write!(
+                f,
+                "Truncated {what}: {expected} bytes expected, {received} received"
+            ),
+            DecodeError::Malformed { what, input } => {
+                write!(f, "Invalid {what} {}", input.escape_ascii())
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Represents the header of a message exchanged with adbd, followed by `data_length` bytes of data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageHeader {
+    /// Command of the message (e.g. `CNXN`, `OPEN`, `WRTE`).
+    pub command: [u8; 4],
+    /// First argument, depending on the command (e.g. local stream id).
+    pub arg0: u32,
+    /// Second argument, depending on the command (e.g. remote stream id).
+    pub arg1: u32,
+    /// Length of the data following the header.
+    pub data_length: u32,
+    /// Checksum of the data, see [checksum].
+    pub data_check: u32,
+}
+
+impl MessageHeader {
+    /// Builds the header of a message carrying `data`.
+    pub fn new(command: &[u8; 4], arg0: u32, arg1: u32, data: &[u8]) -> Self {
+        Self {
+            command: *command,
+            arg0,
+            arg1,
+            data_length: data.len() as u32,
+            data_check: checksum(data),
+        }
+    }
+
+    /// Encodes the header, ending with the magic value (`command` with all bits flipped).
+    pub fn encode(&self) -> [u8; MESSAGE_HEADER_SIZE] {
+        let command = u32::from_le_bytes(self.command);
+        let mut header = [0_u8; MESSAGE_HEADER_SIZE];
+        for (index, value) in [
+            command,
+            self.arg0,
+            self.arg1,
+            self.data_length,
+            self.data_check,
+            command ^ u32::MAX,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            header[index * 4..index * 4 + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        header
+    }
+
+    /// Decodes a header, checking its magic value.
+    pub fn decode(input: &[u8]) -> Result<Self, DecodeError> {
+        let header = take(input, MESSAGE_HEADER_SIZE, "message header")?;
+        let command = read_u32(&header[0..4]);
+        if read_u32(&header[20..24]) != command ^ u32::MAX {
+            return Err(malformed("message header", header));
+        }
+
+        Ok(Self {
+            command: command.to_le_bytes(),
+            arg0: read_u32(&header[4..8]),
+            arg1: read_u32(&header[8..12]),
+            data_length: read_u32(&header[12..16]),
+            data_check: read_u32(&header[16..20]),
+        })
+    }
+
+    /// Whether `data` is the one announced by the header, with a matching length and checksum.
+    ///
+    /// Recent adbd versions send a zero checksum, which is not checked.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() == self.data_length as usize
+            && (self.data_check == 0 || self.data_check == checksum(data))
+    }
+}
+
+/// Checksum of the data of messages exchanged with adbd: the sum of its bytes.
+pub fn checksum(data: &[u8]) -> u32 {
+    data.iter()
+        .fold(0_u32, |sum, byte| sum.wrapping_add(u32::from(*byte)))
+}
+
+/// Encodes a request to the ADB server: the length of `service` as 4 hexadecimal digits, followed by `service`.
+pub fn encode_request(service: &str) -> Vec<u8> {
+    format!("{:04x}{service}", service.len()).into_bytes()
+}
+
+/// Decodes the 4 hexadecimal digits prefixing the body of ADB server responses.
+pub fn decode_length(input: &[u8]) -> Result<usize, DecodeError> {
+    let digits = take(input, 4, "length")?;
+
+    digits.iter().try_fold(0, |length, digit| {
+        let value = char::from(*digit)
+            .to_digit(16)
+            .ok_or_else(|| malformed("length", input))?;
+        Ok(length * 16 + value as usize)
+    })
+}
+
+/// Encodes a sync packet header: 4 bytes id followed by a 32-bit little-endian value, usually the length of the payload.
+pub fn encode_sync_header(id: &[u8; 4], value: u32) -> [u8; 8] {
+    let mut header = [0_u8; 8];
+    header[..4].copy_from_slice(id);
+    header[4..].copy_from_slice(&value.to_le_bytes());
+
+    header
+}
+
+/// Decodes a sync packet header: 4 bytes id followed by a 32-bit little-endian value.
+pub fn decode_sync_header(input: &[u8]) -> Result<([u8; 4], u32), DecodeError> {
+    let header = take(input, 8, "sync packet header")?;
+
+    let mut id = [0_u8; 4];
+    id.copy_from_slice(&header[..4]);
+    Ok((id, read_u32(&header[4..8])))
+}
+
+/// Decodes the 12 bytes following the id of a sync `STAT` response: mode, size and modification time.
+pub fn decode_sync_stat(input: &[u8]) -> Result<AdbStatResponse, DecodeError> {
+    let stat = take(input, 12, "stat response")?;
+
+    Ok(AdbStatResponse {
+        file_perm: read_u32(&stat[0..4]),
+        file_size: read_u32(&stat[4..8]).into(),
+        mod_time: read_u32(&stat[8..12]).into(),
+    })
+}
+
+/// Decodes the 16 bytes following the id of a sync `DENT` response: the metadata of the entry, and the length of the name that follows.
+pub fn decode_sync_dent(input: &[u8]) -> Result<(AdbStatResponse, usize), DecodeError> {
+    let dent = take(input, 16, "directory entry")?;
+
+    let name_length = read_u32(&dent[12..16]) as usize;
+    if name_length > MAX_DENT_NAME_LENGTH {
+        return Err(malformed("directory entry", dent));
+    }
+
+    Ok((decode_sync_stat(&dent[..12])?, name_length))
+}
+
+/// Decodes the 68 bytes following the id of a sync `LST2` or `STA2` response (`stat_v2`): the error code (0 when the metadata
+/// could be read) and the metadata, with 64-bit size and modification time.
+///
+/// Device, inode, links, owner, group, access and change times are skipped. Modification times before Unix epoch are clamped.
+pub fn decode_sync_stat_v2(input: &[u8]) -> Result<(u32, AdbStatResponse), DecodeError> {
+    let stat = take(input, SYNC_STAT_V2_SIZE, "stat response v2")?;
+
+    let mod_time = i64::from_le_bytes(read_array(&stat[52..60]));
+    Ok((
+        read_u32(&stat[0..4]),
+        AdbStatResponse {
+            file_perm: read_u32(&stat[20..24]),
+            file_size: u64::from_le_bytes(read_array(&stat[36..44])),
+            mod_time: mod_time.max(0) as u64,
+        },
+    ))
+}
+
+/// Decodes the 72 bytes following the id of a sync `DNT2` response (`ls_v2`): the error code of the entry,
+/// its metadata as [decode_sync_stat_v2], and the length of the name that follows.
+pub fn decode_sync_dent_v2(input: &[u8]) -> Result<(u32, AdbStatResponse, usize), DecodeError> {
+    let dent = take(input, SYNC_DENT_V2_SIZE, "directory entry v2")?;
+
+    let name_length = read_u32(&dent[SYNC_STAT_V2_SIZE..]) as usize;
+    if name_length > MAX_DENT_NAME_LENGTH {
+        return Err(malformed("directory entry v2", dent));
+    }
+
+    let (error, stat) = decode_sync_stat_v2(&dent[..SYNC_STAT_V2_SIZE])?;
+    Ok((error, stat, name_length))
+}
+
+/// Encodes a shell v2 packet header: packet id and little-endian payload length.
+pub fn encode_shell_packet_header(id: u8, length: u32) -> [u8; 5] {
+    let mut header = [id, 0, 0, 0, 0];
+    header[1..].copy_from_slice(&length.to_le_bytes());
+
+    header
+}
+
+/// Decodes a shell v2 packet header: packet id and little-endian payload length.
+pub fn decode_shell_packet_header(input: &[u8]) -> Result<(u8, usize), DecodeError> {
+    let header = take(input, 5, "shell packet header")?;
+
+    let length = read_u32(&header[1..5]) as usize;
+    if length > MAX_SHELL_PAYLOAD_LENGTH {
+        return Err(malformed("shell packet header", header));
+    }
+
+    Ok((header[0], length))
+}
+
+/// First `size` bytes of `input`, failing if it is shorter.
+fn take<'a>(input: &'a [u8], size: usize, what: &'static str) -> Result<&'a [u8], DecodeError> {
+    input.get(..size).ok_or(DecodeError::Truncated {
+        what,
+        expected: size,
+        received: input.len(),
+    })
+}
+
+fn malformed(what: &'static str, input: &[u8]) -> DecodeError {
+    DecodeError::Malformed {
+        what,
+        input: input.to_vec(),
+    }
+}
+
+/// Reads a little-endian 32-bit value from exactly 4 bytes.
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut value = [0_u8; 4];
+    value.copy_from_slice(bytes);
+    u32::from_le_bytes(value)
+}
+
+/// Copies exactly `N` bytes into an array, e.g. to read a little-endian 64-bit value.
+fn read_array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut value = [0_u8; N];
+    value.copy_from_slice(bytes);
+    value
+}
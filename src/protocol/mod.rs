@@ -0,0 +1,5 @@
+//! Low-level framing of ADB protocols, for advanced users implementing services not provided by this crate.
+
+pub mod codec;
+pub mod service;
+pub mod sync;
@@ -0,0 +1,85 @@
+//! Framing of ADB services, used by requests sent to the ADB server or to devices (e.g. `host:version`, `shell:`).
+//!
+//! Once a service is opened, its response is either raw data until the connection is closed (e.g. `shell:`),
+//! or a body prefixed with its length as 4 hexadecimal digits (e.g. `host:devices`), read with
+//! [ServiceStream::read_length_prefixed]. Some services send another status once done (e.g. `host:forward`),
+//! read with [ServiceStream::read_status].
+
+use std::{
+    io::{Read, Write},
+    str::{self, FromStr},
+};
+
+use crate::{adb_stream::AdbStream, models::AdbRequestStatus, parser, Result, RustADBError};
+
+/// Represents a connection carrying an opened service, returned by [AdbTcpConnexion::open_service](crate::AdbTcpConnexion::open_service)
+/// and [AdbTcpConnexion::open_host_service](crate::AdbTcpConnexion::open_host_service).
+#[derive(Debug)]
+pub struct ServiceStream<'a> {
+    stream: &'a mut AdbStream,
+}
+
+impl<'a> ServiceStream<'a> {
+    pub(crate) fn new(stream: &'a mut AdbStream) -> Self {
+        Self { stream }
+    }
+
+    /// Reads a status sent by the ADB server, returning the error matching its message on `FAIL`.
+    pub fn read_status(&mut self) -> Result<()> {
+        read_status(self.stream)
+    }
+
+    /// Reads a body prefixed with its length as 4 hexadecimal digits.
+    pub fn read_length_prefixed(&mut self) -> Result<Vec<u8>> {
+        read_length_prefixed(self.stream)
+    }
+
+    /// Reads everything sent until the connection is closed.
+    pub fn read_to_end(&mut self) -> Result<Vec<u8>> {
+        let mut output = vec![];
+        self.stream.read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+}
+
+impl Read for ServiceStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for ServiceStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Reads a status code, returning the error matching the message that follows `FAIL`.
+pub(crate) fn read_status<R: Read>(reader: &mut R) -> Result<()> {
+    let mut status = [0; 4];
+    reader.read_exact(&mut status)?;
+
+    match AdbRequestStatus::from_str(str::from_utf8(&status)?)? {
+        AdbRequestStatus::Fail => {
+            let message = read_length_prefixed(reader)?;
+            Err(RustADBError::from_fail_message(String::from_utf8(message)?))
+        }
+        AdbRequestStatus::Okay => Ok(()),
+    }
+}
+
+/// Reads a body prefixed with its length as 4 hexadecimal digits.
+pub(crate) fn read_length_prefixed<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+
+    let mut body = vec![0; parser::hex_length(&length)?];
+    reader.read_exact(&mut body)?;
+
+    Ok(body)
+}
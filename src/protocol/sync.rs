@@ -0,0 +1,94 @@
+//! Framing of the sync protocol, used by file transfer services (e.g. `SEND`, `RECV`, `STAT`).
+//!
+//! Most sync packets are made of a 4 bytes id, a 32-bit little-endian length and a payload of that length.
+//! A few packets use the 32-bit value for something else (e.g. the modification time sent with `DONE`),
+//! or have a fixed layout (e.g. `STAT` responses), they can be handled with [SyncSession::send_header],
+//! [SyncSession::read_header] and [SyncSession::read_exact].
+
+use std::io::{Read, Write};
+
+use super::codec;
+use crate::{adb_stream::AdbStream, parser, Result, RustADBError};
+
+/// Maximum payload size of sync packets, as accepted by adbd.
+pub const MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+
+/// Represents a sync packet: its id and its payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncPacket {
+    /// Id of the packet (e.g. `DATA`, `DONE`, `FAIL`).
+    pub id: [u8; 4],
+    /// Payload of the packet.
+    pub payload: Vec<u8>,
+}
+
+/// Represents a connection switched to sync mode, opened with [AdbTcpConnexion::sync_session](crate::AdbTcpConnexion::sync_session).
+#[derive(Debug)]
+pub struct SyncSession<'a> {
+    stream: &'a mut AdbStream,
+}
+
+impl<'a> SyncSession<'a> {
+    pub(crate) fn new(stream: &'a mut AdbStream) -> Self {
+        Self { stream }
+    }
+
+    /// Sends a packet made of `id`, the little-endian length of `payload` and `payload`.
+    ///
+    /// Requests are sent this way with the remote path as payload (e.g. `STAT` followed by the path to stat).
+    pub fn send_packet(&mut self, id: &[u8; 4], payload: &[u8]) -> Result<()> {
+        write_packet(self.stream, id, payload)
+    }
+
+    /// Sends a packet made of `id` and a 32-bit little-endian `value` which is not followed by a payload.
+    pub fn send_header(&mut self, id: &[u8; 4], value: u32) -> Result<()> {
+        write_header(self.stream, id, value)
+    }
+
+    /// Reads a packet made of an id, a little-endian payload length and the payload.
+    ///
+    /// Payloads larger than [MAX_PAYLOAD_SIZE] are rejected, as they mean the packet is not framed this way.
+    pub fn read_packet(&mut self) -> Result<SyncPacket> {
+        let (id, length) = self.read_header()?;
+        let length = length as usize;
+        if length > MAX_PAYLOAD_SIZE {
+            return Err(RustADBError::UnknownResponseType(format!(
+                "sync packet {} of {length} bytes",
+                id.escape_ascii()
+            )));
+        }
+
+        let mut payload = vec![0; length];
+        self.stream.read_exact(&mut payload)?;
+
+        Ok(SyncPacket { id, payload })
+    }
+
+    /// Reads the id of a packet and the following 32-bit little-endian value, leaving anything after them unread.
+    pub fn read_header(&mut self) -> Result<([u8; 4], u32)> {
+        let mut header = [0_u8; 8];
+        self.stream.read_exact(&mut header)?;
+
+        parser::sync_header(&header)
+    }
+
+    /// Reads exactly `buf.len()` bytes, for packets with a fixed layout.
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        Ok(self.stream.read_exact(buf)?)
+    }
+}
+
+/// Writes a packet made of `id`, the little-endian length of `payload` and `payload`.
+pub(crate) fn write_packet<W: Write>(writer: &mut W, id: &[u8; 4], payload: &[u8]) -> Result<()> {
+    write_header(writer, id, payload.len() as u32)?;
+    writer.write_all(payload)?;
+
+    Ok(())
+}
+
+/// Writes a packet header made of `id` and a 32-bit little-endian `value`.
+pub(crate) fn write_header<W: Write>(writer: &mut W, id: &[u8; 4], value: u32) -> Result<()> {
+    writer.write_all(&codec::encode_sync_header(id, value))?;
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::{
+    fs::File,
+    io::{self, ErrorKind, Read, Take},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Result, RustADBError};
+
+/// Quotes `value` so that it is interpreted as a single word by the device shell.
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Reader of exactly `size` bytes of another one, failing if it ends before.
+///
+/// Commands told the size of their input (e.g. `pm install -S`) would otherwise wait forever for the rest of it.
+pub(crate) struct ExactReader<R> {
+    inner: Take<R>,
+}
+
+impl<R: Read> ExactReader<R> {
+    pub(crate) fn new(inner: R, size: u64) -> Self {
+        Self {
+            inner: inner.take(size),
+        }
+    }
+}
+
+impl<R: Read> Read for ExactReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read == 0 && !buf.is_empty() && self.inner.limit() > 0 {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "input ended {} bytes before its announced size",
+                    self.inner.limit()
+                ),
+            ));
+        }
+
+        Ok(read)
+    }
+}
+
+/// Opens the local file located at `path`, failing with [RustADBError::LocalFileNotFound] if it does not exist.
+pub(crate) fn open_local_file<P: AsRef<Path>>(path: P) -> Result<File> {
+    File::open(path.as_ref()).map_err(|error| match error.kind() {
+        ErrorKind::NotFound => RustADBError::LocalFileNotFound(path.as_ref().to_path_buf()),
+        _ => error.into(),
+    })
+}
+
+/// Seconds since Unix epoch of `time`, as 32-bit timestamps of the sync protocol.
+pub(crate) fn sync_timestamp(time: SystemTime) -> Result<u32> {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| RustADBError::InvalidTimestamp("time before Unix epoch".to_string()))?
+        .as_secs();
+
+    u32::try_from(seconds).map_err(|_| {
+        RustADBError::InvalidTimestamp(format!(
+            "{seconds} seconds since Unix epoch overflows 32 bits"
+        ))
+    })
+}